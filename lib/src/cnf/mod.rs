@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 #[cfg(feature = "parallel")]
 // Specifies how many concurrent jobs can be buffered in the worker channel.
 pub const MAX_CONCURRENT_TASKS: usize = 64;
@@ -5,6 +7,18 @@ pub const MAX_CONCURRENT_TASKS: usize = 64;
 // Specifies how many subqueries will be processed recursively before the query fails.
 pub const MAX_RECURSIVE_QUERIES: usize = 16;
 
+// Specifies how many schema definitions are cached within each transaction.
+pub const TRANSACTION_CACHE_SIZE: usize = 1000;
+
+// Specifies how long a transaction waits to acquire a table write lock before the lock fails.
+pub const TABLE_LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+// Specifies how long a table write lock is held across nodes if it is not released.
+pub const TABLE_LOCK_LEASE: Duration = Duration::from_secs(60);
+
+// Specifies how long the sentinel of an open transaction is kept across nodes if it is not renewed.
+pub const TRANSACTION_SENTINEL_LEASE: Duration = Duration::from_secs(60);
+
 // The characters which are supported in server record IDs.
 pub const ID_CHARS: [char; 36] = [
 	'0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i',
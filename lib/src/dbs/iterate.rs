@@ -33,13 +33,9 @@ impl Iterable {
 					// Check that the table exists
 					txn.lock().await.check_ns_db_tb(opt.ns(), opt.db(), &v.tb, opt.strict).await?;
 					// Fetch the data from the store
-					let key = thing::new(opt.ns(), opt.db(), &v.tb, &v.id);
-					let val = txn.clone().lock().await.get(key).await?;
+					let val = txn.clone().lock().await.get_record(opt.ns(), opt.db(), &v).await?;
 					// Parse the data from the store
-					let val = Operable::Value(match val {
-						Some(v) => Value::from(v),
-						None => Value::None,
-					});
+					let val = Operable::Value(val);
 					// Process the document record
 					ite.process(ctx, opt, txn, stm, Some(v), val).await;
 				}
@@ -47,13 +43,7 @@ impl Iterable {
 					// Check that the table exists
 					txn.lock().await.check_ns_db_tb(opt.ns(), opt.db(), &v.tb, opt.strict).await?;
 					// Fetch the data from the store
-					let key = thing::new(opt.ns(), opt.db(), &v.tb, &v.id);
-					let val = txn.clone().lock().await.get(key).await?;
-					// Parse the data from the store
-					let x = match val {
-						Some(v) => Value::from(v),
-						None => Value::None,
-					};
+					let x = txn.clone().lock().await.get_record(opt.ns(), opt.db(), &v).await?;
 					// Create a new operable value
 					let val = Operable::Mergeable(x, o);
 					// Process the document record
@@ -63,13 +53,7 @@ impl Iterable {
 					// Check that the table exists
 					txn.lock().await.check_ns_db_tb(opt.ns(), opt.db(), &v.tb, opt.strict).await?;
 					// Fetch the data from the store
-					let key = thing::new(opt.ns(), opt.db(), &v.tb, &v.id);
-					let val = txn.clone().lock().await.get(key).await?;
-					// Parse the data from the store
-					let x = match val {
-						Some(v) => Value::from(v),
-						None => Value::None,
-					};
+					let x = txn.clone().lock().await.get_record(opt.ns(), opt.db(), &v).await?;
 					// Create a new operable value
 					let val = Operable::Relatable(f, x, w);
 					// Process the document record
@@ -91,10 +75,12 @@ impl Iterable {
 						}
 						// Get the next 1000 key-value entries
 						let res = match nxt {
+							// Only the first batch counts as a table access
 							None => {
 								let min = beg.clone();
 								let max = end.clone();
-								txn.clone().lock().await.scan(min..max, 1000).await?
+								let mut run = txn.lock().await;
+								run.scan_records(opt.ns(), opt.db(), &v, min..max, 1000).await?
 							}
 							Some(ref mut beg) => {
 								beg.push(0x00);
@@ -12,7 +12,7 @@ impl<'a> Document<'a> {
 		ctx: &Context<'_>,
 		opt: &Options,
 		txn: &Transaction,
-		_stm: &Statement<'_>,
+		stm: &Statement<'_>,
 	) -> Result<(), Error> {
 		// Check if forced
 		if !opt.force && !self.changed() {
@@ -46,14 +46,17 @@ impl<'a> Document<'a> {
 			if opt.force || o != n {
 				match ix.uniq {
 					true => {
-						// Delete the old index data
+						// Delete the old index data, if it belongs to this record
 						if self.initial.is_some() {
 							#[rustfmt::skip]
 							let key = crate::key::index::new(opt.ns(), opt.db(), &ix.what, &ix.name, o, None);
-							run.delc(key, Some(rid)).await?;
+							match run.delc(key, Some(rid)).await {
+								Err(Error::TxConditionNotMet) => (),
+								res => res?,
+							}
 						}
 						// Create the new index data
-						if self.current.is_some() {
+						if self.current.is_some() && !stm.is_delete() {
 							#[rustfmt::skip]
 							let key = crate::key::index::new(opt.ns(), opt.db(), &ix.what, &ix.name, n, None);
 							if run.putc(key, rid, None).await.is_err() {
@@ -69,10 +72,10 @@ impl<'a> Document<'a> {
 						if self.initial.is_some() {
 							#[rustfmt::skip]
 							let key = crate::key::index::new(opt.ns(), opt.db(), &ix.what, &ix.name, o, Some(&rid.id));
-							run.delc(key, Some(rid)).await?;
+							run.del(key).await?;
 						}
 						// Create the new index data
-						if self.current.is_some() {
+						if self.current.is_some() && !stm.is_delete() {
 							#[rustfmt::skip]
 							let key = crate::key::index::new(opt.ns(), opt.db(), &ix.what, &ix.name, n, Some(&rid.id));
 							if run.putc(key, rid, None).await.is_err() {
@@ -4,15 +4,60 @@ use crate::dbs::Statement;
 use crate::dbs::Transaction;
 use crate::doc::Document;
 use crate::err::Error;
+use crate::kvs::LiveAction;
+use crate::sql::value::Value;
 
 impl<'a> Document<'a> {
 	pub async fn lives(
 		&self,
-		_ctx: &Context<'_>,
-		_opt: &Options,
-		_txn: &Transaction,
-		_stm: &Statement<'_>,
+		ctx: &Context<'_>,
+		opt: &Options,
+		txn: &Transaction,
+		stm: &Statement<'_>,
 	) -> Result<(), Error> {
+		// Check if changed
+		if !self.changed() {
+			return Ok(());
+		}
+		// Get the record id
+		let rid = self.id.as_ref().unwrap();
+		// Get the query action
+		let act = if stm.is_delete() {
+			LiveAction::Delete
+		} else if self.is_new() {
+			LiveAction::Create
+		} else {
+			LiveAction::Update
+		};
+		// Get the record to match against
+		let doc = match act {
+			LiveAction::Delete => &self.initial,
+			_ => &self.current,
+		};
+		// Loop through all live query statements
+		for lv in self.lv(opt, txn).await?.iter() {
+			// Skip paused live queries
+			if lv.paused {
+				continue;
+			}
+			// Skip live queries without a subscription here
+			if !txn.lock().await.is_subscribed(&lv.id) {
+				continue;
+			}
+			// Check the live query condition
+			if let Some(cond) = &lv.cond {
+				if !cond.compute(ctx, opt, txn, Some(doc)).await?.is_truthy() {
+					continue;
+				}
+			}
+			// Queue the live query message
+			let res = match act {
+				LiveAction::Delete => Value::Thing(rid.clone()),
+				_ => self.current.as_ref().clone(),
+			};
+			txn.lock().await.notify(lv.id.0, act, res);
+		}
+		// Carry on
 		Ok(())
 	}
 }
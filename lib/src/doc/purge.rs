@@ -35,8 +35,7 @@ impl<'a> Document<'a> {
 		// Get the record id
 		let rid = self.id.as_ref().unwrap();
 		// Purge the record data
-		let key = crate::key::thing::new(opt.ns(), opt.db(), &rid.tb, &rid.id);
-		run.del(key).await?;
+		run.del_record(opt.ns(), opt.db(), rid).await?;
 		// Purge the record edges
 		match (self.initial.pick(&*IN), self.initial.pick(&*OUT)) {
 			(Value::Thing(ref l), Value::Thing(ref r)) => {
@@ -1,4 +1,6 @@
 use crate::sql::idiom::Idiom;
+use crate::sql::thing::Thing;
+use jsonwebtoken::errors::Error as JWTError;
 use msgpack::encode::Error as SerdeError;
 use serde::Serialize;
 use storekey::decode::Error as DecodeError;
@@ -37,6 +39,30 @@ pub enum Error {
 	#[error("The key being inserted already exists")]
 	TxKeyAlreadyExists,
 
+	/// The key being written in the transaction is empty
+	#[error("The key being written is empty")]
+	TxKeyEmpty,
+
+	/// A transaction operation did not complete within the timeout
+	#[error("The transaction operation exceeded the timeout")]
+	TxTimedOut,
+
+	/// The transaction was killed by another transaction
+	#[error("The transaction was killed")]
+	TxKilled,
+
+	/// The transaction to be killed is not open on the datastore
+	#[error("The transaction does not exist")]
+	TxNotFound,
+
+	/// The savepoint does not exist, or has already been released
+	#[error("The savepoint does not exist or has been released")]
+	InvalidSavepoint,
+
+	/// The connection to the underlying datastore was lost
+	#[error("The connection to the underlying datastore was lost")]
+	ConnectionLost,
+
 	/// No namespace has been selected
 	#[error("Specify a namespace to use")]
 	NsEmpty,
@@ -57,6 +83,10 @@ pub enum Error {
 		sql: String,
 	},
 
+	/// There was an error when importing a database export
+	#[error("Import failed on line {0}: {1}")]
+	ImportError(usize, String),
+
 	/// There was an error with the provided JSON Patch
 	#[error("The JSON Patch contains invalid operations. {message}")]
 	InvalidPatch {
@@ -108,6 +138,10 @@ pub enum Error {
 		db: String,
 	},
 
+	/// The token could not be verified with any of the token definitions
+	#[error("The token could not be verified")]
+	InvalidToken,
+
 	/// The requested namespace does not exist
 	#[error("The namespace does not exist")]
 	NsNotFound,
@@ -140,10 +174,36 @@ pub enum Error {
 	#[error("The scope token does not exist")]
 	StNotFound,
 
+	/// The table is locked for writing by another transaction
+	#[error("The table is locked for writing by another transaction")]
+	TableLocked,
+
+	/// The table write lock could not be acquired within the timeout
+	#[error("The table write lock could not be acquired within the timeout")]
+	TableLockTimeout,
+
+	/// The table write lock held by this transaction expired before it could be renewed
+	#[error("The table write lock held by this transaction has expired")]
+	TableLockExpired,
+
 	/// The requested table does not exist
 	#[error("The table does not exist")]
 	TbNotFound,
 
+	/// The requested index does not exist
+	#[error("The index does not exist")]
+	IxNotFound,
+
+	/// The requested live query does not exist
+	#[error("The live query does not exist")]
+	LvNotFound,
+
+	/// The index is referenced by live queries or table views
+	#[error("The index is referenced by {}", .referenced_by.join(", "))]
+	IndexInUse {
+		referenced_by: Vec<String>,
+	},
+
 	/// Unable to perform the realtime query
 	#[error("Unable to perform the realtime query")]
 	RealtimeDisabled,
@@ -194,6 +254,12 @@ pub enum Error {
 		value: String,
 	},
 
+	/// The live query condition can not be evaluated outside of a query
+	#[error("Can not evaluate the live query condition '{value}' outside of a query")]
+	LiveCondition {
+		value: String,
+	},
+
 	/// The permissions do not allow this query to be run on this table
 	#[error("You don't have permission to run this query on the `{table}` table")]
 	TablePermissions {
@@ -206,12 +272,24 @@ pub enum Error {
 		table: String,
 	},
 
+	/// The specified table can not be refreshed as it is not setup as a foreign table view
+	#[error("Unable to refresh the `{table}` table as it is not setup as a view")]
+	TableIsNotView {
+		table: String,
+	},
+
 	/// A database entry for the specified record already exists
 	#[error("Database record `{thing}` already exists")]
 	RecordExists {
 		thing: String,
 	},
 
+	/// A database with the specified name already exists
+	#[error("The database `{db}` already exists")]
+	DbExists {
+		db: String,
+	},
+
 	/// A database index entry for the specified record already exists
 	#[error("Database index `{index}` already contains `{thing}`")]
 	IndexExists {
@@ -219,6 +297,31 @@ pub enum Error {
 		thing: String,
 	},
 
+	/// A unique database index can not be defined as existing records have duplicate values
+	#[error("Database index `{index}` can not be unique, as records have duplicate values: {duplicates}")]
+	IndexDuplicates {
+		index: String,
+		duplicates: String,
+	},
+
+	/// A unique database index contains multiple entries for the same value
+	#[error("The unique index contains multiple entries for the same value")]
+	DuplicateIndexValue,
+
+	/// The migration version is not newer than the current migration version
+	#[error("The migration version must be greater than the current migration version")]
+	MigrationVersionConflict,
+
+	/// There are no applied migrations which can be rolled back
+	#[error("There are no applied migrations to roll back")]
+	NoMigrationToRollback,
+
+	/// A migration script contained a statement which can not be run within a migration
+	#[error("The statement '{stm}' can not be run within a migration")]
+	MigrationStatement {
+		stm: String,
+	},
+
 	/// The specified field did not conform to the field ASSERT clause
 	#[error("Found '{value}' for field '{field}' but field must conform to: {check}")]
 	FieldValue {
@@ -227,6 +330,28 @@ pub enum Error {
 		check: String,
 	},
 
+	/// The specified field references a record which does not exist
+	#[error("Found '{missing}' for field '{field}' but the referenced record does not exist")]
+	ForeignKeyViolation {
+		field: Idiom,
+		missing: Thing,
+	},
+
+	/// The database has used more of a resource than its quota allows
+	#[error("The database has exceeded its {resource} quota of {limit}, with a usage of {actual}")]
+	QuotaExceeded {
+		resource: &'static str,
+		limit: u64,
+		actual: u64,
+	},
+
+	/// The start of a time window is outside of the range of supported datetimes
+	#[error("The time window starting {window} windows after '{from}' is out of range")]
+	WindowOutOfRange {
+		from: String,
+		window: u128,
+	},
+
 	/// Found a record id for the record but this is not a valid id
 	#[error("Found '{value}' for the record ID but this is not a valid id")]
 	IdInvalid {
@@ -252,6 +377,10 @@ pub enum Error {
 	/// Represents an error when decoding a key-value entry
 	#[error("Key decoding error: {0}")]
 	Decode(#[from] DecodeError),
+
+	/// Represents an error when encoding or decoding a token
+	#[error("JWT error: {0}")]
+	Jwt(#[from] JWTError),
 }
 
 impl From<Error> for String {
@@ -265,6 +394,7 @@ impl From<echodb::err::Error> for Error {
 	fn from(e: echodb::err::Error) -> Error {
 		match e {
 			echodb::err::Error::KeyAlreadyExists => Error::TxKeyAlreadyExists,
+			echodb::err::Error::ValNotExpectedValue => Error::TxConditionNotMet,
 			_ => Error::Tx(e.to_string()),
 		}
 	}
@@ -275,16 +405,25 @@ impl From<indxdb::err::Error> for Error {
 	fn from(e: indxdb::err::Error) -> Error {
 		match e {
 			indxdb::err::Error::KeyAlreadyExists => Error::TxKeyAlreadyExists,
+			indxdb::err::Error::ValNotExpectedValue => Error::TxConditionNotMet,
 			_ => Error::Tx(e.to_string()),
 		}
 	}
 }
 
+#[cfg(feature = "kv-rocksdb")]
+impl From<rocksdb::Error> for Error {
+	fn from(e: rocksdb::Error) -> Error {
+		Error::Tx(e.to_string())
+	}
+}
+
 #[cfg(feature = "kv-tikv")]
 impl From<tikv::Error> for Error {
 	fn from(e: tikv::Error) -> Error {
 		match e {
 			tikv::Error::DuplicateKeyInsertion => Error::TxKeyAlreadyExists,
+			tikv::Error::Grpc(_) => Error::ConnectionLost,
 			_ => Error::Tx(e.to_string()),
 		}
 	}
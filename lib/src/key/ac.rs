@@ -0,0 +1,55 @@
+use derive::Key;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Key)]
+pub struct Ac {
+	__: u8,
+	_a: u8,
+	pub ns: String,
+	_b: u8,
+	pub db: String,
+	_c: u8,
+	pub tb: String,
+	_d: u8,
+	_e: u8,
+	_f: u8,
+}
+
+pub fn new(ns: &str, db: &str, tb: &str) -> Ac {
+	Ac::new(ns.to_string(), db.to_string(), tb.to_string())
+}
+
+impl Ac {
+	pub fn new(ns: String, db: String, tb: String) -> Ac {
+		Ac {
+			__: 0x2f, // /
+			_a: 0x2a, // *
+			ns,
+			_b: 0x2a, // *
+			db,
+			_c: 0x2a, // *
+			tb,
+			_d: 0x21, // !
+			_e: 0x61, // a
+			_f: 0x63, // c
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn key() {
+		use super::*;
+		#[rustfmt::skip]
+		let val = Ac::new(
+			"test".to_string(),
+			"test".to_string(),
+			"test".to_string(),
+		);
+		let enc = Ac::encode(&val).unwrap();
+		assert_eq!(enc, b"/*test\x00*test\x00*test\x00!ac");
+		let dec = Ac::decode(&enc).unwrap();
+		assert_eq!(val, dec);
+	}
+}
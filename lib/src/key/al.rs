@@ -0,0 +1,87 @@
+use derive::Key;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Key)]
+pub struct Al {
+	__: u8,
+	_a: u8,
+	pub ns: String,
+	_b: u8,
+	pub db: String,
+	_c: u8,
+	_d: u8,
+	_e: u8,
+	pub op: String,
+	pub ts: u64,
+	pub node: String,
+	pub seq: u64,
+}
+
+pub fn new(ns: &str, db: &str, op: &str, ts: u64, node: &str, seq: u64) -> Al {
+	Al::new(ns.to_string(), db.to_string(), op.to_string(), ts, node.to_string(), seq)
+}
+
+pub fn prefix(ns: &str, db: &str, op: &str) -> Vec<u8> {
+	let mut k = super::database::new(ns, db).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x61, 0x6c]);
+	k.extend_from_slice(op.as_bytes());
+	k.push(0x00);
+	k
+}
+
+impl Al {
+	pub fn new(ns: String, db: String, op: String, ts: u64, node: String, seq: u64) -> Al {
+		Al {
+			__: 0x2f, // /
+			_a: 0x2a, // *
+			ns,
+			_b: 0x2a, // *
+			db,
+			_c: 0x21, // !
+			_d: 0x61, // a
+			_e: 0x6c, // l
+			op,
+			ts,
+			node,
+			seq,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn key() {
+		use super::*;
+		#[rustfmt::skip]
+		let val = Al::new(
+			"test".to_string(),
+			"test".to_string(),
+			"get".to_string(),
+			12345,
+			"test".to_string(),
+			1,
+		);
+		let enc = Al::encode(&val).unwrap();
+		assert_eq!(enc, b"/*test\x00*test\x00!alget\x00\x00\x00\x00\x00\x00\x00\x30\x39test\x00\x00\x00\x00\x00\x00\x00\x00\x01");
+		let dec = Al::decode(&enc).unwrap();
+		assert_eq!(val, dec);
+		assert!(prefix("test", "test", "get") < enc);
+	}
+
+	#[test]
+	fn range() {
+		use super::*;
+		let beg: Vec<u8> = new("test", "test", "get", 100, "", 0).into();
+		let end: Vec<u8> = new("test", "test", "get", 200, "", 0).into();
+		// Entries within the time range are within the range of keys
+		let min: Vec<u8> = new("test", "test", "get", 100, "", 0).into();
+		let max: Vec<u8> = new("test", "test", "get", 199, "test", u64::MAX).into();
+		assert!(prefix("test", "test", "get") <= beg && beg <= min && max < end);
+		// Entries of other operations are outside of the range
+		let op: Vec<u8> = new("test", "test", "getall", 150, "", 0).into();
+		assert!(op >= end);
+		let op: Vec<u8> = new("test", "test", "ge", 150, "test", u64::MAX).into();
+		assert!(op < beg);
+	}
+}
@@ -0,0 +1,50 @@
+use derive::Key;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Key)]
+pub struct Ar {
+	__: u8,
+	_a: u8,
+	pub ns: String,
+	_b: u8,
+	pub db: String,
+	_c: u8,
+	_d: u8,
+	_e: u8,
+}
+
+pub fn new(ns: &str, db: &str) -> Ar {
+	Ar::new(ns.to_string(), db.to_string())
+}
+
+impl Ar {
+	pub fn new(ns: String, db: String) -> Ar {
+		Ar {
+			__: 0x2f, // /
+			_a: 0x2a, // *
+			ns,
+			_b: 0x2a, // *
+			db,
+			_c: 0x21, // !
+			_d: 0x61, // a
+			_e: 0x72, // r
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn key() {
+		use super::*;
+		#[rustfmt::skip]
+		let val = Ar::new(
+			"test".to_string(),
+			"test".to_string(),
+		);
+		let enc = Ar::encode(&val).unwrap();
+		assert_eq!(enc, b"/*test\x00*test\x00!ar");
+		let dec = Ar::decode(&enc).unwrap();
+		assert_eq!(val, dec);
+	}
+}
@@ -0,0 +1,113 @@
+use crate::sql::id::Id;
+use derive::Key;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Key)]
+struct Prefix {
+	__: u8,
+	_a: u8,
+	pub ns: String,
+	_b: u8,
+	pub db: String,
+	_c: u8,
+	_d: u8,
+	_e: u8,
+	pub lsn: u64,
+}
+
+impl Prefix {
+	fn new(ns: &str, db: &str, lsn: u64) -> Prefix {
+		Prefix {
+			__: 0x2f, // /
+			_a: 0x2a, // *
+			ns: ns.to_string(),
+			_b: 0x2a, // *
+			db: db.to_string(),
+			_c: 0x21, // !
+			_d: 0x63, // c
+			_e: 0x66, // f
+			lsn,
+		}
+	}
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Key)]
+pub struct Cf {
+	__: u8,
+	_a: u8,
+	pub ns: String,
+	_b: u8,
+	pub db: String,
+	_c: u8,
+	_d: u8,
+	_e: u8,
+	pub lsn: u64,
+	pub tb: String,
+	pub id: Id,
+}
+
+pub fn new(ns: &str, db: &str, lsn: u64, tb: &str, id: &Id) -> Cf {
+	Cf::new(ns.to_string(), db.to_string(), lsn, tb.to_string(), id.to_owned())
+}
+
+pub fn suffix(ns: &str, db: &str) -> Vec<u8> {
+	let mut k = super::database::new(ns, db).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x63, 0x66, 0xff]);
+	k
+}
+
+pub fn lsn(ns: &str, db: &str, lsn: u64) -> Vec<u8> {
+	Prefix::new(ns, db, lsn).encode().unwrap()
+}
+
+impl Cf {
+	pub fn new(ns: String, db: String, lsn: u64, tb: String, id: Id) -> Cf {
+		Cf {
+			__: 0x2f, // /
+			_a: 0x2a, // *
+			ns,
+			_b: 0x2a, // *
+			db,
+			_c: 0x21, // !
+			_d: 0x63, // c
+			_e: 0x66, // f
+			lsn,
+			tb,
+			id,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn key() {
+		use super::*;
+		#[rustfmt::skip]
+		let val = Cf::new(
+			"test".to_string(),
+			"test".to_string(),
+			1234567890,
+			"test".to_string(),
+			"test".into(),
+		);
+		let enc = Cf::encode(&val).unwrap();
+		let dec = Cf::decode(&enc).unwrap();
+		assert_eq!(val, dec);
+		assert!(lsn("test", "test", 1234567890) < enc && enc < lsn("test", "test", 1234567891));
+	}
+
+	#[test]
+	fn range() {
+		use super::*;
+		let end = suffix("test", "test");
+		// Changes are ordered by their log sequence number
+		let beg = lsn("test", "test", 0);
+		assert_eq!(beg, b"/*test\x00*test\x00!cf\x00\x00\x00\x00\x00\x00\x00\x00");
+		let max: Vec<u8> = new("test", "test", i64::MAX as u64, "test", &"test".into()).into();
+		assert!(lsn("test", "test", i64::MAX as u64) < max && max < end);
+		// Changes of other databases are outside of the range
+		let db: Vec<u8> = new("test", "testing", 0, "test", &"test".into()).into();
+		assert!(db >= end);
+	}
+}
@@ -19,6 +19,18 @@ pub fn new(ns: &str, db: &str, lq: &Uuid) -> Lq {
 	Lq::new(ns.to_string(), db.to_string(), lq.to_owned())
 }
 
+pub fn prefix(ns: &str, db: &str) -> Vec<u8> {
+	let mut k = super::database::new(ns, db).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x6c, 0x71, 0x00]);
+	k
+}
+
+pub fn suffix(ns: &str, db: &str) -> Vec<u8> {
+	let mut k = super::database::new(ns, db).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x6c, 0x71, 0xff]);
+	k
+}
+
 impl Lq {
 	pub fn new(ns: String, db: String, lq: Uuid) -> Lq {
 		Lq {
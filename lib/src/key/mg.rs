@@ -0,0 +1,81 @@
+use derive::Key;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Key)]
+pub struct Mg {
+	__: u8,
+	_a: u8,
+	pub ns: String,
+	_b: u8,
+	pub db: String,
+	_c: u8,
+	_d: u8,
+	_e: u8,
+	pub version: u64,
+}
+
+pub fn new(ns: &str, db: &str, version: u64) -> Mg {
+	Mg::new(ns.to_string(), db.to_string(), version)
+}
+
+pub fn prefix(ns: &str, db: &str) -> Vec<u8> {
+	let mut k = super::database::new(ns, db).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x6d, 0x67, 0x00]);
+	k
+}
+
+pub fn suffix(ns: &str, db: &str) -> Vec<u8> {
+	let mut k = super::database::new(ns, db).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x6d, 0x67, 0xff]);
+	k
+}
+
+impl Mg {
+	pub fn new(ns: String, db: String, version: u64) -> Mg {
+		Mg {
+			__: 0x2f, // /
+			_a: 0x2a, // *
+			ns,
+			_b: 0x2a, // *
+			db,
+			_c: 0x21, // !
+			_d: 0x6d, // m
+			_e: 0x67, // g
+			version,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn key() {
+		use super::*;
+		#[rustfmt::skip]
+		let val = Mg::new(
+			"test".to_string(),
+			"test".to_string(),
+			12345,
+		);
+		let enc = Mg::encode(&val).unwrap();
+		assert_eq!(enc, b"/*test\x00*test\x00!mg\x00\x00\x00\x00\x00\x00\x30\x39");
+		let dec = Mg::decode(&enc).unwrap();
+		assert_eq!(val, dec);
+	}
+
+	#[test]
+	fn range() {
+		use super::*;
+		let beg = prefix("test", "test");
+		let end = suffix("test", "test");
+		// Migrations of any version are within the range of the database
+		let min: Vec<u8> = new("test", "test", 0).into();
+		let max: Vec<u8> = new("test", "test", i64::MAX as u64).into();
+		assert!(beg <= min && max < end);
+		// The migration version and other databases are outside of the range
+		let mv: Vec<u8> = super::super::mv::new("test", "test").into();
+		assert!(mv < beg || mv >= end);
+		let db: Vec<u8> = new("test", "tes", i64::MAX as u64).into();
+		assert!(db < beg);
+	}
+}
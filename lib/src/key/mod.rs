@@ -1,5 +1,7 @@
 /// KV              /
 /// NS              /!ns{ns}
+/// TX              /!tx{id}
+/// TK              /!tk{id}
 ///
 /// Namespace       /*{ns}
 /// NL              /*{ns}!nl{us}
@@ -13,6 +15,14 @@
 /// ST              /*{ns}*{db}!st{sc}!tk{tk}
 /// TB              /*{ns}*{db}!tb{tb}
 /// LQ              /*{ns}*{db}!lq{lq}
+/// CF              /*{ns}*{db}!cf{lsn}{tb}{id}
+/// MG              /*{ns}*{db}!mg{version}
+/// MV              /*{ns}*{db}!mv
+/// AR              /*{ns}*{db}!ar
+/// AL              /*{ns}*{db}!al{op}{ts}{node}{seq}
+/// SE              /*{ns}*{db}!se{id}
+/// QU              /*{ns}*{db}!qu
+/// QC              /*{ns}*{db}!qc{tb}
 ///
 /// Table           /*{ns}*{db}*{tb}
 /// FT              /*{ns}*{db}*{tb}!ft{ft}
@@ -20,6 +30,10 @@
 /// EV              /*{ns}*{db}*{tb}!ev{ev}
 /// IX              /*{ns}*{db}*{tb}!ix{ix}
 /// LV              /*{ns}*{db}*{tb}!lv{lv}
+/// VS              /*{ns}*{db}*{tb}!vs{version}
+/// VR              /*{ns}*{db}*{tb}!vr{ft}
+/// AC              /*{ns}*{db}*{tb}!ac
+/// LK              /*{ns}*{db}*{tb}!lk
 ///
 /// Thing           /*{ns}*{db}*{tb}*{id}
 ///
@@ -27,6 +41,10 @@
 ///
 /// Index           /*{ns}*{db}*{tb}¤{ix}{fd}{id}
 ///
+pub mod ac;
+pub mod al;
+pub mod ar;
+pub mod cf;
 pub mod database;
 pub mod db;
 pub mod dl;
@@ -38,14 +56,24 @@ pub mod graph;
 pub mod index;
 pub mod ix;
 pub mod kv;
+pub mod lk;
 pub mod lq;
 pub mod lv;
+pub mod mg;
+pub mod mv;
 pub mod namespace;
 pub mod nl;
 pub mod ns;
 pub mod nt;
+pub mod qc;
+pub mod qu;
 pub mod sc;
+pub mod se;
 pub mod st;
 pub mod table;
 pub mod tb;
 pub mod thing;
+pub mod tk;
+pub mod tx;
+pub mod vr;
+pub mod vs;
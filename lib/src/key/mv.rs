@@ -0,0 +1,50 @@
+use derive::Key;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Key)]
+pub struct Mv {
+	__: u8,
+	_a: u8,
+	pub ns: String,
+	_b: u8,
+	pub db: String,
+	_c: u8,
+	_d: u8,
+	_e: u8,
+}
+
+pub fn new(ns: &str, db: &str) -> Mv {
+	Mv::new(ns.to_string(), db.to_string())
+}
+
+impl Mv {
+	pub fn new(ns: String, db: String) -> Mv {
+		Mv {
+			__: 0x2f, // /
+			_a: 0x2a, // *
+			ns,
+			_b: 0x2a, // *
+			db,
+			_c: 0x21, // !
+			_d: 0x6d, // m
+			_e: 0x76, // v
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn key() {
+		use super::*;
+		#[rustfmt::skip]
+		let val = Mv::new(
+			"test".to_string(),
+			"test".to_string(),
+		);
+		let enc = Mv::encode(&val).unwrap();
+		assert_eq!(enc, b"/*test\x00*test\x00!mv");
+		let dec = Mv::decode(&enc).unwrap();
+		assert_eq!(val, dec);
+	}
+}
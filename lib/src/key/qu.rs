@@ -0,0 +1,50 @@
+use derive::Key;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Key)]
+pub struct Qu {
+	__: u8,
+	_a: u8,
+	pub ns: String,
+	_b: u8,
+	pub db: String,
+	_c: u8,
+	_d: u8,
+	_e: u8,
+}
+
+pub fn new(ns: &str, db: &str) -> Qu {
+	Qu::new(ns.to_string(), db.to_string())
+}
+
+impl Qu {
+	pub fn new(ns: String, db: String) -> Qu {
+		Qu {
+			__: 0x2f, // /
+			_a: 0x2a, // *
+			ns,
+			_b: 0x2a, // *
+			db,
+			_c: 0x21, // !
+			_d: 0x71, // q
+			_e: 0x75, // u
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn key() {
+		use super::*;
+		#[rustfmt::skip]
+		let val = Qu::new(
+			"test".to_string(),
+			"test".to_string(),
+		);
+		let enc = Qu::encode(&val).unwrap();
+		assert_eq!(enc, b"/*test\x00*test\x00!qu");
+		let dec = Qu::decode(&enc).unwrap();
+		assert_eq!(val, dec);
+	}
+}
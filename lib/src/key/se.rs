@@ -0,0 +1,79 @@
+use derive::Key;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Key)]
+pub struct Se {
+	__: u8,
+	_a: u8,
+	pub ns: String,
+	_b: u8,
+	pub db: String,
+	_c: u8,
+	_d: u8,
+	_e: u8,
+	pub id: String,
+}
+
+pub fn new(ns: &str, db: &str, id: &str) -> Se {
+	Se::new(ns.to_string(), db.to_string(), id.to_string())
+}
+
+pub fn prefix(ns: &str, db: &str) -> Vec<u8> {
+	let mut k = super::database::new(ns, db).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x73, 0x65, 0x00]);
+	k
+}
+
+pub fn suffix(ns: &str, db: &str) -> Vec<u8> {
+	let mut k = super::database::new(ns, db).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x73, 0x65, 0xff]);
+	k
+}
+
+impl Se {
+	pub fn new(ns: String, db: String, id: String) -> Se {
+		Se {
+			__: 0x2f, // /
+			_a: 0x2a, // *
+			ns,
+			_b: 0x2a, // *
+			db,
+			_c: 0x21, // !
+			_d: 0x73, // s
+			_e: 0x65, // e
+			id,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn key() {
+		use super::*;
+		#[rustfmt::skip]
+		let val = Se::new(
+			"test".to_string(),
+			"test".to_string(),
+			"test".to_string(),
+		);
+		let enc = Se::encode(&val).unwrap();
+		assert_eq!(enc, b"/*test\x00*test\x00!setest\x00");
+		let dec = Se::decode(&enc).unwrap();
+		assert_eq!(val, dec);
+	}
+
+	#[test]
+	fn range() {
+		use super::*;
+		let beg = prefix("test", "test");
+		let end = suffix("test", "test");
+		// Sessions with any id are within the range of the database
+		let min: Vec<u8> = new("test", "test", "").into();
+		let max: Vec<u8> = new("test", "test", "\u{fe}").into();
+		assert!(beg <= min && max < end);
+		// Sessions of other databases are outside of the range
+		let db: Vec<u8> = new("test", "testing", "").into();
+		assert!(db >= end);
+	}
+}
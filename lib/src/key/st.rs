@@ -22,15 +22,25 @@ pub fn new(ns: &str, db: &str, sc: &str, tk: &str) -> St {
 	St::new(ns.to_string(), db.to_string(), sc.to_string(), tk.to_string())
 }
 
+pub fn all(ns: &str, db: &str) -> Vec<u8> {
+	let mut k = super::database::new(ns, db).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x73, 0x74]);
+	k
+}
+
 pub fn prefix(ns: &str, db: &str, sc: &str) -> Vec<u8> {
-	let mut k = super::sc::new(ns, db, sc).encode().unwrap();
-	k.extend_from_slice(&[0x21, 0x74, 0x6b, 0x00]);
+	let mut k = super::database::new(ns, db).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x73, 0x74]);
+	k.extend_from_slice(sc.as_bytes());
+	k.extend_from_slice(&[0x00, 0x21, 0x74, 0x6b, 0x00]);
 	k
 }
 
 pub fn suffix(ns: &str, db: &str, sc: &str) -> Vec<u8> {
-	let mut k = super::sc::new(ns, db, sc).encode().unwrap();
-	k.extend_from_slice(&[0x21, 0x74, 0x6b, 0xff]);
+	let mut k = super::database::new(ns, db).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x73, 0x74]);
+	k.extend_from_slice(sc.as_bytes());
+	k.extend_from_slice(&[0x00, 0x21, 0x74, 0x6b, 0xff]);
 	k
 }
 
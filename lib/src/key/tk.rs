@@ -0,0 +1,44 @@
+use derive::Key;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Key)]
+pub struct Tk {
+	__: u8,
+	_a: u8,
+	_b: u8,
+	_c: u8,
+	pub id: String,
+}
+
+pub fn new(id: &Uuid) -> Tk {
+	Tk::new(id.to_string())
+}
+
+impl Tk {
+	pub fn new(id: String) -> Tk {
+		Tk {
+			__: 0x2f, // /
+			_a: 0x21, // !
+			_b: 0x74, // t
+			_c: 0x6b, // k
+			id,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn key() {
+		use super::*;
+		#[rustfmt::skip]
+		let val = Tk::new(
+			"test".to_string(),
+		);
+		let enc = Tk::encode(&val).unwrap();
+		assert_eq!(enc, b"/!tktest\x00");
+		let dec = Tk::decode(&enc).unwrap();
+		assert_eq!(val, dec);
+	}
+}
@@ -0,0 +1,57 @@
+use derive::Key;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Key)]
+pub struct Tx {
+	__: u8,
+	_a: u8,
+	_b: u8,
+	_c: u8,
+	pub id: String,
+}
+
+pub fn new(id: &Uuid) -> Tx {
+	Tx::new(id.to_string())
+}
+
+pub fn prefix() -> Vec<u8> {
+	let mut k = super::kv::new().encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x74, 0x78, 0x00]);
+	k
+}
+
+pub fn suffix() -> Vec<u8> {
+	let mut k = super::kv::new().encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x74, 0x78, 0xff]);
+	k
+}
+
+impl Tx {
+	pub fn new(id: String) -> Tx {
+		Tx {
+			__: 0x2f, // /
+			_a: 0x21, // !
+			_b: 0x74, // t
+			_c: 0x78, // x
+			id,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn key() {
+		use super::*;
+		#[rustfmt::skip]
+		let val = Tx::new(
+			"test".to_string(),
+		);
+		let enc = Tx::encode(&val).unwrap();
+		assert_eq!(enc, b"/!txtest\x00");
+		let dec = Tx::decode(&enc).unwrap();
+		assert_eq!(val, dec);
+		assert!(prefix() < enc && enc < suffix());
+	}
+}
@@ -0,0 +1,84 @@
+use derive::Key;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Key)]
+pub struct Vr {
+	__: u8,
+	_a: u8,
+	pub ns: String,
+	_b: u8,
+	pub db: String,
+	_c: u8,
+	pub tb: String,
+	_d: u8,
+	_e: u8,
+	_f: u8,
+	pub ft: String,
+}
+
+pub fn new(ns: &str, db: &str, tb: &str, ft: &str) -> Vr {
+	Vr::new(ns.to_string(), db.to_string(), tb.to_string(), ft.to_string())
+}
+
+pub fn prefix(ns: &str, db: &str, tb: &str) -> Vec<u8> {
+	let mut k = super::table::new(ns, db, tb).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x76, 0x72, 0x00]);
+	k
+}
+
+pub fn suffix(ns: &str, db: &str, tb: &str) -> Vec<u8> {
+	let mut k = super::table::new(ns, db, tb).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x76, 0x72, 0xff]);
+	k
+}
+
+impl Vr {
+	pub fn new(ns: String, db: String, tb: String, ft: String) -> Vr {
+		Vr {
+			__: 0x2f, // /
+			_a: 0x2a, // *
+			ns,
+			_b: 0x2a, // *
+			db,
+			_c: 0x2a, // *
+			tb,
+			_d: 0x21, // !
+			_e: 0x76, // v
+			_f: 0x72, // r
+			ft,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn key() {
+		use super::*;
+		#[rustfmt::skip]
+		let val = Vr::new(
+			"test".to_string(),
+			"test".to_string(),
+			"test".to_string(),
+			"test".to_string(),
+		);
+		let enc = Vr::encode(&val).unwrap();
+		assert_eq!(enc, b"/*test\x00*test\x00*test\x00!vrtest\x00");
+		let dec = Vr::decode(&enc).unwrap();
+		assert_eq!(val, dec);
+	}
+
+	#[test]
+	fn range() {
+		use super::*;
+		let beg = prefix("test", "test", "test");
+		let end = suffix("test", "test", "test");
+		// Versions of any foreign table are within the range of the view
+		let min: Vec<u8> = new("test", "test", "test", "").into();
+		let max: Vec<u8> = new("test", "test", "test", "\u{fe}").into();
+		assert!(beg <= min && max < end);
+		// Versions recorded for other views are outside of the range
+		let tb: Vec<u8> = new("test", "test", "testing", "").into();
+		assert!(tb >= end);
+	}
+}
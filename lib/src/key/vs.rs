@@ -0,0 +1,84 @@
+use derive::Key;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Key)]
+pub struct Vs {
+	__: u8,
+	_a: u8,
+	pub ns: String,
+	_b: u8,
+	pub db: String,
+	_c: u8,
+	pub tb: String,
+	_d: u8,
+	_e: u8,
+	_f: u8,
+	pub version: u64,
+}
+
+pub fn new(ns: &str, db: &str, tb: &str, version: u64) -> Vs {
+	Vs::new(ns.to_string(), db.to_string(), tb.to_string(), version)
+}
+
+pub fn prefix(ns: &str, db: &str, tb: &str) -> Vec<u8> {
+	let mut k = super::table::new(ns, db, tb).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x76, 0x73, 0x00]);
+	k
+}
+
+pub fn suffix(ns: &str, db: &str, tb: &str) -> Vec<u8> {
+	let mut k = super::table::new(ns, db, tb).encode().unwrap();
+	k.extend_from_slice(&[0x21, 0x76, 0x73, 0xff]);
+	k
+}
+
+impl Vs {
+	pub fn new(ns: String, db: String, tb: String, version: u64) -> Vs {
+		Vs {
+			__: 0x2f, // /
+			_a: 0x2a, // *
+			ns,
+			_b: 0x2a, // *
+			db,
+			_c: 0x2a, // *
+			tb,
+			_d: 0x21, // !
+			_e: 0x76, // v
+			_f: 0x73, // s
+			version,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	#[test]
+	fn key() {
+		use super::*;
+		#[rustfmt::skip]
+		let val = Vs::new(
+			"test".to_string(),
+			"test".to_string(),
+			"test".to_string(),
+			1665861143000000,
+		);
+		let enc = Vs::encode(&val).unwrap();
+		assert_eq!(enc, b"/*test\x00*test\x00*test\x00!vs\x00\x05\xeb\x17\x85\xac\x73\xc0");
+		let dec = Vs::decode(&enc).unwrap();
+		assert_eq!(val, dec);
+	}
+
+	#[test]
+	fn range() {
+		use super::*;
+		let beg = prefix("test", "test", "test");
+		let end = suffix("test", "test", "test");
+		// Snapshots of any version are within the range of the table
+		let min: Vec<u8> = new("test", "test", "test", 0).into();
+		let max: Vec<u8> = new("test", "test", "test", i64::MAX as u64).into();
+		assert!(beg <= min && max < end);
+		// Snapshots of other tables are outside of the range
+		let tb: Vec<u8> = new("test", "test", "testing", 0).into();
+		assert!(tb >= end);
+	}
+}
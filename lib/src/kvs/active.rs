@@ -0,0 +1,292 @@
+use super::ds;
+use crate::cnf::TRANSACTION_SENTINEL_LEASE;
+use crate::err::Error;
+use chrono::DateTime;
+use chrono::Utc;
+use derive::Store;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use trice::Instant;
+use uuid::Uuid;
+
+// How often a transaction checks whether it has been killed on another node
+const KILL_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The transactions which are currently open on a datastore.
+///
+/// Embedded datastores can only be opened by a single process, so the open
+/// transactions are tracked in memory. On distributed datastores, each open
+/// write transaction is also stored as a sentinel key, so that it is visible
+/// on every node, until the transaction is committed or cancelled. Storing and
+/// removing a sentinel costs two extra write transactions, so read-only
+/// transactions are only tracked on the node which is running them.
+#[derive(Clone)]
+pub struct Active {
+	node: Uuid,
+	started: Instant,
+	txs: Arc<Mutex<HashMap<Uuid, Entry>>>,
+}
+
+// The state of an open transaction
+struct Entry {
+	info: TxInfo,
+	kill: Arc<AtomicBool>,
+}
+
+impl Default for Active {
+	fn default() -> Self {
+		Active {
+			node: Uuid::new_v4(),
+			started: Instant::now(),
+			txs: Arc::default(),
+		}
+	}
+}
+
+impl Active {
+	// Register a newly started transaction
+	pub fn register(&self, write: bool) -> Registration {
+		let id = Uuid::new_v4();
+		let kill = Arc::new(AtomicBool::new(false));
+		let info = TxInfo {
+			id,
+			started_at: Utc::now(),
+			node_id: self.node,
+			write,
+		};
+		self.txs.lock().unwrap().insert(
+			id,
+			Entry {
+				info,
+				kill: kill.clone(),
+			},
+		);
+		Registration {
+			active: self.clone(),
+			id,
+			kill,
+			polled: Instant::now(),
+			expires: None,
+		}
+	}
+	// List all of the open transactions
+	pub fn list(&self) -> Vec<TxInfo> {
+		let mut out: Vec<TxInfo> =
+			self.txs.lock().unwrap().values().map(|v| v.info.clone()).collect();
+		out.sort_by_key(|v| v.started_at);
+		out
+	}
+	// Get the time since the datastore was started
+	pub fn uptime(&self) -> Duration {
+		self.started.elapsed()
+	}
+	// Flag an open transaction to be killed
+	pub fn kill(&self, id: &Uuid) -> bool {
+		match self.txs.lock().unwrap().get(id) {
+			Some(v) => {
+				v.kill.store(true, Ordering::Relaxed);
+				true
+			}
+			None => false,
+		}
+	}
+}
+
+/// The registration of an open transaction, which is removed when dropped.
+pub struct Registration {
+	active: Active,
+	id: Uuid,
+	kill: Arc<AtomicBool>,
+	polled: Instant,
+	expires: Option<i64>,
+}
+
+impl Registration {
+	// List all of the open transactions
+	pub fn list(&self) -> Vec<TxInfo> {
+		self.active.list()
+	}
+	// Get the time since the datastore was started
+	pub fn uptime(&self) -> Duration {
+		self.active.uptime()
+	}
+	// Flag an open transaction to be killed
+	pub fn kill(&self, id: &Uuid) -> bool {
+		self.active.kill(id)
+	}
+	// Get the unique id of the transaction
+	pub fn id(&self) -> Uuid {
+		self.id
+	}
+	// Get the unique id of the node running the transaction
+	pub fn node(&self) -> Uuid {
+		self.active.node
+	}
+	// Get information about the transaction
+	pub fn info(&self) -> Option<TxInfo> {
+		self.active.txs.lock().unwrap().get(&self.id).map(|v| v.info.clone())
+	}
+	// Check if the transaction has been killed
+	pub fn killed(&self) -> bool {
+		self.kill.load(Ordering::Relaxed)
+	}
+	// Check if the stored kill flag is due to be polled
+	pub fn poll(&mut self) -> bool {
+		match self.polled.elapsed() >= KILL_POLL_INTERVAL {
+			true => {
+				self.polled = Instant::now();
+				true
+			}
+			false => false,
+		}
+	}
+	// Check if the transaction has a stored sentinel
+	pub fn stored(&self) -> bool {
+		self.expires.is_some()
+	}
+	// Set the time at which the stored sentinel of the transaction expires
+	pub fn set_expires(&mut self, expires: i64) {
+		self.expires = Some(expires);
+	}
+	// Check if the stored sentinel of the transaction is due to be renewed
+	pub fn renew(&self) -> bool {
+		match self.expires {
+			Some(v) => Utc::now().timestamp_millis() >= renewal(v),
+			None => false,
+		}
+	}
+	// Mark the transaction as finished
+	pub fn remove(&self) {
+		self.active.txs.lock().unwrap().remove(&self.id);
+	}
+}
+
+impl Drop for Registration {
+	fn drop(&mut self) {
+		self.remove();
+	}
+}
+
+/// Information about a transaction which is currently open.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Store)]
+pub struct TxInfo {
+	/// The unique id of the transaction
+	pub id: Uuid,
+	/// The time at which the transaction was started
+	pub started_at: DateTime<Utc>,
+	/// The unique id of the datastore node running the transaction
+	pub node_id: Uuid,
+	/// Whether the transaction is writeable
+	pub write: bool,
+}
+
+/// The sentinel of an open transaction, which is stored so that it is visible on other nodes.
+///
+/// A sentinel lasts for [`TRANSACTION_SENTINEL_LEASE`], and is renewed as its
+/// transaction performs operations, so that the sentinel of a transaction on a
+/// node which has failed is ignored once it expires, and is then removed.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Store)]
+struct Sentinel {
+	info: TxInfo,
+	expires: i64,
+}
+
+impl Sentinel {
+	// Check if the sentinel has expired
+	fn expired(&self) -> bool {
+		self.expires <= Utc::now().timestamp_millis()
+	}
+}
+
+// The time at which a sentinel should be renewed, given the time at which it expires
+fn renewal(expires: i64) -> i64 {
+	expires - TRANSACTION_SENTINEL_LEASE.as_millis() as i64 / 2
+}
+
+// Store the sentinel of an open transaction, returning the time at which it expires
+pub async fn store(ds: &ds::Inner, info: &TxInfo) -> Result<i64, Error> {
+	let val = Sentinel {
+		info: info.clone(),
+		expires: Utc::now().timestamp_millis() + TRANSACTION_SENTINEL_LEASE.as_millis() as i64,
+	};
+	let mut tx = ds.transaction(true, false).await?;
+	tx.set(crate::key::tx::new(&info.id).into(), val.clone().into()).await?;
+	tx.commit().await?;
+	Ok(val.expires)
+}
+
+// Remove the sentinel and any kill flag of a transaction once it is finished
+pub async fn clear(ds: &ds::Inner, id: &Uuid) -> Result<(), Error> {
+	let mut tx = ds.transaction(true, false).await?;
+	tx.del(crate::key::tx::new(id).into()).await?;
+	tx.del(crate::key::tk::new(id).into()).await?;
+	tx.commit().await
+}
+
+// Flag a transaction open on any node to be killed, if its sentinel has not expired
+pub async fn kill(ds: &ds::Inner, id: &Uuid) -> Result<bool, Error> {
+	let mut tx = ds.transaction(true, false).await?;
+	match tx.get(crate::key::tx::new(id).into()).await?.map(Sentinel::from) {
+		Some(v) if !v.expired() => {
+			tx.set(crate::key::tk::new(id).into(), vec![]).await?;
+			tx.commit().await?;
+			Ok(true)
+		}
+		_ => {
+			tx.cancel().await?;
+			Ok(false)
+		}
+	}
+}
+
+// Check if the stored kill flag of a transaction is set
+pub async fn killed(ds: &ds::Inner, id: &Uuid) -> Result<bool, Error> {
+	let mut tx = ds.transaction(false, false).await?;
+	let res = tx.get(crate::key::tk::new(id).into()).await;
+	tx.cancel().await?;
+	Ok(res?.is_some())
+}
+
+// Retrieve the sentinels of the transactions which are open on all nodes
+//
+// Any sentinels which have expired are removed, along with their kill flags.
+pub async fn scan(ds: &ds::Inner) -> Result<Vec<TxInfo>, Error> {
+	let beg = crate::key::tx::prefix();
+	let end = crate::key::tx::suffix();
+	let mut tx = ds.transaction(true, false).await?;
+	let res = match tx.scan(beg..end, u32::MAX).await {
+		Ok(v) => v,
+		Err(e) => {
+			tx.cancel().await?;
+			return Err(e);
+		}
+	};
+	let mut out = vec![];
+	let mut old = vec![];
+	for (_, v) in res.into_iter() {
+		let v: Sentinel = v.into();
+		match v.expired() {
+			true => old.push(v.info.id),
+			false => out.push(v.info),
+		}
+	}
+	// Remove the expired sentinels
+	if old.is_empty() {
+		tx.cancel().await?;
+	} else {
+		for id in old.iter() {
+			tx.del(crate::key::tx::new(id).into()).await?;
+			tx.del(crate::key::tk::new(id).into()).await?;
+		}
+		// A conflicting commit means another node removed them
+		let _ = tx.commit().await;
+	}
+	out.sort_by_key(|v| v.started_at);
+	Ok(out)
+}
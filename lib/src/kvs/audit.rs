@@ -0,0 +1,61 @@
+use crate::key::al::Al;
+use crate::kvs::AccessLogEntry;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+// The number of access log entries which are recorded before they are stored
+const FLUSH_THRESHOLD: usize = 1000;
+
+/// The access log entries recorded on a datastore which have not yet been stored.
+///
+/// Entries are recorded in memory by every transaction with access logging enabled,
+/// including read-only transactions, and are stored in batches in a separate write
+/// transaction, so that logging a read does not write within the reading transaction.
+/// Each entry is keyed by the node which recorded it, along with a sequence number
+/// which is unique on that node, so entries from different nodes never collide.
+#[derive(Clone, Default)]
+pub struct Audit {
+	seq: Arc<AtomicU64>,
+	pending: Arc<Mutex<Vec<(Al, AccessLogEntry)>>>,
+}
+
+impl Audit {
+	// Get the next sequence number for an entry recorded on this node
+	pub fn next(&self) -> u64 {
+		self.seq.fetch_add(1, Ordering::Relaxed)
+	}
+	// Record an access log entry
+	pub fn record(&self, key: Al, entry: AccessLogEntry) {
+		self.pending.lock().unwrap().push((key, entry));
+	}
+	// Check if enough log entries have been recorded for them to be stored
+	pub fn full(&self) -> bool {
+		self.pending.lock().unwrap().len() >= FLUSH_THRESHOLD
+	}
+	// Retrieve the pending log entries of a database within a time range
+	pub fn get(&self, ns: &str, db: &str, from: u64, to: u64) -> Vec<AccessLogEntry> {
+		self.pending
+			.lock()
+			.unwrap()
+			.iter()
+			.filter(|(k, _)| k.ns == ns && k.db == db && k.ts >= from && k.ts < to)
+			.map(|(_, v)| v.clone())
+			.collect()
+	}
+	// Forget the pending log entries of a database from before a point in time
+	pub fn purge(&self, ns: &str, db: &str, before: u64) {
+		self.pending.lock().unwrap().retain(|(k, _)| k.ns != ns || k.db != db || k.ts >= before);
+	}
+	// Take all of the pending log entries, so that they can be stored
+	pub fn take(&self) -> Vec<(Al, AccessLogEntry)> {
+		std::mem::take(&mut *self.pending.lock().unwrap())
+	}
+	// Return log entries which could not be stored to the pending log entries
+	pub fn restore(&self, entries: Vec<(Al, AccessLogEntry)>) {
+		let mut p = self.pending.lock().unwrap();
+		let newer = std::mem::replace(&mut *p, entries);
+		p.extend(newer);
+	}
+}
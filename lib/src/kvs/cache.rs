@@ -1,4 +1,6 @@
 use crate::kvs::kv::Key;
+use crate::kvs::tx::QuotaCounter;
+use crate::kvs::QuotaDefinition;
 use crate::sql::statements::DefineDatabaseStatement;
 use crate::sql::statements::DefineEventStatement;
 use crate::sql::statements::DefineFieldStatement;
@@ -9,6 +11,7 @@ use crate::sql::statements::DefineScopeStatement;
 use crate::sql::statements::DefineTableStatement;
 use crate::sql::statements::DefineTokenStatement;
 use crate::sql::statements::LiveStatement;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::sync::Arc;
 
@@ -25,28 +28,152 @@ pub enum Entry {
 	Dts(Arc<Vec<DefineTokenStatement>>),
 	Scs(Arc<Vec<DefineScopeStatement>>),
 	Sts(Arc<Vec<DefineTokenStatement>>),
+	AllSts(Arc<Vec<DefineTokenStatement>>),
 	Tbs(Arc<Vec<DefineTableStatement>>),
 	Evs(Arc<Vec<DefineEventStatement>>),
 	Fds(Arc<Vec<DefineFieldStatement>>),
 	Ixs(Arc<Vec<DefineIndexStatement>>),
 	Fts(Arc<Vec<DefineTableStatement>>),
 	Lvs(Arc<Vec<LiveStatement>>),
+	Qu(Arc<Option<QuotaDefinition>>),
+	Qc(QuotaCounter),
 }
 
-#[derive(Default)]
-pub struct Cache(pub HashMap<Key, Entry>);
+pub struct Cache {
+	// The maximum number of cached entries
+	capacity: usize,
+	// The cached entries, along with the tick of their last use
+	entries: HashMap<Key, (Entry, u64)>,
+	// The cached keys, ordered by the tick of their last use
+	recency: BTreeMap<u64, Key>,
+	// The tick of the most recent use
+	tick: u64,
+}
 
 impl Cache {
+	// Create a cache which holds at most `capacity` entries
+	pub fn new(capacity: usize) -> Cache {
+		Cache {
+			capacity,
+			entries: HashMap::new(),
+			recency: BTreeMap::new(),
+			tick: 0,
+		}
+	}
 	// Check if key exists
 	pub fn exi(&mut self, key: &Key) -> bool {
-		self.0.contains_key(key)
+		self.entries.contains_key(key)
 	}
 	// Set a key in the cache
 	pub fn set(&mut self, key: Key, val: Entry) {
-		self.0.insert(key, val);
+		if self.capacity == 0 {
+			return;
+		}
+		// Replace any existing entry
+		self.del(&key);
+		// Evict the least recently used entries
+		while self.entries.len() >= self.capacity {
+			let used = match self.recency.keys().next() {
+				Some(v) => *v,
+				None => break,
+			};
+			if let Some(k) = self.recency.remove(&used) {
+				self.entries.remove(&k);
+			}
+		}
+		// Insert the new entry
+		self.tick += 1;
+		self.recency.insert(self.tick, key.clone());
+		self.entries.insert(key, (val, self.tick));
 	}
 	// get a key from the cache
 	pub fn get(&mut self, key: &Key) -> Option<Entry> {
-		self.0.get(key).cloned()
+		let (val, used) = self.entries.get_mut(key)?;
+		// Mark the entry as the most recently used
+		self.tick += 1;
+		let key = self.recency.remove(used)?;
+		self.recency.insert(self.tick, key);
+		*used = self.tick;
+		Some(val.clone())
+	}
+	// Delete a key from the cache
+	pub fn del(&mut self, key: &Key) {
+		if let Some((_, used)) = self.entries.remove(key) {
+			self.recency.remove(&used);
+		}
+	}
+	// Delete all keys with a prefix from the cache
+	pub fn del_prefix(&mut self, prefix: &[u8]) {
+		let keys: Vec<Key> =
+			self.entries.keys().filter(|k| k.starts_with(prefix)).cloned().collect();
+		for key in keys {
+			self.del(&key);
+		}
+	}
+	// Delete all keys from the cache
+	pub fn clear(&mut self) {
+		self.entries.clear();
+		self.recency.clear();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+
+	fn entry(name: &str) -> Entry {
+		Entry::Ns(Arc::new(DefineNamespaceStatement {
+			name: name.into(),
+		}))
+	}
+
+	fn name(entry: Option<Entry>) -> Option<String> {
+		match entry {
+			Some(Entry::Ns(v)) => Some(v.name.to_string()),
+			_ => None,
+		}
+	}
+
+	#[test]
+	fn cache_evicts_least_recently_used() {
+		let mut cache = Cache::new(2);
+		cache.set(b"a".to_vec(), entry("a"));
+		cache.set(b"b".to_vec(), entry("b"));
+		assert_eq!(name(cache.get(&b"a".to_vec())), Some("a".to_owned()));
+		cache.set(b"c".to_vec(), entry("c"));
+		assert!(cache.exi(&b"a".to_vec()));
+		assert!(!cache.exi(&b"b".to_vec()));
+		assert!(cache.exi(&b"c".to_vec()));
+	}
+
+	#[test]
+	fn cache_replaces_existing_entry() {
+		let mut cache = Cache::new(2);
+		cache.set(b"a".to_vec(), entry("a"));
+		cache.set(b"b".to_vec(), entry("b"));
+		cache.set(b"a".to_vec(), entry("z"));
+		assert_eq!(name(cache.get(&b"a".to_vec())), Some("z".to_owned()));
+		assert_eq!(name(cache.get(&b"b".to_vec())), Some("b".to_owned()));
+	}
+
+	#[test]
+	fn cache_deletes_prefix() {
+		let mut cache = Cache::new(3);
+		cache.set(b"ab".to_vec(), entry("ab"));
+		cache.set(b"ac".to_vec(), entry("ac"));
+		cache.set(b"b".to_vec(), entry("b"));
+		cache.del_prefix(b"a");
+		assert!(!cache.exi(&b"ab".to_vec()));
+		assert!(!cache.exi(&b"ac".to_vec()));
+		assert!(cache.exi(&b"b".to_vec()));
+	}
+
+	#[test]
+	fn cache_with_no_capacity() {
+		let mut cache = Cache::new(0);
+		cache.set(b"a".to_vec(), entry("a"));
+		assert!(!cache.exi(&b"a".to_vec()));
+		cache.del(&b"a".to_vec());
 	}
 }
@@ -1,6 +1,8 @@
 use super::tx::Transaction;
+use crate::cnf::TRANSACTION_CACHE_SIZE;
 use crate::ctx::Context;
 use crate::dbs::Attach;
+use crate::dbs::Auth;
 use crate::dbs::Executor;
 use crate::dbs::Options;
 use crate::dbs::Response;
@@ -13,11 +15,38 @@ use crate::sql::Query;
 use crate::sql::Value;
 use channel::Sender;
 use futures::lock::Mutex;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::Arc;
 
 /// The underlying datastore instance which stores the dataset.
 pub struct Datastore {
-	pub(super) inner: Inner,
+	pub(super) inner: Arc<Inner>,
+	pub(super) locks: super::lock::Locks,
+	pub(super) active: super::active::Active,
+	pub(super) live: super::live::Subscriptions,
+	pub(super) stats: super::stats::Stats,
+	pub(super) audit: super::audit::Audit,
+	pub(super) versions: super::versions::Versions,
+	pub(super) cache_capacity: usize,
+}
+
+/// The type of a transaction, which determines whether it can write to the datastore.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TransactionType {
+	/// A transaction which can only read from the datastore
+	Read,
+	/// A transaction which can read from and write to the datastore
+	Write,
+}
+
+impl From<bool> for TransactionType {
+	fn from(write: bool) -> Self {
+		match write {
+			true => TransactionType::Write,
+			false => TransactionType::Read,
+		}
+	}
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -28,12 +57,49 @@ pub(super) enum Inner {
 	IxDB(super::ixdb::Datastore),
 	#[cfg(feature = "kv-yokudb")]
 	File(super::file::Datastore),
+	#[cfg(feature = "kv-rocksdb")]
+	RocksDB(super::rocksdb::Datastore),
 	#[cfg(feature = "kv-tikv")]
 	TiKV(super::tikv::Datastore),
 	#[cfg(feature = "kv-fdb")]
 	FDB(super::fdb::Datastore),
 }
 
+impl Inner {
+	// Check if the datastore can be shared by several nodes
+	pub(super) fn distributed(&self) -> bool {
+		match self {
+			#[cfg(feature = "kv-tikv")]
+			Inner::TiKV(_) => true,
+			#[cfg(feature = "kv-fdb")]
+			Inner::FDB(_) => true,
+			#[allow(unreachable_patterns)]
+			_ => false,
+		}
+	}
+	// Start a new transaction on the underlying datastore
+	pub(super) async fn transaction(
+		&self,
+		write: bool,
+		lock: bool,
+	) -> Result<super::tx::Inner, Error> {
+		match self {
+			#[cfg(feature = "kv-echodb")]
+			Inner::Mem(v) => Ok(super::tx::Inner::Mem(v.transaction(write, lock).await?)),
+			#[cfg(feature = "kv-indxdb")]
+			Inner::IxDB(v) => Ok(super::tx::Inner::IxDB(v.transaction(write, lock).await?)),
+			#[cfg(feature = "kv-yokudb")]
+			Inner::File(v) => Ok(super::tx::Inner::File(v.transaction(write, lock).await?)),
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => Ok(super::tx::Inner::RocksDB(v.transaction(write, lock).await?)),
+			#[cfg(feature = "kv-tikv")]
+			Inner::TiKV(v) => Ok(super::tx::Inner::TiKV(v.transaction(write, lock).await?)),
+			#[cfg(feature = "kv-fdb")]
+			Inner::FDB(v) => Ok(super::tx::Inner::FDB(v.transaction(write, lock).await?)),
+		}
+	}
+}
+
 impl Datastore {
 	/// Creates a new datastore instance
 	///
@@ -61,6 +127,18 @@ impl Datastore {
 	/// # }
 	/// ```
 	///
+	/// Or to create a rocksdb-backed store:
+	///
+	/// ```rust,no_run
+	/// # use surrealdb::Datastore;
+	/// # use surrealdb::Error;
+	/// # #[tokio::main]
+	/// # async fn main() -> Result<(), Error> {
+	/// let ds = Datastore::new("rocksdb://./data").await?;
+	/// # Ok(())
+	/// # }
+	/// ```
+	///
 	/// Or to connect to a tikv-backed distributed store:
 	///
 	/// ```rust,no_run
@@ -77,9 +155,9 @@ impl Datastore {
 			#[cfg(feature = "kv-echodb")]
 			"memory" => {
 				info!(target: LOG, "Starting kvs store in {}", path);
-				let v = super::mem::Datastore::new().await.map(|v| Datastore {
-					inner: Inner::Mem(v),
-				});
+				let v = super::mem::Datastore::new()
+					.await
+					.map(|v| Datastore::with_inner(Inner::Mem(v)));
 				info!(target: LOG, "Started kvs store in {}", path);
 				v
 			}
@@ -88,9 +166,9 @@ impl Datastore {
 			s if s.starts_with("ixdb:") => {
 				info!(target: LOG, "Starting kvs store at {}", path);
 				let s = s.trim_start_matches("ixdb://");
-				let v = super::ixdb::Datastore::new(s).await.map(|v| Datastore {
-					inner: Inner::IxDB(v),
-				});
+				let v = super::ixdb::Datastore::new(s)
+					.await
+					.map(|v| Datastore::with_inner(Inner::IxDB(v)));
 				info!(target: LOG, "Started kvs store at {}", path);
 				v
 			}
@@ -99,9 +177,20 @@ impl Datastore {
 			s if s.starts_with("file:") => {
 				info!(target: LOG, "Starting kvs store at {}", path);
 				let s = s.trim_start_matches("file://");
-				let v = super::file::Datastore::new(s).await.map(|v| Datastore {
-					inner: Inner::File(v),
-				});
+				let v = super::file::Datastore::new(s)
+					.await
+					.map(|v| Datastore::with_inner(Inner::File(v)));
+				info!(target: LOG, "Started kvs store at {}", path);
+				v
+			}
+			// Parse and initiate an RocksDB database
+			#[cfg(feature = "kv-rocksdb")]
+			s if s.starts_with("rocksdb:") => {
+				info!(target: LOG, "Starting kvs store at {}", path);
+				let s = s.trim_start_matches("rocksdb://");
+				let v = super::rocksdb::Datastore::new(s)
+					.await
+					.map(|v| Datastore::with_inner(Inner::RocksDB(v)));
 				info!(target: LOG, "Started kvs store at {}", path);
 				v
 			}
@@ -110,9 +199,9 @@ impl Datastore {
 			s if s.starts_with("tikv:") => {
 				info!(target: LOG, "Connecting to kvs store at {}", path);
 				let s = s.trim_start_matches("tikv://");
-				let v = super::tikv::Datastore::new(s).await.map(|v| Datastore {
-					inner: Inner::TiKV(v),
-				});
+				let v = super::tikv::Datastore::new(s)
+					.await
+					.map(|v| Datastore::with_inner(Inner::TiKV(v)));
 				info!(target: LOG, "Connected to kvs store at {}", path);
 				v
 			}
@@ -121,9 +210,9 @@ impl Datastore {
 			s if s.starts_with("fdb:") => {
 				info!(target: LOG, "Connecting to kvs store at {}", path);
 				let s = s.trim_start_matches("fdb://");
-				let v = super::fdb::Datastore::new(s).await.map(|v| Datastore {
-					inner: Inner::FDB(v),
-				});
+				let v = super::fdb::Datastore::new(s)
+					.await
+					.map(|v| Datastore::with_inner(Inner::FDB(v)));
 				info!(target: LOG, "Connected to kvs store at {}", path);
 				v
 			}
@@ -132,50 +221,177 @@ impl Datastore {
 		}
 	}
 
+	// Create a datastore around an underlying datastore instance
+	fn with_inner(inner: Inner) -> Datastore {
+		Datastore {
+			inner: Arc::new(inner),
+			locks: super::lock::Locks::default(),
+			active: super::active::Active::default(),
+			live: super::live::Subscriptions::default(),
+			stats: super::stats::Stats::default(),
+			audit: super::audit::Audit::default(),
+			versions: super::versions::Versions::default(),
+			cache_capacity: TRANSACTION_CACHE_SIZE,
+		}
+	}
+
+	/// Limit the number of schema definitions cached within each transaction.
+	///
+	/// Once a transaction has cached this many definitions, the least recently
+	/// used definition is evicted, and is fetched from the datastore again if it
+	/// is needed later in the transaction.
+	pub fn with_cache_capacity(mut self, capacity: usize) -> Self {
+		self.cache_capacity = capacity;
+		self
+	}
+
 	/// Create a new transaction on this datastore
-	pub async fn transaction(&self, write: bool, lock: bool) -> Result<Transaction, Error> {
-		match &self.inner {
-			#[cfg(feature = "kv-echodb")]
-			Inner::Mem(v) => {
-				let tx = v.transaction(write, lock).await?;
-				Ok(Transaction {
-					inner: super::tx::Inner::Mem(tx),
-					cache: super::cache::Cache::default(),
-				})
-			}
-			#[cfg(feature = "kv-indxdb")]
-			Inner::IxDB(v) => {
-				let tx = v.transaction(write, lock).await?;
-				Ok(Transaction {
-					inner: super::tx::Inner::IxDB(tx),
-					cache: super::cache::Cache::default(),
-				})
+	///
+	/// Any attempt to write to a [`TransactionType::Read`] transaction
+	/// will fail with a [`Error::TxReadonly`] error.
+	pub async fn transaction(
+		&self,
+		kind: TransactionType,
+		lock: bool,
+	) -> Result<Transaction, Error> {
+		let mut tx = self.begin(kind, lock).await?;
+		// Make a write transaction visible on all nodes
+		if self.inner.distributed() && kind == TransactionType::Write {
+			if let Some(info) = tx.active.info() {
+				let exp = super::active::store(&self.inner, &info).await?;
+				tx.active.set_expires(exp);
 			}
-			#[cfg(feature = "kv-yokudb")]
-			Inner::File(v) => {
-				let tx = v.transaction(write, lock).await?;
-				Ok(Transaction {
-					inner: super::tx::Inner::File(tx),
-					cache: super::cache::Cache::default(),
-				})
+		}
+		Ok(tx)
+	}
+
+	/// Perform the periodic maintenance of this datastore.
+	///
+	/// Once enough of them have been recorded by the transactions on this datastore,
+	/// the table accesses and access log entries are stored, and the superseded table
+	/// versions are removed. None of this is done when transactions are started, so
+	/// this should be called regularly, for instance from a background task. Each
+	/// step is attempted, and the first error which occurred is returned.
+	pub async fn tick(&self) -> Result<(), Error> {
+		let mut res = Ok(());
+		if self.stats.full() {
+			res = res.and(self.flush_table_access().await);
+		}
+		if self.audit.full() {
+			res = res.and(self.flush_access_log().await);
+		}
+		if self.versions.full() {
+			res = res.and(self.compact_table_versions().await);
+		}
+		res
+	}
+
+	/// Store the table accesses which have been counted by the transactions on this datastore.
+	///
+	/// Accesses are stored by [`Datastore::tick`] once enough of them have been counted,
+	/// so this only needs to be called to make the latest access counts visible on
+	/// other nodes, for instance before shutting down.
+	pub async fn flush_table_access(&self) -> Result<(), Error> {
+		let tables = self.stats.take();
+		if tables.is_empty() {
+			return Ok(());
+		}
+		let mut tx = self.begin(TransactionType::Write, false).await?;
+		let res = match tx.store_table_access(&tables).await {
+			Ok(_) => tx.commit().await,
+			Err(e) => {
+				tx.cancel().await?;
+				Err(e)
 			}
-			#[cfg(feature = "kv-tikv")]
-			Inner::TiKV(v) => {
-				let tx = v.transaction(write, lock).await?;
-				Ok(Transaction {
-					inner: super::tx::Inner::TiKV(tx),
-					cache: super::cache::Cache::default(),
-				})
+		};
+		// Keep any accesses which could not be stored
+		if res.is_err() {
+			self.stats.restore(tables);
+		}
+		res
+	}
+
+	/// Store the access log entries which have been recorded by the transactions on this datastore.
+	///
+	/// Entries are stored by [`Datastore::tick`] once enough of them have been recorded,
+	/// so this only needs to be called to make the latest entries visible on other
+	/// nodes, for instance before shutting down.
+	pub async fn flush_access_log(&self) -> Result<(), Error> {
+		let entries = self.audit.take();
+		if entries.is_empty() {
+			return Ok(());
+		}
+		let mut tx = self.begin(TransactionType::Write, false).await?;
+		let res = match tx.store_access_log(&entries).await {
+			Ok(_) => tx.commit().await,
+			Err(e) => {
+				tx.cancel().await?;
+				Err(e)
 			}
-			#[cfg(feature = "kv-fdb")]
-			Inner::FDB(v) => {
-				let tx = v.transaction(write, lock).await?;
-				Ok(Transaction {
-					inner: super::tx::Inner::FDB(tx),
-					cache: super::cache::Cache::default(),
-				})
+		};
+		// Keep any entries which could not be stored
+		if res.is_err() {
+			self.audit.restore(entries);
+		}
+		res
+	}
+
+	/// Remove the superseded versions of the tables which have been modified on this datastore.
+	///
+	/// Every writing transaction records a new version for each table which it
+	/// modifies, and superseded versions are removed by [`Datastore::tick`] once
+	/// enough of them have been recorded, so this only needs to be called to reclaim the
+	/// space used by the latest versions straight away.
+	pub async fn compact_table_versions(&self) -> Result<(), Error> {
+		let tables = self.versions.take();
+		if tables.is_empty() {
+			return Ok(());
+		}
+		let mut tx = self.begin(TransactionType::Write, false).await?;
+		let res = match tx.compact_tb_versions(&tables).await {
+			Ok(_) => tx.commit().await,
+			Err(e) => {
+				tx.cancel().await?;
+				Err(e)
 			}
+		};
+		// Keep any tables which could not be compacted
+		if res.is_err() {
+			self.versions.restore(tables);
 		}
+		res
+	}
+
+	// Start a new transaction on the underlying datastore
+	async fn begin(&self, kind: TransactionType, lock: bool) -> Result<Transaction, Error> {
+		let write = kind == TransactionType::Write;
+		let tx = self.inner.transaction(write, lock).await?;
+		Ok(Transaction {
+			inner: Some(tx),
+			ds: self.inner.clone(),
+			cache: super::cache::Cache::new(self.cache_capacity),
+			timeout: super::timeout::Timeout::default(),
+			locks: self.locks.holder(),
+			active: self.active.register(write),
+			live: self.live.clone(),
+			stats: self.stats.clone(),
+			audit: self.audit.clone(),
+			versions: self.versions.clone(),
+			notifications: vec![],
+			savepoint: 0,
+			savepoints: vec![],
+			undo: vec![],
+			readonly: !write,
+			log: false,
+			fk: false,
+			rewrite: false,
+			changes: vec![],
+			leases: vec![],
+			writes: HashMap::new(),
+			checked: HashSet::new(),
+			stale: HashSet::new(),
+			quotas: HashMap::new(),
+		})
 	}
 
 	/// Parse and execute an SQL query
@@ -251,7 +467,7 @@ impl Datastore {
 		strict: bool,
 	) -> Result<Value, Error> {
 		// Start a new transaction
-		let txn = self.transaction(val.writeable(), false).await?;
+		let txn = self.transaction(val.writeable().into(), false).await?;
 		//
 		let txn = Arc::new(Mutex::new(txn));
 		// Create a new query options
@@ -283,10 +499,76 @@ impl Datastore {
 	/// Performs a full database export as SQL
 	pub async fn export(&self, ns: String, db: String, chn: Sender<Vec<u8>>) -> Result<(), Error> {
 		// Start a new transaction
-		let mut txn = self.transaction(false, false).await?;
+		let mut txn = self.transaction(TransactionType::Read, false).await?;
 		// Process the export
 		txn.export(&ns, &db, chn).await?;
 		// Everything ok
 		Ok(())
 	}
+
+	/// Revert the most recently applied schema migration on a database
+	///
+	/// The down migration script is run within a single write transaction, along with
+	/// the removal of the latest migration log entry and the migration version update,
+	/// so that a failing script leaves the database unchanged. SurrealQL statements are
+	/// run against a transaction which is shared with the statement executor, which a
+	/// `&mut Transaction` can not provide, so this is defined on the datastore rather
+	/// than on a [`Transaction`]. The migration log and version are reverted within the
+	/// same transaction with [`Transaction::revert_migration`].
+	///
+	/// Any `BEGIN`, `COMMIT`, `CANCEL`, and `OPTION` statements in the script are
+	/// ignored, as the script always runs within the rollback transaction, and a
+	/// `USE` statement returns an [`Error::MigrationStatement`].
+	pub async fn rollback_migration(
+		&self,
+		ns: &str,
+		db: &str,
+		down_sql: &str,
+	) -> Result<(), Error> {
+		// Parse the down migration script
+		let ast = sql::parse(down_sql)?;
+		// Start a new transaction
+		let txn = self.transaction(TransactionType::Write, false).await?;
+		//
+		let txn = Arc::new(Mutex::new(txn));
+		// Create a new query options
+		let mut opt = Options::new(Auth::Kv);
+		// Set current NS and DB
+		opt.ns = Some(Arc::new(ns.to_owned()));
+		opt.db = Some(Arc::new(db.to_owned()));
+		// Create a default context
+		let ctx = Context::default();
+		// Process the rollback
+		let res = async {
+			// Revert the latest migration
+			txn.lock().await.revert_migration(ns, db).await?;
+			// Run the down migration script
+			for stm in ast.iter() {
+				match stm {
+					// The script runs within the rollback transaction
+					sql::Statement::Option(_) => continue,
+					sql::Statement::Begin(_) => continue,
+					sql::Statement::Cancel(_) => continue,
+					sql::Statement::Commit(_) => continue,
+					// The script runs on the migrated database
+					sql::Statement::Use(_) => {
+						return Err(Error::MigrationStatement {
+							stm: stm.to_string(),
+						})
+					}
+					_ => stm.compute(&ctx, &opt, &txn, None).await?,
+				};
+			}
+			Ok::<(), Error>(())
+		}
+		.await;
+		// Store any data
+		match res {
+			Ok(_) => txn.lock().await.commit().await,
+			Err(e) => {
+				txn.lock().await.cancel().await?;
+				Err(e)
+			}
+		}
+	}
 }
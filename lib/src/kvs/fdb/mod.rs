@@ -195,6 +195,28 @@ impl Transaction {
 			.map_err(|e| Error::Tx(format!("Unable to get kv from FDB: {}", e)));
 		res
 	}
+	// Fetch many keys from the database
+	pub async fn mget<K>(&mut self, keys: Vec<K>) -> Result<Vec<Option<Val>>, Error>
+	where
+		K: Into<Key>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Get the keys
+		let keys: Vec<Vec<u8>> = keys.into_iter().map(Into::into).collect();
+		let tx = self.tx.lock().await;
+		let tx = tx.as_ref().unwrap();
+		// The get requests are all issued before any are awaited, so that
+		// FDB can coalesce them into as few round-trips as possible.
+		let snapshot = self.snapshot();
+		let res = futures::future::try_join_all(keys.iter().map(|key| tx.get(key, snapshot)))
+			.await
+			.map(|v| v.into_iter().map(|v| v.as_ref().map(|v| Val::from(v.to_vec()))).collect())
+			.map_err(|e| Error::Tx(format!("Unable to get kv from FDB: {}", e)));
+		res
+	}
 	// Insert or update a key in the database
 	pub async fn set<K, V>(&mut self, key: K, val: V) -> Result<(), Error>
 	where
@@ -220,6 +242,27 @@ impl Transaction {
 		// Return result
 		Ok(())
 	}
+	// Insert or update many keys in the database
+	pub async fn mset(&mut self, pairs: Vec<(Key, Val)>) -> Result<(), Error> {
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Check to see if transaction is writable
+		if !self.rw {
+			return Err(Error::TxReadonly);
+		}
+		// Set the keys, all under a single lock of the
+		// transaction, so that FDB receives the writes
+		// as one batch when the transaction commits.
+		let tx = self.tx.lock().await;
+		let tx = tx.as_ref().unwrap();
+		for (key, val) in pairs.iter() {
+			tx.set(&key[..], &val[..]);
+		}
+		// Return result
+		Ok(())
+	}
 	// Insert a key if it doesn't exist in the database
 	//
 	// This function is used when the client sent a CREATE query,
@@ -393,4 +436,86 @@ impl Transaction {
 		}
 		return Ok(res);
 	}
+	// Retrieve a range of keys from the databases, without their values
+	pub async fn scan_keys<K>(&mut self, rng: Range<K>, limit: u32) -> Result<Vec<Key>, Error>
+	where
+		K: Into<Key>,
+	{
+		// The datastore always returns values with the keys
+		let res = self.scan(rng, limit).await?;
+		// Return result
+		Ok(res.into_iter().map(|(k, _)| k).collect())
+	}
+	// Retrieve a range of keys from the databases in reverse order
+	pub async fn scan_rev<K>(&mut self, rng: Range<K>, limit: u32) -> Result<Vec<(Key, Val)>, Error>
+	where
+		K: Into<Key>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Convert the range to bytes
+		let rng: Range<Key> = Range {
+			start: rng.start.into(),
+			end: rng.end.into(),
+		};
+		// Scan the keys
+		let begin: Vec<u8> = rng.start.into();
+		let end: Vec<u8> = rng.end.into();
+		let opt = foundationdb::RangeOption {
+			limit: Some(limit.try_into().unwrap()),
+			reverse: true,
+			..foundationdb::RangeOption::from((begin.as_slice(), end.as_slice()))
+		};
+		let tx = self.tx.lock().await;
+		let tx = tx.as_ref().unwrap();
+		let mut stream = tx.get_ranges_keyvalues(opt, self.snapshot());
+		let mut res: Vec<(Key, Val)> = vec![];
+		loop {
+			let x = stream.try_next().await;
+			match x {
+				Ok(Some(v)) => {
+					let x = (Key::from(v.key()), Val::from(v.value()));
+					res.push(x)
+				}
+				Ok(None) => break,
+				Err(e) => return Err(Error::Tx(format!("GetRanges failed: {}", e).to_string())),
+			}
+		}
+		return Ok(res);
+	}
+	// Fetch the read version of this transaction
+	pub async fn read_version(&mut self) -> Result<u64, Error> {
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Fetch the read version, pinning it for all later reads
+		let tx = self.tx.lock().await;
+		let tx = tx.as_ref().unwrap();
+		match tx.get_read_version().await {
+			Ok(v) => Ok(v as u64),
+			Err(e) => Err(Error::Tx(format!("GetReadVersion failed: {}", e))),
+		}
+	}
+	// Retrieve a range of keys at a specific read version
+	pub async fn scan_at<K>(
+		&mut self,
+		version: u64,
+		rng: Range<K>,
+		limit: u32,
+	) -> Result<Vec<(Key, Val)>, Error>
+	where
+		K: Into<Key>,
+	{
+		// The read version of an FDB transaction can not
+		// be changed once set, so the snapshot must have
+		// been taken from within this transaction.
+		if self.read_version().await? != version {
+			return Err(Error::Tx("The snapshot was not taken in this transaction".to_string()));
+		}
+		// Scan the keys
+		self.scan(rng, limit).await
+	}
 }
@@ -0,0 +1,124 @@
+//! The keys used to sign and verify tokens with each token algorithm.
+
+use crate::sql::algorithm::Algorithm;
+use jsonwebtoken::errors::Error;
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::DecodingKey;
+use jsonwebtoken::EncodingKey;
+use jsonwebtoken::Header;
+use jsonwebtoken::Validation;
+
+/// Get the decoding key and validation for a token definition.
+///
+/// ES512 tokens are not supported by the token library, so an error is
+/// returned for them, rather than verifying them with another algorithm.
+pub fn config(algo: &Algorithm, code: &str) -> Result<(DecodingKey, Validation), Error> {
+	match algo {
+		Algorithm::Hs256 => Ok((
+			DecodingKey::from_secret(code.as_ref()),
+			Validation::new(jsonwebtoken::Algorithm::HS256),
+		)),
+		Algorithm::Hs384 => Ok((
+			DecodingKey::from_secret(code.as_ref()),
+			Validation::new(jsonwebtoken::Algorithm::HS384),
+		)),
+		Algorithm::Hs512 => Ok((
+			DecodingKey::from_secret(code.as_ref()),
+			Validation::new(jsonwebtoken::Algorithm::HS512),
+		)),
+		Algorithm::EdDSA => Ok((
+			DecodingKey::from_ed_pem(code.as_ref())?,
+			Validation::new(jsonwebtoken::Algorithm::EdDSA),
+		)),
+		Algorithm::Es256 => Ok((
+			DecodingKey::from_ec_pem(code.as_ref())?,
+			Validation::new(jsonwebtoken::Algorithm::ES256),
+		)),
+		Algorithm::Es384 => Ok((
+			DecodingKey::from_ec_pem(code.as_ref())?,
+			Validation::new(jsonwebtoken::Algorithm::ES384),
+		)),
+		// ES512 is not supported by the token library
+		Algorithm::Es512 => Err(ErrorKind::InvalidAlgorithm.into()),
+		Algorithm::Ps256 => Ok((
+			DecodingKey::from_rsa_pem(code.as_ref())?,
+			Validation::new(jsonwebtoken::Algorithm::PS256),
+		)),
+		Algorithm::Ps384 => Ok((
+			DecodingKey::from_rsa_pem(code.as_ref())?,
+			Validation::new(jsonwebtoken::Algorithm::PS384),
+		)),
+		Algorithm::Ps512 => Ok((
+			DecodingKey::from_rsa_pem(code.as_ref())?,
+			Validation::new(jsonwebtoken::Algorithm::PS512),
+		)),
+		Algorithm::Rs256 => Ok((
+			DecodingKey::from_rsa_pem(code.as_ref())?,
+			Validation::new(jsonwebtoken::Algorithm::RS256),
+		)),
+		Algorithm::Rs384 => Ok((
+			DecodingKey::from_rsa_pem(code.as_ref())?,
+			Validation::new(jsonwebtoken::Algorithm::RS384),
+		)),
+		Algorithm::Rs512 => Ok((
+			DecodingKey::from_rsa_pem(code.as_ref())?,
+			Validation::new(jsonwebtoken::Algorithm::RS512),
+		)),
+	}
+}
+
+/// Get the encoding key and header for a token definition.
+pub fn signer(algo: &Algorithm, code: &str) -> Result<(EncodingKey, Header), Error> {
+	match algo {
+		Algorithm::Hs256 => Ok((
+			EncodingKey::from_secret(code.as_ref()),
+			Header::new(jsonwebtoken::Algorithm::HS256),
+		)),
+		Algorithm::Hs384 => Ok((
+			EncodingKey::from_secret(code.as_ref()),
+			Header::new(jsonwebtoken::Algorithm::HS384),
+		)),
+		Algorithm::Hs512 => Ok((
+			EncodingKey::from_secret(code.as_ref()),
+			Header::new(jsonwebtoken::Algorithm::HS512),
+		)),
+		Algorithm::EdDSA => Ok((
+			EncodingKey::from_ed_pem(code.as_ref())?,
+			Header::new(jsonwebtoken::Algorithm::EdDSA),
+		)),
+		Algorithm::Es256 => Ok((
+			EncodingKey::from_ec_pem(code.as_ref())?,
+			Header::new(jsonwebtoken::Algorithm::ES256),
+		)),
+		Algorithm::Es384 => Ok((
+			EncodingKey::from_ec_pem(code.as_ref())?,
+			Header::new(jsonwebtoken::Algorithm::ES384),
+		)),
+		// ES512 is not supported by the token library
+		Algorithm::Es512 => Err(ErrorKind::InvalidAlgorithm.into()),
+		Algorithm::Ps256 => Ok((
+			EncodingKey::from_rsa_pem(code.as_ref())?,
+			Header::new(jsonwebtoken::Algorithm::PS256),
+		)),
+		Algorithm::Ps384 => Ok((
+			EncodingKey::from_rsa_pem(code.as_ref())?,
+			Header::new(jsonwebtoken::Algorithm::PS384),
+		)),
+		Algorithm::Ps512 => Ok((
+			EncodingKey::from_rsa_pem(code.as_ref())?,
+			Header::new(jsonwebtoken::Algorithm::PS512),
+		)),
+		Algorithm::Rs256 => Ok((
+			EncodingKey::from_rsa_pem(code.as_ref())?,
+			Header::new(jsonwebtoken::Algorithm::RS256),
+		)),
+		Algorithm::Rs384 => Ok((
+			EncodingKey::from_rsa_pem(code.as_ref())?,
+			Header::new(jsonwebtoken::Algorithm::RS384),
+		)),
+		Algorithm::Rs512 => Ok((
+			EncodingKey::from_rsa_pem(code.as_ref())?,
+			Header::new(jsonwebtoken::Algorithm::RS512),
+		)),
+	}
+}
@@ -0,0 +1,59 @@
+use crate::sql::Value;
+use channel::Sender;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// The table subscriptions which are open on a datastore.
+#[derive(Clone, Default)]
+pub struct Subscriptions(Arc<Mutex<HashMap<Uuid, Sender<LiveMessage>>>>);
+
+impl Subscriptions {
+	// Register the channel of a new subscription
+	pub fn insert(&self, id: Uuid, chn: Sender<LiveMessage>) {
+		self.0.lock().unwrap().insert(id, chn);
+	}
+	// Remove a subscription, closing its channel
+	pub fn remove(&self, id: &Uuid) {
+		if let Some(chn) = self.0.lock().unwrap().remove(id) {
+			chn.close();
+		}
+	}
+	// Check if a subscription is open on this datastore
+	pub fn contains(&self, id: &Uuid) -> bool {
+		self.0.lock().unwrap().contains_key(id)
+	}
+	// Send a message to a subscription
+	pub fn send(&self, msg: LiveMessage) {
+		let mut map = self.0.lock().unwrap();
+		if let Some(chn) = map.get(&msg.id) {
+			// Forget any subscription which is no longer received
+			if chn.try_send(msg.clone()).is_err() {
+				map.remove(&msg.id);
+			}
+		}
+	}
+}
+
+/// The change which caused a live query message to be sent.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LiveAction {
+	/// A record was created
+	Create,
+	/// A record was updated
+	Update,
+	/// A record was deleted
+	Delete,
+}
+
+/// A message sent to a table subscription when a matching record is changed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LiveMessage {
+	/// The id of the live query which matched the record
+	pub id: Uuid,
+	/// The change which was made to the record
+	pub action: LiveAction,
+	/// The record after the change, or the record id if it was deleted
+	pub result: Value,
+}
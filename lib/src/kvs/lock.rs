@@ -0,0 +1,186 @@
+use super::ds;
+use crate::cnf::TABLE_LOCK_LEASE;
+use crate::err::Error;
+use crate::kvs::Key;
+use crate::kvs::Val;
+use chrono::Utc;
+use derive::Store;
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+static OWNER: AtomicU64 = AtomicU64::new(1);
+
+/// The table write locks held on a datastore.
+///
+/// Each lock is stored along with the transaction which holds it, and the
+/// number of guards which that transaction holds for the lock.
+#[derive(Clone, Default)]
+pub struct Locks(Arc<Mutex<HashMap<Key, (u64, usize)>>>);
+
+impl Locks {
+	// Create a lock holder for a new transaction
+	pub fn holder(&self) -> Holder {
+		Holder {
+			locks: self.clone(),
+			owner: OWNER.fetch_add(1, Ordering::Relaxed),
+		}
+	}
+}
+
+/// The table write locks which can be held by a transaction.
+pub struct Holder {
+	locks: Locks,
+	owner: u64,
+}
+
+impl Holder {
+	// Attempt to acquire a lock, which may already be held by this holder
+	pub fn acquire(&self, key: &Key) -> Result<Option<TableLockGuard>, Error> {
+		let mut map = self.locks.0.lock().unwrap();
+		match map.get_mut(key) {
+			Some((owner, _)) if *owner != self.owner => return Ok(None),
+			Some((_, count)) => *count += 1,
+			None => {
+				map.insert(key.clone(), (self.owner, 1));
+			}
+		}
+		Ok(Some(TableLockGuard {
+			locks: self.locks.clone(),
+			owner: self.owner,
+			key: key.clone(),
+		}))
+	}
+	// Check that a lock is not held by another transaction
+	pub fn check(&self, key: &Key) -> Result<(), Error> {
+		match self.locks.0.lock().unwrap().get(key) {
+			Some((v, _)) if *v != self.owner => Err(Error::TableLocked),
+			_ => Ok(()),
+		}
+	}
+}
+
+/// A write lock on a table, which is released when dropped.
+///
+/// A transaction which acquires a lock it already holds receives another guard
+/// for the same lock, and the lock is released once all of its guards are dropped.
+pub struct TableLockGuard {
+	locks: Locks,
+	owner: u64,
+	key: Key,
+}
+
+impl Drop for TableLockGuard {
+	fn drop(&mut self) {
+		let mut map = self.locks.0.lock().unwrap();
+		if let Some((owner, count)) = map.get_mut(&self.key) {
+			if *owner == self.owner {
+				*count -= 1;
+				if *count == 0 {
+					map.remove(&self.key);
+				}
+			}
+		}
+	}
+}
+
+/// A lease on a table write lock, which is stored so that it is held across nodes.
+///
+/// A lease is held by a single transaction until the transaction finishes, at which
+/// point the lease is removed. While the transaction writes to the table, the lease
+/// is renewed once half of its duration has passed. If the node running the
+/// transaction fails, then the lease is left in place, and is ignored by other
+/// transactions once it expires.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Store)]
+struct Lease {
+	owner: Uuid,
+	expires: i64,
+}
+
+impl Lease {
+	// Create a lease for a transaction, starting now
+	fn new(owner: Uuid) -> Lease {
+		Lease {
+			owner,
+			expires: Utc::now().timestamp_millis() + TABLE_LOCK_LEASE.as_millis() as i64,
+		}
+	}
+	// Check if the lease is held by another transaction
+	fn held(&self, owner: &Uuid) -> bool {
+		self.owner != *owner && self.expires > Utc::now().timestamp_millis()
+	}
+}
+
+// The time at which a lease should be renewed, given the time at which it expires
+pub fn renewal(expires: i64) -> i64 {
+	expires - TABLE_LOCK_LEASE.as_millis() as i64 / 2
+}
+
+// Attempt to acquire a lease on a table write lock, returning the time at which it expires
+pub async fn acquire(ds: &ds::Inner, key: Key, owner: Uuid) -> Result<Option<i64>, Error> {
+	let mut tx = ds.transaction(true, false).await?;
+	// Check that the lease is not held elsewhere
+	if let Some(v) = tx.get(key.clone()).await? {
+		if Lease::from(v).held(&owner) {
+			tx.cancel().await?;
+			return Ok(None);
+		}
+	}
+	// Store the lease for this transaction
+	let val = Lease::new(owner);
+	let exp = val.expires;
+	tx.set(key, val.into()).await?;
+	// A conflicting commit means the lease was taken
+	match tx.commit().await {
+		Ok(_) => Ok(Some(exp)),
+		Err(_) => Ok(None),
+	}
+}
+
+// Renew a lease which is held by a transaction, returning the time at which it expires
+//
+// A lease which has already expired can not be renewed, as other transactions
+// may have written to the table since it expired.
+pub async fn renew(ds: &ds::Inner, key: Key, owner: Uuid) -> Result<i64, Error> {
+	let mut tx = ds.transaction(true, false).await?;
+	match tx.get(key.clone()).await?.map(Lease::from) {
+		Some(v) if v.owner == owner && v.expires > Utc::now().timestamp_millis() => {
+			let val = Lease::new(owner);
+			let exp = val.expires;
+			tx.set(key, val.into()).await?;
+			match tx.commit().await {
+				Ok(_) => Ok(exp),
+				Err(_) => Err(Error::TableLockExpired),
+			}
+		}
+		_ => {
+			tx.cancel().await?;
+			Err(Error::TableLockExpired)
+		}
+	}
+}
+
+// Check that a stored lease is not held by another transaction
+pub fn check(val: Option<Val>, owner: &Uuid) -> Result<(), Error> {
+	match val.map(Lease::from) {
+		Some(v) if v.held(owner) => Err(Error::TableLocked),
+		_ => Ok(()),
+	}
+}
+
+// Release a lease which is held by a transaction
+pub async fn release(ds: &ds::Inner, key: Key, owner: Uuid) -> Result<(), Error> {
+	let mut tx = ds.transaction(true, false).await?;
+	match tx.get(key.clone()).await?.map(Lease::from) {
+		Some(v) if v.owner == owner => {
+			tx.del(key).await?;
+			tx.commit().await
+		}
+		_ => tx.cancel().await,
+	}
+}
@@ -3,6 +3,7 @@
 use crate::err::Error;
 use crate::kvs::Key;
 use crate::kvs::Val;
+use std::collections::VecDeque;
 use std::ops::Range;
 
 pub struct Datastore {
@@ -214,4 +215,58 @@ impl Transaction {
 		// Return result
 		Ok(res)
 	}
+	// Retrieve a range of keys from the databases, without their values
+	pub fn scan_keys<K>(&mut self, rng: Range<K>, limit: u32) -> Result<Vec<Key>, Error>
+	where
+		K: Into<Key>,
+	{
+		// The datastore always returns values with the keys
+		let res = self.scan(rng, limit)?;
+		// Return result
+		Ok(res.into_iter().map(|(k, _)| k).collect())
+	}
+	// Retrieve the last keys in a range from the databases, in reverse order
+	//
+	// This reads every key in the range, as the datastore can not be scanned backwards
+	pub fn scan_rev<K>(&mut self, rng: Range<K>, limit: u32) -> Result<Vec<(Key, Val)>, Error>
+	where
+		K: Into<Key>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Check if any keys were requested
+		if limit == 0 {
+			return Ok(vec![]);
+		}
+		// Convert the range to bytes
+		let rng: Range<Key> = Range {
+			start: rng.start.into(),
+			end: rng.end.into(),
+		};
+		// The datastore only scans forwards, so the whole range is
+		// scanned in batches, keeping only the last keys which are found
+		let mut res: VecDeque<(Key, Val)> = VecDeque::new();
+		let mut beg = rng.start;
+		loop {
+			let batch = self.tx.scan(beg.clone()..rng.end.clone(), 1000)?;
+			let done = batch.len() < 1000;
+			if let Some((k, _)) = batch.last() {
+				beg = k.clone();
+				beg.push(0x00);
+			}
+			for v in batch {
+				if res.len() == limit as usize {
+					res.pop_front();
+				}
+				res.push_back(v);
+			}
+			if done {
+				break;
+			}
+		}
+		// Return result
+		Ok(res.into_iter().rev().collect())
+	}
 }
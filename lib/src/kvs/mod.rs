@@ -1,15 +1,28 @@
+mod active;
+mod audit;
 mod cache;
 mod ds;
 mod fdb;
 mod file;
 mod ixdb;
+pub mod jwt;
 mod kv;
+mod live;
+mod lock;
 mod mem;
+mod rocksdb;
+mod stats;
 mod tikv;
+mod timeout;
 mod tx;
+mod versions;
 
+pub use self::active::TxInfo;
 pub use self::ds::*;
 pub use self::kv::*;
+pub use self::live::LiveAction;
+pub use self::live::LiveMessage;
+pub use self::lock::TableLockGuard;
 pub use self::tx::*;
 
 pub const LOG: &str = "surrealdb::kvs";
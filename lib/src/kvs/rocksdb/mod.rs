@@ -0,0 +1,483 @@
+#![cfg(feature = "kv-rocksdb")]
+
+use crate::err::Error;
+use crate::kvs::Key;
+use crate::kvs::Val;
+use futures::lock::Mutex;
+use ouroboros::self_referencing;
+use rocksdb::ReadOptions;
+use rocksdb::TransactionDB;
+use rocksdb::TransactionOptions;
+use rocksdb::WriteOptions;
+use std::ops::Range;
+use std::sync::Arc;
+
+pub struct Datastore {
+	db: Arc<TransactionDB>,
+}
+
+#[self_referencing]
+struct Tx {
+	// The database which the transaction refers to
+	db: Arc<TransactionDB>,
+	// The datastore transaction, which borrows the database
+	#[borrows(db)]
+	#[covariant]
+	tx: Option<rocksdb::Transaction<'this, TransactionDB>>,
+}
+
+pub struct Transaction {
+	// Is the transaction complete?
+	ok: bool,
+	// Is the transaction read+write?
+	rw: bool,
+	// Does the transaction lock the keys which it reads?
+	lock: bool,
+	// The datastore transaction
+	tx: Arc<Mutex<Tx>>,
+}
+
+impl Datastore {
+	// Open a new database
+	pub async fn new(path: &str) -> Result<Datastore, Error> {
+		Ok(Datastore {
+			db: Arc::new(TransactionDB::open_default(path)?),
+		})
+	}
+	// Start a new transaction
+	//
+	// The database is opened as a pessimistic transaction database, so
+	// every write locks the key being written until the transaction is
+	// finished. When a locking transaction is requested, the keys which
+	// are read within a writeable transaction are also locked.
+	pub async fn transaction(&self, write: bool, lock: bool) -> Result<Transaction, Error> {
+		// Take a snapshot when the transaction starts, which
+		// every read is made from, and which the keys written
+		// by the transaction are checked against for conflicts
+		let mut to = TransactionOptions::default();
+		to.set_snapshot(true);
+		// Create a new transaction
+		let tx = TxBuilder {
+			db: self.db.clone(),
+			tx_builder: |db| Some(db.transaction_opt(&WriteOptions::default(), &to)),
+		}
+		.build();
+		Ok(Transaction {
+			ok: false,
+			rw: write,
+			lock: write && lock,
+			tx: Arc::new(Mutex::new(tx)),
+		})
+	}
+}
+
+// Fetch a key from the snapshot of the transaction, locking
+// it if the transaction locks the keys which it reads
+fn fetch(
+	tx: &rocksdb::Transaction<'_, TransactionDB>,
+	key: &Key,
+	lock: bool,
+) -> Result<Option<Val>, Error> {
+	let snap = tx.snapshot();
+	let mut ro = ReadOptions::default();
+	ro.set_snapshot(&snap);
+	Ok(match lock {
+		true => tx.get_for_update_opt(key, true, &ro)?,
+		false => tx.get_opt(key, &ro)?,
+	})
+}
+
+impl Transaction {
+	// Check if closed
+	pub fn closed(&self) -> bool {
+		self.ok
+	}
+	// Cancel a transaction
+	pub async fn cancel(&mut self) -> Result<(), Error> {
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Mark this transaction as done
+		self.ok = true;
+		// Cancel this transaction
+		self.tx.lock().await.with_tx_mut(|tx| match tx.take() {
+			Some(tx) => tx.rollback(),
+			None => Ok(()),
+		})?;
+		// Continue
+		Ok(())
+	}
+	// Commit a transaction
+	pub async fn commit(&mut self) -> Result<(), Error> {
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Check to see if transaction is writable
+		if !self.rw {
+			return Err(Error::TxReadonly);
+		}
+		// Mark this transaction as done
+		self.ok = true;
+		// Commit this transaction
+		self.tx.lock().await.with_tx_mut(|tx| match tx.take() {
+			Some(tx) => tx.commit(),
+			None => Ok(()),
+		})?;
+		// Continue
+		Ok(())
+	}
+	// Check if a key exists
+	pub async fn exi<K>(&mut self, key: K) -> Result<bool, Error>
+	where
+		K: Into<Key>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Get the transaction
+		let tx = self.tx.lock().await;
+		let tx = tx.borrow_tx().as_ref().unwrap();
+		// Check the key
+		let res = fetch(tx, &key.into(), self.lock)?.is_some();
+		// Return result
+		Ok(res)
+	}
+	// Fetch a key from the database
+	pub async fn get<K>(&mut self, key: K) -> Result<Option<Val>, Error>
+	where
+		K: Into<Key>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Get the transaction
+		let tx = self.tx.lock().await;
+		let tx = tx.borrow_tx().as_ref().unwrap();
+		// Get the key
+		let res = fetch(tx, &key.into(), self.lock)?;
+		// Return result
+		Ok(res)
+	}
+	// Insert or update a key in the database
+	pub async fn set<K, V>(&mut self, key: K, val: V) -> Result<(), Error>
+	where
+		K: Into<Key>,
+		V: Into<Val>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Check to see if transaction is writable
+		if !self.rw {
+			return Err(Error::TxReadonly);
+		}
+		// Set the key
+		self.tx.lock().await.borrow_tx().as_ref().unwrap().put(key.into(), val.into())?;
+		// Return result
+		Ok(())
+	}
+	// Insert a key if it doesn't exist in the database
+	pub async fn put<K, V>(&mut self, key: K, val: V) -> Result<(), Error>
+	where
+		K: Into<Key>,
+		V: Into<Val>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Check to see if transaction is writable
+		if !self.rw {
+			return Err(Error::TxReadonly);
+		}
+		// Get the transaction
+		let tx = self.tx.lock().await;
+		let tx = tx.borrow_tx().as_ref().unwrap();
+		// Get the arguments
+		let key = key.into();
+		// Set the key if empty
+		match fetch(tx, &key, true)? {
+			None => tx.put(key, val.into())?,
+			_ => return Err(Error::TxKeyAlreadyExists),
+		};
+		// Return result
+		Ok(())
+	}
+	// Insert a key if it doesn't exist in the database
+	pub async fn putc<K, V>(&mut self, key: K, val: V, chk: Option<V>) -> Result<(), Error>
+	where
+		K: Into<Key>,
+		V: Into<Val>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Check to see if transaction is writable
+		if !self.rw {
+			return Err(Error::TxReadonly);
+		}
+		// Get the transaction
+		let tx = self.tx.lock().await;
+		let tx = tx.borrow_tx().as_ref().unwrap();
+		// Get the arguments
+		let key = key.into();
+		let chk = chk.map(Into::into);
+		// Set the key if valid
+		match (fetch(tx, &key, true)?, chk) {
+			(Some(v), Some(w)) if v == w => tx.put(key, val.into())?,
+			(None, None) => tx.put(key, val.into())?,
+			_ => return Err(Error::TxConditionNotMet),
+		};
+		// Return result
+		Ok(())
+	}
+	// Delete a key
+	pub async fn del<K>(&mut self, key: K) -> Result<(), Error>
+	where
+		K: Into<Key>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Check to see if transaction is writable
+		if !self.rw {
+			return Err(Error::TxReadonly);
+		}
+		// Remove the key
+		self.tx.lock().await.borrow_tx().as_ref().unwrap().delete(key.into())?;
+		// Return result
+		Ok(())
+	}
+	// Delete a key
+	pub async fn delc<K, V>(&mut self, key: K, chk: Option<V>) -> Result<(), Error>
+	where
+		K: Into<Key>,
+		V: Into<Val>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Check to see if transaction is writable
+		if !self.rw {
+			return Err(Error::TxReadonly);
+		}
+		// Get the transaction
+		let tx = self.tx.lock().await;
+		let tx = tx.borrow_tx().as_ref().unwrap();
+		// Get the arguments
+		let key = key.into();
+		let chk = chk.map(Into::into);
+		// Delete the key if valid
+		match (fetch(tx, &key, true)?, chk) {
+			(Some(v), Some(w)) if v == w => tx.delete(key)?,
+			(None, None) => tx.delete(key)?,
+			_ => return Err(Error::TxConditionNotMet),
+		};
+		// Return result
+		Ok(())
+	}
+	// Retrieve a range of keys from the databases
+	pub async fn scan<K>(&mut self, rng: Range<K>, limit: u32) -> Result<Vec<(Key, Val)>, Error>
+	where
+		K: Into<Key>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Get the transaction
+		let tx = self.tx.lock().await;
+		let tx = tx.borrow_tx().as_ref().unwrap();
+		// Set the key range, within the snapshot
+		let snap = tx.snapshot();
+		let mut ro = ReadOptions::default();
+		ro.set_snapshot(&snap);
+		ro.set_iterate_lower_bound(rng.start.into());
+		ro.set_iterate_upper_bound(rng.end.into());
+		// Scan the keys
+		let mut iter = tx.raw_iterator_opt(ro);
+		iter.seek_to_first();
+		let mut res = vec![];
+		while res.len() < limit as usize {
+			match iter.item() {
+				Some((k, v)) => res.push((k.to_vec(), v.to_vec())),
+				None => break,
+			}
+			iter.next();
+		}
+		// Check for any iterator error
+		iter.status()?;
+		// Return result
+		Ok(res)
+	}
+	// Retrieve a range of keys from the databases, without their values
+	pub async fn scan_keys<K>(&mut self, rng: Range<K>, limit: u32) -> Result<Vec<Key>, Error>
+	where
+		K: Into<Key>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Get the transaction
+		let tx = self.tx.lock().await;
+		let tx = tx.borrow_tx().as_ref().unwrap();
+		// Set the key range, within the snapshot
+		let snap = tx.snapshot();
+		let mut ro = ReadOptions::default();
+		ro.set_snapshot(&snap);
+		ro.set_iterate_lower_bound(rng.start.into());
+		ro.set_iterate_upper_bound(rng.end.into());
+		// Scan the keys
+		let mut iter = tx.raw_iterator_opt(ro);
+		iter.seek_to_first();
+		let mut res = vec![];
+		while res.len() < limit as usize {
+			match iter.key() {
+				Some(k) => res.push(k.to_vec()),
+				None => break,
+			}
+			iter.next();
+		}
+		// Check for any iterator error
+		iter.status()?;
+		// Return result
+		Ok(res)
+	}
+	// Retrieve a range of keys from the databases in reverse order
+	pub async fn scan_rev<K>(&mut self, rng: Range<K>, limit: u32) -> Result<Vec<(Key, Val)>, Error>
+	where
+		K: Into<Key>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Get the transaction
+		let tx = self.tx.lock().await;
+		let tx = tx.borrow_tx().as_ref().unwrap();
+		// Set the key range, within the snapshot
+		let snap = tx.snapshot();
+		let mut ro = ReadOptions::default();
+		ro.set_snapshot(&snap);
+		ro.set_iterate_lower_bound(rng.start.into());
+		ro.set_iterate_upper_bound(rng.end.into());
+		// Scan the keys backwards
+		let mut iter = tx.raw_iterator_opt(ro);
+		iter.seek_to_last();
+		let mut res = vec![];
+		while res.len() < limit as usize {
+			match iter.item() {
+				Some((k, v)) => res.push((k.to_vec(), v.to_vec())),
+				None => break,
+			}
+			iter.prev();
+		}
+		// Check for any iterator error
+		iter.status()?;
+		// Return result
+		Ok(res)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// Open a new database in a temporary directory
+	async fn open() -> Datastore {
+		let path = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+		Datastore::new(path.to_str().unwrap()).await.unwrap()
+	}
+
+	#[tokio::test]
+	async fn commit_and_cancel() -> Result<(), Error> {
+		let ds = open().await;
+		// Committed changes are visible
+		let mut tx = ds.transaction(true, false).await?;
+		tx.set("test", "one").await?;
+		tx.commit().await?;
+		let mut tx = ds.transaction(false, false).await?;
+		assert_eq!(tx.get("test").await?, Some(b"one".to_vec()));
+		assert!(matches!(tx.set("test", "two").await, Err(Error::TxReadonly)));
+		tx.cancel().await?;
+		// Cancelled changes are discarded
+		let mut tx = ds.transaction(true, false).await?;
+		tx.set("test", "two").await?;
+		tx.del("test").await?;
+		tx.cancel().await?;
+		assert!(matches!(tx.get("test").await, Err(Error::TxFinished)));
+		let mut tx = ds.transaction(false, false).await?;
+		assert!(tx.exi("test").await?);
+		tx.cancel().await?;
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn conditional_writes() -> Result<(), Error> {
+		let ds = open().await;
+		let mut tx = ds.transaction(true, true).await?;
+		tx.put("test", "one").await?;
+		assert!(matches!(tx.put("test", "two").await, Err(Error::TxKeyAlreadyExists)));
+		tx.putc("test", "two", Some("one")).await?;
+		assert!(matches!(
+			tx.putc("test", "three", Some("one")).await,
+			Err(Error::TxConditionNotMet)
+		));
+		assert!(matches!(tx.delc("test", Some("one")).await, Err(Error::TxConditionNotMet)));
+		tx.delc("test", Some("two")).await?;
+		assert!(!tx.exi("test").await?);
+		tx.commit().await?;
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn scan_ranges() -> Result<(), Error> {
+		let ds = open().await;
+		let mut tx = ds.transaction(true, false).await?;
+		for k in ["a", "b", "c", "d"] {
+			tx.set(k, k).await?;
+		}
+		tx.commit().await?;
+		let mut tx = ds.transaction(false, false).await?;
+		let res = tx.scan("b".."d", 10).await?;
+		assert_eq!(res, vec![(b"b".to_vec(), b"b".to_vec()), (b"c".to_vec(), b"c".to_vec())]);
+		let res = tx.scan_keys("a".."z", 3).await?;
+		assert_eq!(res, vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]);
+		let res = tx.scan_rev("a".."d", 2).await?;
+		assert_eq!(res, vec![(b"c".to_vec(), b"c".to_vec()), (b"b".to_vec(), b"b".to_vec())]);
+		tx.cancel().await?;
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn snapshot_reads() -> Result<(), Error> {
+		let ds = open().await;
+		let mut tx = ds.transaction(false, false).await?;
+		// Changes committed after the transaction started are not visible
+		let mut tc = ds.transaction(true, false).await?;
+		tc.set("a", "a").await?;
+		tc.set("b", "b").await?;
+		tc.commit().await?;
+		assert_eq!(tx.get("a").await?, None);
+		assert_eq!(tx.scan("a".."z", 10).await?, vec![]);
+		assert_eq!(tx.scan_keys("a".."z", 10).await?, Vec::<Key>::new());
+		assert_eq!(tx.scan_rev("a".."z", 10).await?, vec![]);
+		tx.cancel().await?;
+		// Changes made within the transaction are visible
+		let mut tx = ds.transaction(true, false).await?;
+		tx.set("c", "c").await?;
+		assert_eq!(tx.get("c").await?, Some(b"c".to_vec()));
+		assert_eq!(tx.scan_keys("a".."z", 10).await?.len(), 3);
+		tx.cancel().await?;
+		Ok(())
+	}
+}
@@ -0,0 +1,65 @@
+use crate::kvs::Key;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+// The number of table accesses which are counted before they are stored
+const FLUSH_THRESHOLD: u64 = 1000;
+
+/// The table accesses counted on a datastore which have not yet been stored.
+///
+/// Accesses are counted in memory by every transaction, including read-only
+/// transactions, and are added to the stored access counts in batches, so that
+/// reading the records of a table does not write to the datastore.
+#[derive(Clone, Default)]
+pub struct Stats(Arc<Mutex<Pending>>);
+
+#[derive(Default)]
+struct Pending {
+	// The total number of pending accesses
+	total: u64,
+	// The access count and time of the last access, for each table key
+	tables: HashMap<Key, (u64, u64)>,
+}
+
+impl Stats {
+	// Count an access to a table
+	pub fn count(&self, key: Key, at: u64) {
+		let mut p = self.0.lock().unwrap();
+		let v = p.tables.entry(key).or_default();
+		v.0 += 1;
+		v.1 = v.1.max(at);
+		p.total += 1;
+	}
+	// Check if enough accesses have been counted for them to be stored
+	pub fn full(&self) -> bool {
+		self.0.lock().unwrap().total >= FLUSH_THRESHOLD
+	}
+	// Retrieve the pending access count and time of the last access of a table
+	pub fn get(&self, key: &Key) -> Option<(u64, u64)> {
+		self.0.lock().unwrap().tables.get(key).copied()
+	}
+	// Forget the pending accesses of a table
+	pub fn reset(&self, key: &Key) {
+		let mut p = self.0.lock().unwrap();
+		if let Some((num, _)) = p.tables.remove(key) {
+			p.total = p.total.saturating_sub(num);
+		}
+	}
+	// Take all of the pending accesses, so that they can be stored
+	pub fn take(&self) -> HashMap<Key, (u64, u64)> {
+		let mut p = self.0.lock().unwrap();
+		p.total = 0;
+		std::mem::take(&mut p.tables)
+	}
+	// Return accesses which could not be stored to the pending accesses
+	pub fn restore(&self, tables: HashMap<Key, (u64, u64)>) {
+		let mut p = self.0.lock().unwrap();
+		for (key, (num, at)) in tables {
+			let v = p.tables.entry(key).or_default();
+			v.0 += num;
+			v.1 = v.1.max(at);
+			p.total += num;
+		}
+	}
+}
@@ -3,12 +3,26 @@
 use crate::err::Error;
 use crate::kvs::Key;
 use crate::kvs::Val;
+use futures::lock::Mutex;
+use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::ops::Range;
+use std::sync::Arc;
 use tikv::CheckLevel;
+use tikv::Timestamp;
+use tikv::TimestampExt;
 use tikv::TransactionOptions;
 
 pub struct Datastore {
-	db: tikv::TransactionClient,
+	db: Client,
+}
+
+#[derive(Clone)]
+struct Client {
+	// The placement driver endpoint
+	path: String,
+	// The current distributed datastore client
+	db: Arc<Mutex<Arc<tikv::TransactionClient>>>,
 }
 
 pub struct Transaction {
@@ -16,31 +30,54 @@ pub struct Transaction {
 	ok: bool,
 	// Is the transaction read+write?
 	rw: bool,
+	// The distributed datastore client
+	db: Client,
 	// The distributed datastore transaction
 	tx: tikv::Transaction,
 }
 
-impl Datastore {
-	// Open a new database
-	pub async fn new(path: &str) -> Result<Datastore, Error> {
+impl Client {
+	// Connect to the distributed datastore
+	async fn connect(path: &str) -> Result<tikv::TransactionClient, Error> {
 		match tikv::TransactionClient::new(vec![path]).await {
-			Ok(db) => Ok(Datastore {
-				db,
-			}),
+			Ok(db) => Ok(db),
 			Err(e) => Err(Error::Ds(e.to_string())),
 		}
 	}
+	// Replace the current client with a new connection
+	async fn reconnect(&self) -> Result<(), Error> {
+		let db = Client::connect(&self.path).await?;
+		*self.db.lock().await = Arc::new(db);
+		Ok(())
+	}
+}
+
+impl Datastore {
+	// Open a new database
+	pub async fn new(path: &str) -> Result<Datastore, Error> {
+		let db = Client::connect(path).await?;
+		Ok(Datastore {
+			db: Client {
+				path: path.to_owned(),
+				db: Arc::new(Mutex::new(Arc::new(db))),
+			},
+		})
+	}
 	// Start a new transaction
 	pub async fn transaction(&self, write: bool, lock: bool) -> Result<Transaction, Error> {
-		match lock {
+		// Get the current datastore client
+		let db = self.db.db.lock().await.clone();
+		// Read-only transactions never acquire pessimistic locks
+		match lock || !write {
 			true => {
 				// Set the behaviour when dropping an unfinished transaction
 				let opt = TransactionOptions::new_optimistic().drop_check(CheckLevel::Warn);
 				// Create a new optimistic transaction
-				match self.db.begin_with_options(opt).await {
+				match db.begin_with_options(opt).await {
 					Ok(tx) => Ok(Transaction {
 						ok: false,
 						rw: write,
+						db: self.db.clone(),
 						tx,
 					}),
 					Err(e) => Err(Error::Tx(e.to_string())),
@@ -50,10 +87,11 @@ impl Datastore {
 				// Set the behaviour when dropping an unfinished transaction
 				let opt = TransactionOptions::new_pessimistic().drop_check(CheckLevel::Warn);
 				// Create a new pessimistic transaction
-				match self.db.begin_with_options(opt).await {
+				match db.begin_with_options(opt).await {
 					Ok(tx) => Ok(Transaction {
 						ok: false,
 						rw: write,
+						db: self.db.clone(),
 						tx,
 					}),
 					Err(e) => Err(Error::Tx(e.to_string())),
@@ -68,6 +106,13 @@ impl Transaction {
 	pub fn closed(&self) -> bool {
 		self.ok
 	}
+	// Reconnect to the datastore
+	pub async fn reconnect(&mut self) -> Result<(), Error> {
+		// The state of this transaction is unknown
+		self.ok = true;
+		// Replace the datastore client
+		self.db.reconnect().await
+	}
 	// Cancel a transaction
 	pub async fn cancel(&mut self) -> Result<(), Error> {
 		// Check to see if transaction is closed
@@ -126,6 +171,23 @@ impl Transaction {
 		// Return result
 		Ok(res)
 	}
+	// Fetch many keys from the database
+	pub async fn mget<K>(&mut self, keys: Vec<K>) -> Result<Vec<Option<Val>>, Error>
+	where
+		K: Into<Key>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Get the keys
+		let keys: Vec<Key> = keys.into_iter().map(Into::into).collect();
+		let res = self.tx.batch_get(keys.clone()).await?;
+		// Only the keys which exist are returned
+		let mut res: HashMap<Key, Val> = res.map(|kv| (Key::from(kv.0), kv.1)).collect();
+		// Return result
+		Ok(keys.iter().map(|k| res.remove(k)).collect())
+	}
 	// Insert or update a key in the database
 	pub async fn set<K, V>(&mut self, key: K, val: V) -> Result<(), Error>
 	where
@@ -145,6 +207,29 @@ impl Transaction {
 		// Return result
 		Ok(())
 	}
+	// Insert or update many keys in the database
+	pub async fn mset(&mut self, pairs: Vec<(Key, Val)>) -> Result<(), Error> {
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Check to see if transaction is writable
+		if !self.rw {
+			return Err(Error::TxReadonly);
+		}
+		// The TiKV client has no batch write call, so the
+		// keys are set one at a time. Within an optimistic
+		// transaction each write is only buffered locally,
+		// until it is sent to TiKV along with every other
+		// write when the transaction commits, but within a
+		// pessimistic transaction each write first locks
+		// its key with a separate request.
+		for (key, val) in pairs {
+			self.tx.put(key, val).await?;
+		}
+		// Return result
+		Ok(())
+	}
 	// Insert a key if it doesn't exist in the database
 	pub async fn put<K, V>(&mut self, key: K, val: V) -> Result<(), Error>
 	where
@@ -258,4 +343,114 @@ impl Transaction {
 		// Return result
 		Ok(res)
 	}
+	// Retrieve a range of keys from the databases, without their values
+	pub async fn scan_keys<K>(&mut self, rng: Range<K>, limit: u32) -> Result<Vec<Key>, Error>
+	where
+		K: Into<Key>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Convert the range to bytes
+		let rng: Range<Key> = Range {
+			start: rng.start.into(),
+			end: rng.end.into(),
+		};
+		// Scan the keys
+		let res = self.tx.scan_keys(rng, limit).await?;
+		let res = res.map(Key::from).collect();
+		// Return result
+		Ok(res)
+	}
+	// Retrieve the last keys in a range from the databases, in reverse order
+	//
+	// This reads every key in the range, as the client can not scan backwards
+	pub async fn scan_rev<K>(&mut self, rng: Range<K>, limit: u32) -> Result<Vec<(Key, Val)>, Error>
+	where
+		K: Into<Key>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Check if any keys were requested
+		if limit == 0 {
+			return Ok(vec![]);
+		}
+		// Convert the range to bytes
+		let rng: Range<Key> = Range {
+			start: rng.start.into(),
+			end: rng.end.into(),
+		};
+		// The client only scans forwards, so the whole range is
+		// scanned in batches, keeping only the last keys which are found
+		let mut res: VecDeque<(Key, Val)> = VecDeque::new();
+		let mut beg = rng.start;
+		loop {
+			let batch: Vec<(Key, Val)> = self
+				.tx
+				.scan(beg.clone()..rng.end.clone(), 1000)
+				.await?
+				.map(|kv| (Key::from(kv.0), kv.1))
+				.collect();
+			let done = batch.len() < 1000;
+			if let Some((k, _)) = batch.last() {
+				beg = k.clone();
+				beg.push(0x00);
+			}
+			for v in batch {
+				if res.len() == limit as usize {
+					res.pop_front();
+				}
+				res.push_back(v);
+			}
+			if done {
+				break;
+			}
+		}
+		// Return result
+		Ok(res.into_iter().rev().collect())
+	}
+	// Fetch the current read version from the datastore
+	pub async fn read_version(&mut self) -> Result<u64, Error> {
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Fetch the current timestamp
+		let db = self.db.db.lock().await.clone();
+		let res = db.current_timestamp().await?;
+		// Return result
+		Ok(res.version())
+	}
+	// Retrieve a range of keys at a specific read version
+	pub async fn scan_at<K>(
+		&mut self,
+		version: u64,
+		rng: Range<K>,
+		limit: u32,
+	) -> Result<Vec<(Key, Val)>, Error>
+	where
+		K: Into<Key>,
+	{
+		// Check to see if transaction is closed
+		if self.ok {
+			return Err(Error::TxFinished);
+		}
+		// Convert the range to bytes
+		let rng: Range<Key> = Range {
+			start: rng.start.into(),
+			end: rng.end.into(),
+		};
+		// Create a read-only snapshot at the read version
+		let db = self.db.db.lock().await.clone();
+		let opt = TransactionOptions::new_optimistic().drop_check(CheckLevel::None);
+		let mut snp = db.snapshot(Timestamp::from_version(version), opt);
+		// Scan the keys
+		let res = snp.scan(rng, limit).await?;
+		let res = res.map(|kv| (Key::from(kv.0), kv.1)).collect();
+		// Return result
+		Ok(res)
+	}
 }
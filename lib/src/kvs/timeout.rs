@@ -0,0 +1,82 @@
+use crate::err::Error;
+use futures::future::select;
+use futures::future::Either;
+use futures::Future;
+use futures_timer::Delay;
+use std::time::Duration;
+
+#[derive(Default)]
+pub struct Timeout {
+	duration: Option<Duration>,
+	expired: bool,
+}
+
+impl Timeout {
+	// Specify the per-operation timeout
+	pub fn set(&mut self, duration: Duration) {
+		self.duration = Some(duration);
+	}
+	// Check if a previous operation timed out
+	pub fn check(&self) -> Result<(), Error> {
+		match self.expired {
+			true => Err(Error::TxTimedOut),
+			false => Ok(()),
+		}
+	}
+	// Run an operation within the timeout
+	pub async fn run<T, F>(&mut self, fut: F) -> Result<T, Error>
+	where
+		F: Future<Output = Result<T, Error>>,
+	{
+		// Fail fast if a previous operation timed out
+		self.check()?;
+		// Run the operation without a timeout
+		let duration = match self.duration {
+			Some(v) => v,
+			None => return fut.await,
+		};
+		// Race the operation against the timer
+		futures::pin_mut!(fut);
+		match select(fut, Delay::new(duration)).await {
+			Either::Left((res, _)) => res,
+			Either::Right(_) => {
+				self.expired = true;
+				Err(Error::TxTimedOut)
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn operations_within_the_timeout() -> Result<(), Error> {
+		let mut timeout = Timeout::default();
+		timeout.set(Duration::from_secs(10));
+		assert_eq!(timeout.run(async { Ok(1) }).await?, 1);
+		assert_eq!(timeout.run(async { Ok(2) }).await?, 2);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn operations_after_the_timeout() -> Result<(), Error> {
+		let mut timeout = Timeout::default();
+		timeout.set(Duration::from_millis(10));
+		let res = timeout.run(futures::future::pending::<Result<(), Error>>()).await;
+		assert!(matches!(res, Err(Error::TxTimedOut)));
+		// Further operations fail without being run
+		let mut run = false;
+		let res = timeout
+			.run(async {
+				run = true;
+				Ok(())
+			})
+			.await;
+		assert!(matches!(res, Err(Error::TxTimedOut)));
+		assert!(!run);
+		assert!(matches!(timeout.check(), Err(Error::TxTimedOut)));
+		Ok(())
+	}
+}
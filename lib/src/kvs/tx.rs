@@ -2,13 +2,49 @@ use super::kv::Add;
 use super::kv::Convert;
 use super::Key;
 use super::Val;
+use crate::ctx::Context;
+use crate::dbs::Auth;
+use crate::dbs::Options;
 use crate::err::Error;
 use crate::key::thing;
+use crate::kvs::active;
+use crate::kvs::active::Registration;
+use crate::kvs::active::TxInfo;
+use crate::kvs::audit::Audit;
 use crate::kvs::cache::Cache;
 use crate::kvs::cache::Entry;
+use crate::kvs::live::LiveAction;
+use crate::kvs::live::LiveMessage;
+use crate::kvs::live::Subscriptions;
+use crate::kvs::lock;
+use crate::kvs::lock::Holder;
+use crate::kvs::lock::TableLockGuard;
+use crate::kvs::stats::Stats;
+use crate::kvs::timeout::Timeout;
+use crate::kvs::versions::Table;
+use crate::kvs::versions::Versions;
+use crate::kvs::LOG;
 use crate::sql;
+use crate::sql::idiom::Idiom;
+use crate::sql::kind::Kind;
+use crate::sql::number::Number;
+use crate::sql::object::Object;
+use crate::sql::part::Part;
 use crate::sql::thing::Thing;
+use crate::sql::value::Value;
+use channel::Receiver;
 use channel::Sender;
+use chrono::DateTime;
+use chrono::Timelike;
+use chrono::Utc;
+use derive::Store;
+use futures_timer::Delay;
+use geo::algorithm::haversine_distance::HaversineDistance;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use sql::algorithm::Algorithm;
 use sql::permission::Permissions;
 use sql::statements::DefineDatabaseStatement;
 use sql::statements::DefineEventStatement;
@@ -20,13 +56,107 @@ use sql::statements::DefineScopeStatement;
 use sql::statements::DefineTableStatement;
 use sql::statements::DefineTokenStatement;
 use sql::statements::LiveStatement;
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::ops::Range;
 use std::sync::Arc;
+use std::time::Duration;
+use trice::Instant;
+
+type Claims = BTreeMap<String, serde_json::Value>;
+
+// Decode a stored table access count, along with the time of the last access
+fn decode_table_access(v: &[u8]) -> (u64, u64) {
+	let num = v.get(0..8).and_then(|v| v.try_into().ok()).map_or(0, u64::from_be_bytes);
+	let at = v.get(8..16).and_then(|v| v.try_into().ok()).map_or(0, u64::from_be_bytes);
+	(num, at)
+}
+
+// A token definition, along with the database, scope, and nonce it belongs to
+type TokenDef<'a> = (Option<&'a str>, Option<String>, Option<u64>, Algorithm, String);
 
 /// A set of undoable updates and requests against a dataset.
 pub struct Transaction {
-	pub(super) inner: Inner,
+	pub(super) inner: Option<Inner>,
+	pub(super) ds: Arc<super::ds::Inner>,
 	pub(super) cache: Cache,
+	pub(super) timeout: Timeout,
+	pub(super) locks: Holder,
+	pub(super) active: Registration,
+	pub(super) live: Subscriptions,
+	pub(super) stats: Stats,
+	pub(super) audit: Audit,
+	pub(super) versions: Versions,
+	pub(super) notifications: Vec<LiveMessage>,
+	pub(super) savepoint: u32,
+	pub(super) savepoints: Vec<Savepoint>,
+	pub(super) undo: Vec<(Key, Option<Val>)>,
+	pub(super) readonly: bool,
+	pub(super) log: bool,
+	pub(super) fk: bool,
+	pub(super) rewrite: bool,
+	pub(super) changes: Vec<Change>,
+	pub(super) leases: Vec<(Key, i64)>,
+	pub(super) writes: HashMap<Key, Writes>,
+	pub(super) checked: HashSet<Key>,
+	pub(super) stale: HashSet<Key>,
+	pub(super) quotas: HashMap<Key, QuotaDelta>,
+}
+
+// The state of a transaction when a savepoint was created
+pub(super) struct Savepoint {
+	// The identifier of the savepoint
+	id: SavepointId,
+	// The length of the undo log
+	undo: usize,
+	// The number of pending live query messages
+	notifications: usize,
+	// The number of pending table changes
+	changes: usize,
+	// The pending quota usage changes
+	quotas: HashMap<Key, QuotaDelta>,
+	// The definitions checked by stored and deleted records
+	writes: HashMap<Key, Writes>,
+	// The table write leases which have been checked
+	checked: HashSet<Key>,
+	// The views which have been marked as stale
+	stale: HashSet<Key>,
+}
+
+// The definitions which are checked whenever a record in a table is
+// stored or deleted, which are kept for the rest of the transaction
+#[derive(Clone)]
+pub(super) struct Writes {
+	// The quota of the database
+	quota: Option<QuotaDefinition>,
+	// The views which depend on the table
+	views: Arc<Vec<DefineTableStatement>>,
+	// Whether the table has a change feed
+	feed: bool,
+}
+
+// A change which is recorded at the version of
+// the transaction, when the transaction is committed
+pub(super) enum Change {
+	// A record in a table was stored or deleted, along with
+	// the new state of the record, if the table has a change feed
+	Record {
+		ns: String,
+		db: String,
+		tb: String,
+		feed: Option<(crate::sql::id::Id, Val)>,
+	},
+	// A view was refreshed after one of its source tables was changed
+	Refresh {
+		ns: String,
+		db: String,
+		tb: String,
+		ft: String,
+	},
 }
 
 #[allow(clippy::large_enum_variant)]
@@ -37,13 +167,157 @@ pub(super) enum Inner {
 	IxDB(super::ixdb::Transaction),
 	#[cfg(feature = "kv-yokudb")]
 	File(super::file::Transaction),
+	#[cfg(feature = "kv-rocksdb")]
+	RocksDB(super::rocksdb::Transaction),
 	#[cfg(feature = "kv-tikv")]
 	TiKV(super::tikv::Transaction),
 	#[cfg(feature = "kv-fdb")]
 	FDB(super::fdb::Transaction),
 }
 
+impl Inner {
+	// Cancel a transaction which was started beside another transaction
+	pub(super) async fn cancel(&mut self) -> Result<(), Error> {
+		match self {
+			#[cfg(feature = "kv-echodb")]
+			Inner::Mem(v) => v.cancel(),
+			#[cfg(feature = "kv-indxdb")]
+			Inner::IxDB(v) => v.cancel().await,
+			#[cfg(feature = "kv-yokudb")]
+			Inner::File(v) => v.cancel(),
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => v.cancel().await,
+			#[cfg(feature = "kv-tikv")]
+			Inner::TiKV(v) => v.cancel().await,
+			#[cfg(feature = "kv-fdb")]
+			Inner::FDB(v) => v.cancel().await,
+		}
+	}
+	// Commit a transaction which was started beside another transaction
+	pub(super) async fn commit(&mut self) -> Result<(), Error> {
+		match self {
+			#[cfg(feature = "kv-echodb")]
+			Inner::Mem(v) => v.commit(),
+			#[cfg(feature = "kv-indxdb")]
+			Inner::IxDB(v) => v.commit().await,
+			#[cfg(feature = "kv-yokudb")]
+			Inner::File(v) => v.commit(),
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => v.commit().await,
+			#[cfg(feature = "kv-tikv")]
+			Inner::TiKV(v) => v.commit().await,
+			#[cfg(feature = "kv-fdb")]
+			Inner::FDB(v) => v.commit().await,
+		}
+	}
+	// Fetch a key, without the checks of the main transaction
+	pub(super) async fn get(&mut self, key: Key) -> Result<Option<Val>, Error> {
+		match self {
+			#[cfg(feature = "kv-echodb")]
+			Inner::Mem(v) => v.get(key),
+			#[cfg(feature = "kv-indxdb")]
+			Inner::IxDB(v) => v.get(key).await,
+			#[cfg(feature = "kv-yokudb")]
+			Inner::File(v) => v.get(key),
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => v.get(key).await,
+			#[cfg(feature = "kv-tikv")]
+			Inner::TiKV(v) => v.get(key).await,
+			#[cfg(feature = "kv-fdb")]
+			Inner::FDB(v) => v.get(key).await,
+		}
+	}
+	// Insert or update a key, without the checks of the main transaction
+	pub(super) async fn set(&mut self, key: Key, val: Val) -> Result<(), Error> {
+		match self {
+			#[cfg(feature = "kv-echodb")]
+			Inner::Mem(v) => v.set(key, val),
+			#[cfg(feature = "kv-indxdb")]
+			Inner::IxDB(v) => v.set(key, val).await,
+			#[cfg(feature = "kv-yokudb")]
+			Inner::File(v) => v.set(key, val),
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => v.set(key, val).await,
+			#[cfg(feature = "kv-tikv")]
+			Inner::TiKV(v) => v.set(key, val).await,
+			#[cfg(feature = "kv-fdb")]
+			Inner::FDB(v) => v.set(key, val).await,
+		}
+	}
+	// Delete a key, without the checks of the main transaction
+	pub(super) async fn del(&mut self, key: Key) -> Result<(), Error> {
+		match self {
+			#[cfg(feature = "kv-echodb")]
+			Inner::Mem(v) => v.del(key),
+			#[cfg(feature = "kv-indxdb")]
+			Inner::IxDB(v) => v.del(key).await,
+			#[cfg(feature = "kv-yokudb")]
+			Inner::File(v) => v.del(key),
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => v.del(key).await,
+			#[cfg(feature = "kv-tikv")]
+			Inner::TiKV(v) => v.del(key).await,
+			#[cfg(feature = "kv-fdb")]
+			Inner::FDB(v) => v.del(key).await,
+		}
+	}
+	// Retrieve a range of keys, without the checks of the main transaction
+	pub(super) async fn scan(
+		&mut self,
+		rng: Range<Key>,
+		limit: u32,
+	) -> Result<Vec<(Key, Val)>, Error> {
+		match self {
+			#[cfg(feature = "kv-echodb")]
+			Inner::Mem(v) => v.scan(rng, limit),
+			#[cfg(feature = "kv-indxdb")]
+			Inner::IxDB(v) => v.scan(rng, limit).await,
+			#[cfg(feature = "kv-yokudb")]
+			Inner::File(v) => v.scan(rng, limit),
+			#[cfg(feature = "kv-rocksdb")]
+			Inner::RocksDB(v) => v.scan(rng, limit).await,
+			#[cfg(feature = "kv-tikv")]
+			Inner::TiKV(v) => v.scan(rng, limit).await,
+			#[cfg(feature = "kv-fdb")]
+			Inner::FDB(v) => v.scan(rng, limit).await,
+		}
+	}
+}
+
 impl Transaction {
+	/// Limit the duration of each operation on this transaction.
+	///
+	/// If a single call to the underlying datastore does not complete
+	/// within the specified duration, then the call will fail with a
+	/// [`Error::TxTimedOut`] error, and any further calls to functions
+	/// on this transaction, other than [`Transaction::cancel`], will
+	/// fail immediately with the same error.
+	pub fn with_timeout(mut self, duration: Duration) -> Self {
+		self.timeout.set(duration);
+		self
+	}
+	/// Enable access logging on this transaction.
+	///
+	/// Each call to [`Transaction::get`], [`Transaction::set`], and
+	/// [`Transaction::del`] on a key within a database is recorded in
+	/// the access log of that database, within both read-only and
+	/// writeable transactions. Entries are recorded in memory and are
+	/// stored in batches by [`Datastore::tick`](super::Datastore::tick),
+	/// or with [`Datastore::flush_access_log`](super::Datastore::flush_access_log).
+	pub fn with_access_log(mut self) -> Self {
+		self.log = true;
+		self
+	}
+	/// Enable or disable foreign key checking on this transaction.
+	///
+	/// When enabled, each record stored with [`Transaction::set_record`]
+	/// is checked to ensure that the records referenced by its fields of
+	/// type `record(tb)` exist, otherwise the record is not stored, and the
+	/// call will fail with an [`Error::ForeignKeyViolation`] error.
+	pub fn with_fk_checking(mut self, enabled: bool) -> Self {
+		self.fk = enabled;
+		self
+	}
 	/// Check if transactions is finished.
 	///
 	/// If the transaction has been cancelled or committed,
@@ -54,93 +328,278 @@ impl Transaction {
 		match self {
 			#[cfg(feature = "kv-echodb")]
 			Transaction {
-				inner: Inner::Mem(v),
+				inner: Some(Inner::Mem(v)),
 				..
 			} => v.closed(),
 			#[cfg(feature = "kv-yokudb")]
 			Transaction {
-				inner: Inner::File(v),
+				inner: Some(Inner::File(v)),
 				..
 			} => v.closed(),
 			#[cfg(feature = "kv-indxdb")]
 			Transaction {
-				inner: Inner::IxDB(v),
+				inner: Some(Inner::IxDB(v)),
+				..
+			} => v.closed(),
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
 				..
 			} => v.closed(),
 			#[cfg(feature = "kv-tikv")]
 			Transaction {
-				inner: Inner::TiKV(v),
+				inner: Some(Inner::TiKV(v)),
 				..
 			} => v.closed(),
 			#[cfg(feature = "kv-fdb")]
 			Transaction {
-				inner: Inner::FDB(v),
+				inner: Some(Inner::FDB(v)),
 				..
 			} => v.closed(),
+			Transaction {
+				inner: None,
+				..
+			} => true,
 		}
 	}
 	/// Cancel a transaction.
 	///
 	/// This reverses all changes made within the transaction.
 	pub async fn cancel(&mut self) -> Result<(), Error> {
-		match self {
+		self.active.remove();
+		self.notifications.clear();
+		self.savepoints.clear();
+		self.undo.clear();
+		let res = match self {
 			#[cfg(feature = "kv-echodb")]
 			Transaction {
-				inner: Inner::Mem(v),
+				inner: Some(Inner::Mem(v)),
 				..
 			} => v.cancel(),
 			#[cfg(feature = "kv-yokudb")]
 			Transaction {
-				inner: Inner::File(v),
+				inner: Some(Inner::File(v)),
 				..
 			} => v.cancel(),
 			#[cfg(feature = "kv-indxdb")]
 			Transaction {
-				inner: Inner::IxDB(v),
+				inner: Some(Inner::IxDB(v)),
+				..
+			} => v.cancel().await,
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
 				..
 			} => v.cancel().await,
 			#[cfg(feature = "kv-tikv")]
 			Transaction {
-				inner: Inner::TiKV(v),
+				inner: Some(Inner::TiKV(v)),
 				..
 			} => v.cancel().await,
 			#[cfg(feature = "kv-fdb")]
 			Transaction {
-				inner: Inner::FDB(v),
+				inner: Some(Inner::FDB(v)),
 				..
 			} => v.cancel().await,
-		}
+			Transaction {
+				inner: None,
+				..
+			} => Err(Error::TxFinished),
+		};
+		// Release any table write locks
+		self.release_leases().await;
+		// Remove the transaction sentinel
+		self.clear_active().await;
+		res
 	}
 	/// Commit a transaction.
 	///
 	/// This attempts to commit all changes made within the transaction.
 	pub async fn commit(&mut self) -> Result<(), Error> {
-		match self {
+		self.timeout.check()?;
+		self.check_killed().await?;
+		self.poll_killed(true).await?;
+		self.savepoints.clear();
+		self.undo.clear();
+		// Store the pending quota usage
+		self.store_quota_usage().await?;
+		// Record the changes at the version of this transaction
+		let versions = self.versions.clone();
+		let mut clock = match self.changes.is_empty() {
+			true => None,
+			false => Some(versions.clock().await),
+		};
+		let version = match &clock {
+			Some(last) => {
+				let now = Utc::now().timestamp_micros() as u64;
+				let version = std::cmp::max(**last + 1, now);
+				self.record_changes(version).await?;
+				version
+			}
+			None => 0,
+		};
+		self.active.remove();
+		let res = match self {
 			#[cfg(feature = "kv-echodb")]
 			Transaction {
-				inner: Inner::Mem(v),
+				inner: Some(Inner::Mem(v)),
 				..
 			} => v.commit(),
 			#[cfg(feature = "kv-yokudb")]
 			Transaction {
-				inner: Inner::File(v),
+				inner: Some(Inner::File(v)),
 				..
 			} => v.commit(),
 			#[cfg(feature = "kv-indxdb")]
 			Transaction {
-				inner: Inner::IxDB(v),
+				inner: Some(Inner::IxDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.commit()).await,
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
+				timeout,
 				..
-			} => v.commit().await,
+			} => timeout.run(v.commit()).await,
 			#[cfg(feature = "kv-tikv")]
 			Transaction {
-				inner: Inner::TiKV(v),
+				inner: Some(Inner::TiKV(v)),
+				timeout,
 				..
-			} => v.commit().await,
+			} => timeout.run(v.commit()).await,
 			#[cfg(feature = "kv-fdb")]
 			Transaction {
-				inner: Inner::FDB(v),
+				inner: Some(Inner::FDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.commit()).await,
+			Transaction {
+				inner: None,
 				..
-			} => v.commit().await,
+			} => Err(Error::TxFinished),
+		};
+		// Later transactions are given later versions
+		if let Some(last) = clock.as_mut() {
+			**last = version;
+		}
+		drop(clock);
+		// Release any table write locks
+		self.release_leases().await;
+		// Remove the transaction sentinel
+		self.clear_active().await;
+		// Send any live query messages
+		if res.is_ok() {
+			for msg in self.notifications.drain(..) {
+				self.live.send(msg);
+			}
+		}
+		res
+	}
+	/// Reconnect to the underlying datastore.
+	///
+	/// This should be called after an operation returns an [`Error::ConnectionLost`]
+	/// error. As the state of the transaction on the underlying datastore is unknown,
+	/// this transaction is aborted, and a new transaction should then be started on
+	/// the datastore once this function returns successfully.
+	pub async fn reconnect(&mut self) -> Result<(), Error> {
+		// Re-establish the connection
+		#[cfg(feature = "kv-tikv")]
+		if let Transaction {
+			inner: Some(Inner::TiKV(v)),
+			..
+		} = self
+		{
+			return v.reconnect().await;
+		}
+		// Abort this transaction
+		if !self.closed().await {
+			let _ = self.cancel().await;
+		}
+		Ok(())
+	}
+	/// Create a savepoint within this transaction.
+	///
+	/// All changes made to the datastore after the savepoint has been created
+	/// can be undone with [`Transaction::rollback_to`], without affecting the
+	/// changes made before it. While any savepoints are held, the previous value
+	/// of each key is fetched before the key is changed, so savepoints should be
+	/// released with [`Transaction::release_savepoint`] once they are not needed.
+	pub fn savepoint(&mut self) -> Result<SavepointId, Error> {
+		self.timeout.check()?;
+		self.check_readonly()?;
+		self.savepoint += 1;
+		let id = SavepointId(self.savepoint);
+		self.savepoints.push(Savepoint {
+			id,
+			undo: self.undo.len(),
+			notifications: self.notifications.len(),
+			changes: self.changes.len(),
+			quotas: self.quotas.clone(),
+			writes: self.writes.clone(),
+			checked: self.checked.clone(),
+			stale: self.stale.clone(),
+		});
+		Ok(id)
+	}
+	/// Undo all changes made after a savepoint was created.
+	///
+	/// The savepoint is kept, and can be rolled back to again, but any savepoints
+	/// created after it are released.
+	pub async fn rollback_to(&mut self, id: SavepointId) -> Result<(), Error> {
+		let pos = match self.savepoints.iter().position(|v| v.id == id) {
+			Some(v) => v,
+			None => return Err(Error::InvalidSavepoint),
+		};
+		let Savepoint {
+			undo,
+			notifications,
+			changes,
+			..
+		} = self.savepoints[pos];
+		// Prevent the undone changes from being recorded
+		let mut savepoints = std::mem::take(&mut self.savepoints);
+		savepoints.truncate(pos + 1);
+		// Restore the previous values in reverse
+		let log = self.undo.split_off(undo);
+		let mut res = Ok(());
+		for (key, val) in log.into_iter().rev() {
+			res = match val {
+				Some(val) => self.set(key, val).await,
+				None => self.del(key).await,
+			};
+			if res.is_err() {
+				break;
+			}
+		}
+		self.savepoints = savepoints;
+		// Drop any undone live query messages
+		self.notifications.truncate(notifications);
+		// Drop any undone table changes
+		self.changes.truncate(changes);
+		// Restore the previous state of the transaction
+		let sp = &self.savepoints[pos];
+		self.quotas = sp.quotas.clone();
+		self.writes = sp.writes.clone();
+		self.checked = sp.checked.clone();
+		self.stale = sp.stale.clone();
+		// Clear any cached definitions
+		self.cache.clear();
+		res
+	}
+	/// Release a savepoint, along with any savepoints created after it.
+	///
+	/// The changes made after the savepoint was created are kept.
+	pub fn release_savepoint(&mut self, id: SavepointId) -> Result<(), Error> {
+		match self.savepoints.iter().position(|v| v.id == id) {
+			Some(pos) => {
+				self.savepoints.truncate(pos);
+				if self.savepoints.is_empty() {
+					self.undo.clear();
+				}
+				Ok(())
+			}
+			None => Err(Error::InvalidSavepoint),
 		}
 	}
 	/// Delete a key from the datastore.
@@ -148,32 +607,51 @@ impl Transaction {
 	where
 		K: Into<Key>,
 	{
+		self.timeout.check()?;
+		self.check_readonly()?;
+		self.check_killed().await?;
+		let key = key.into();
+		self.log_access("del", &key);
+		self.save_undo(&key).await?;
 		match self {
 			#[cfg(feature = "kv-echodb")]
 			Transaction {
-				inner: Inner::Mem(v),
+				inner: Some(Inner::Mem(v)),
 				..
 			} => v.del(key),
 			#[cfg(feature = "kv-yokudb")]
 			Transaction {
-				inner: Inner::File(v),
+				inner: Some(Inner::File(v)),
 				..
 			} => v.del(key),
 			#[cfg(feature = "kv-indxdb")]
 			Transaction {
-				inner: Inner::IxDB(v),
+				inner: Some(Inner::IxDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.del(key)).await,
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
+				timeout,
 				..
-			} => v.del(key).await,
+			} => timeout.run(v.del(key)).await,
 			#[cfg(feature = "kv-tikv")]
 			Transaction {
-				inner: Inner::TiKV(v),
+				inner: Some(Inner::TiKV(v)),
+				timeout,
 				..
-			} => v.del(key).await,
+			} => timeout.run(v.del(key)).await,
 			#[cfg(feature = "kv-fdb")]
 			Transaction {
-				inner: Inner::FDB(v),
+				inner: Some(Inner::FDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.del(key)).await,
+			Transaction {
+				inner: None,
 				..
-			} => v.del(key).await,
+			} => Err(Error::TxFinished),
 		}
 	}
 	/// Check if a key exists in the datastore.
@@ -181,32 +659,47 @@ impl Transaction {
 	where
 		K: Into<Key>,
 	{
+		self.timeout.check()?;
+		self.check_killed().await?;
 		match self {
 			#[cfg(feature = "kv-echodb")]
 			Transaction {
-				inner: Inner::Mem(v),
+				inner: Some(Inner::Mem(v)),
 				..
 			} => v.exi(key),
 			#[cfg(feature = "kv-yokudb")]
 			Transaction {
-				inner: Inner::File(v),
+				inner: Some(Inner::File(v)),
 				..
 			} => v.exi(key),
 			#[cfg(feature = "kv-indxdb")]
 			Transaction {
-				inner: Inner::IxDB(v),
+				inner: Some(Inner::IxDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.exi(key)).await,
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
+				timeout,
 				..
-			} => v.exi(key).await,
+			} => timeout.run(v.exi(key)).await,
 			#[cfg(feature = "kv-tikv")]
 			Transaction {
-				inner: Inner::TiKV(v),
+				inner: Some(Inner::TiKV(v)),
+				timeout,
 				..
-			} => v.exi(key).await,
+			} => timeout.run(v.exi(key)).await,
 			#[cfg(feature = "kv-fdb")]
 			Transaction {
-				inner: Inner::FDB(v),
+				inner: Some(Inner::FDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.exi(key)).await,
+			Transaction {
+				inner: None,
 				..
-			} => v.exi(key).await,
+			} => Err(Error::TxFinished),
 		}
 	}
 	/// Fetch a key from the datastore.
@@ -214,32 +707,117 @@ impl Transaction {
 	where
 		K: Into<Key>,
 	{
+		self.timeout.check()?;
+		self.check_killed().await?;
+		let key = key.into();
+		self.log_access("get", &key);
 		match self {
 			#[cfg(feature = "kv-echodb")]
 			Transaction {
-				inner: Inner::Mem(v),
+				inner: Some(Inner::Mem(v)),
 				..
 			} => v.get(key),
 			#[cfg(feature = "kv-yokudb")]
 			Transaction {
-				inner: Inner::File(v),
+				inner: Some(Inner::File(v)),
 				..
 			} => v.get(key),
 			#[cfg(feature = "kv-indxdb")]
 			Transaction {
-				inner: Inner::IxDB(v),
+				inner: Some(Inner::IxDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.get(key)).await,
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.get(key)).await,
+			#[cfg(feature = "kv-tikv")]
+			Transaction {
+				inner: Some(Inner::TiKV(v)),
+				timeout,
+				..
+			} => timeout.run(v.get(key)).await,
+			#[cfg(feature = "kv-fdb")]
+			Transaction {
+				inner: Some(Inner::FDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.get(key)).await,
+			Transaction {
+				inner: None,
+				..
+			} => Err(Error::TxFinished),
+		}
+	}
+	/// Fetch many keys from the datastore.
+	///
+	/// The returned values are in the same order as the requested keys, with [`None`]
+	/// for each key which does not exist. On TiKV and FoundationDB the keys are fetched
+	/// in a single batch request, and on the embedded datastores one key at a time.
+	pub async fn mget<K>(&mut self, keys: Vec<K>) -> Result<Vec<Option<Val>>, Error>
+	where
+		K: Into<Key>,
+	{
+		self.timeout.check()?;
+		self.check_killed().await?;
+		let keys: Vec<Key> = keys.into_iter().map(Into::into).collect();
+		for key in keys.iter() {
+			self.log_access("get", key);
+		}
+		match self {
+			#[cfg(feature = "kv-echodb")]
+			Transaction {
+				inner: Some(Inner::Mem(v)),
+				..
+			} => keys.into_iter().map(|k| v.get(k)).collect(),
+			#[cfg(feature = "kv-yokudb")]
+			Transaction {
+				inner: Some(Inner::File(v)),
+				..
+			} => keys.into_iter().map(|k| v.get(k)).collect(),
+			#[cfg(feature = "kv-indxdb")]
+			Transaction {
+				inner: Some(Inner::IxDB(v)),
+				timeout,
+				..
+			} => {
+				let mut out = Vec::with_capacity(keys.len());
+				for k in keys {
+					out.push(timeout.run(v.get(k)).await?);
+				}
+				Ok(out)
+			}
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
+				timeout,
 				..
-			} => v.get(key).await,
+			} => {
+				let mut out = Vec::with_capacity(keys.len());
+				for k in keys {
+					out.push(timeout.run(v.get(k)).await?);
+				}
+				Ok(out)
+			}
 			#[cfg(feature = "kv-tikv")]
 			Transaction {
-				inner: Inner::TiKV(v),
+				inner: Some(Inner::TiKV(v)),
+				timeout,
 				..
-			} => v.get(key).await,
+			} => timeout.run(v.mget(keys)).await,
 			#[cfg(feature = "kv-fdb")]
 			Transaction {
-				inner: Inner::FDB(v),
+				inner: Some(Inner::FDB(v)),
+				timeout,
 				..
-			} => v.get(key).await,
+			} => timeout.run(v.mget(keys)).await,
+			Transaction {
+				inner: None,
+				..
+			} => Err(Error::TxFinished),
 		}
 	}
 	/// Insert or update a key in the datastore.
@@ -248,32 +826,127 @@ impl Transaction {
 		K: Into<Key>,
 		V: Into<Val>,
 	{
+		self.timeout.check()?;
+		self.check_readonly()?;
+		self.check_killed().await?;
+		let key = key.into();
+		self.log_access("set", &key);
+		self.save_undo(&key).await?;
 		match self {
 			#[cfg(feature = "kv-echodb")]
 			Transaction {
-				inner: Inner::Mem(v),
+				inner: Some(Inner::Mem(v)),
 				..
 			} => v.set(key, val),
 			#[cfg(feature = "kv-yokudb")]
 			Transaction {
-				inner: Inner::File(v),
+				inner: Some(Inner::File(v)),
 				..
 			} => v.set(key, val),
 			#[cfg(feature = "kv-indxdb")]
 			Transaction {
-				inner: Inner::IxDB(v),
+				inner: Some(Inner::IxDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.set(key, val)).await,
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.set(key, val)).await,
+			#[cfg(feature = "kv-tikv")]
+			Transaction {
+				inner: Some(Inner::TiKV(v)),
+				timeout,
+				..
+			} => timeout.run(v.set(key, val)).await,
+			#[cfg(feature = "kv-fdb")]
+			Transaction {
+				inner: Some(Inner::FDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.set(key, val)).await,
+			Transaction {
+				inner: None,
+				..
+			} => Err(Error::TxFinished),
+		}
+	}
+	/// Insert or update many keys in the datastore.
+	///
+	/// The keys are validated before any of them are written, so that an invalid
+	/// key causes the whole batch to be rejected. On FoundationDB the keys are written
+	/// to the underlying transaction in a single call. The TiKV client has no batch
+	/// write call, so on TiKV, as on the other datastores, the keys are written one
+	/// after another. Writes to an optimistic TiKV transaction are buffered until it
+	/// commits, but each write to a pessimistic TiKV transaction locks its key with
+	/// a separate request.
+	pub async fn set_batch<K, V>(&mut self, pairs: Vec<(K, V)>) -> Result<(), Error>
+	where
+		K: Into<Key>,
+		V: Into<Val>,
+	{
+		self.timeout.check()?;
+		self.check_readonly()?;
+		self.check_killed().await?;
+		let pairs: Vec<(Key, Val)> = pairs.into_iter().map(|(k, v)| (k.into(), v.into())).collect();
+		if pairs.iter().any(|(k, _)| k.is_empty()) {
+			return Err(Error::TxKeyEmpty);
+		}
+		for (key, _) in pairs.iter() {
+			self.log_access("set", key);
+			self.save_undo(key).await?;
+		}
+		match self {
+			#[cfg(feature = "kv-echodb")]
+			Transaction {
+				inner: Some(Inner::Mem(v)),
+				..
+			} => pairs.into_iter().try_for_each(|(k, x)| v.set(k, x)),
+			#[cfg(feature = "kv-yokudb")]
+			Transaction {
+				inner: Some(Inner::File(v)),
+				..
+			} => pairs.into_iter().try_for_each(|(k, x)| v.set(k, x)),
+			#[cfg(feature = "kv-indxdb")]
+			Transaction {
+				inner: Some(Inner::IxDB(v)),
+				timeout,
+				..
+			} => {
+				for (k, x) in pairs {
+					timeout.run(v.set(k, x)).await?;
+				}
+				Ok(())
+			}
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
+				timeout,
 				..
-			} => v.set(key, val).await,
+			} => {
+				for (k, x) in pairs {
+					timeout.run(v.set(k, x)).await?;
+				}
+				Ok(())
+			}
 			#[cfg(feature = "kv-tikv")]
 			Transaction {
-				inner: Inner::TiKV(v),
+				inner: Some(Inner::TiKV(v)),
+				timeout,
 				..
-			} => v.set(key, val).await,
+			} => timeout.run(v.mset(pairs)).await,
 			#[cfg(feature = "kv-fdb")]
 			Transaction {
-				inner: Inner::FDB(v),
+				inner: Some(Inner::FDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.mset(pairs)).await,
+			Transaction {
+				inner: None,
 				..
-			} => v.set(key, val).await,
+			} => Err(Error::TxFinished),
 		}
 	}
 	/// Insert a key if it doesn't exist in the datastore.
@@ -282,32 +955,50 @@ impl Transaction {
 		K: Into<Key>,
 		V: Into<Val>,
 	{
+		self.timeout.check()?;
+		self.check_readonly()?;
+		self.check_killed().await?;
+		let key = key.into();
+		self.save_undo(&key).await?;
 		match self {
 			#[cfg(feature = "kv-echodb")]
 			Transaction {
-				inner: Inner::Mem(v),
+				inner: Some(Inner::Mem(v)),
 				..
 			} => v.put(key, val),
 			#[cfg(feature = "kv-yokudb")]
 			Transaction {
-				inner: Inner::File(v),
+				inner: Some(Inner::File(v)),
 				..
 			} => v.put(key, val),
 			#[cfg(feature = "kv-indxdb")]
 			Transaction {
-				inner: Inner::IxDB(v),
+				inner: Some(Inner::IxDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.put(key, val)).await,
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
+				timeout,
 				..
-			} => v.put(key, val).await,
+			} => timeout.run(v.put(key, val)).await,
 			#[cfg(feature = "kv-tikv")]
 			Transaction {
-				inner: Inner::TiKV(v),
+				inner: Some(Inner::TiKV(v)),
+				timeout,
 				..
-			} => v.put(key, val).await,
+			} => timeout.run(v.put(key, val)).await,
 			#[cfg(feature = "kv-fdb")]
 			Transaction {
-				inner: Inner::FDB(v),
+				inner: Some(Inner::FDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.put(key, val)).await,
+			Transaction {
+				inner: None,
 				..
-			} => v.put(key, val).await,
+			} => Err(Error::TxFinished),
 		}
 	}
 	/// Retrieve a specific range of keys from the datastore.
@@ -317,32 +1008,152 @@ impl Transaction {
 	where
 		K: Into<Key>,
 	{
+		self.timeout.check()?;
+		self.check_killed().await?;
 		match self {
 			#[cfg(feature = "kv-echodb")]
 			Transaction {
-				inner: Inner::Mem(v),
+				inner: Some(Inner::Mem(v)),
 				..
 			} => v.scan(rng, limit),
 			#[cfg(feature = "kv-yokudb")]
 			Transaction {
-				inner: Inner::File(v),
+				inner: Some(Inner::File(v)),
 				..
 			} => v.scan(rng, limit),
 			#[cfg(feature = "kv-indxdb")]
 			Transaction {
-				inner: Inner::IxDB(v),
+				inner: Some(Inner::IxDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.scan(rng, limit)).await,
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.scan(rng, limit)).await,
+			#[cfg(feature = "kv-tikv")]
+			Transaction {
+				inner: Some(Inner::TiKV(v)),
+				timeout,
+				..
+			} => timeout.run(v.scan(rng, limit)).await,
+			#[cfg(feature = "kv-fdb")]
+			Transaction {
+				inner: Some(Inner::FDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.scan(rng, limit)).await,
+			Transaction {
+				inner: None,
+				..
+			} => Err(Error::TxFinished),
+		}
+	}
+	/// Retrieve a specific range of keys from the datastore, without their values.
+	///
+	/// This function fetches the full range of keys, in a single request to the underlying datastore.
+	/// Only TiKV can scan keys natively, so the other datastores still read the values internally.
+	pub async fn scan_keys<K>(&mut self, rng: Range<K>, limit: u32) -> Result<Vec<Key>, Error>
+	where
+		K: Into<Key>,
+	{
+		self.timeout.check()?;
+		self.check_killed().await?;
+		match self {
+			#[cfg(feature = "kv-echodb")]
+			Transaction {
+				inner: Some(Inner::Mem(v)),
+				..
+			} => v.scan_keys(rng, limit),
+			#[cfg(feature = "kv-yokudb")]
+			Transaction {
+				inner: Some(Inner::File(v)),
+				..
+			} => v.scan_keys(rng, limit),
+			#[cfg(feature = "kv-indxdb")]
+			Transaction {
+				inner: Some(Inner::IxDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.scan_keys(rng, limit)).await,
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.scan_keys(rng, limit)).await,
+			#[cfg(feature = "kv-tikv")]
+			Transaction {
+				inner: Some(Inner::TiKV(v)),
+				timeout,
+				..
+			} => timeout.run(v.scan_keys(rng, limit)).await,
+			#[cfg(feature = "kv-fdb")]
+			Transaction {
+				inner: Some(Inner::FDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.scan_keys(rng, limit)).await,
+			Transaction {
+				inner: None,
+				..
+			} => Err(Error::TxFinished),
+		}
+	}
+	/// Retrieve a specific range of keys from the datastore, in descending key order.
+	///
+	/// On FoundationDB and RocksDB, this function fetches the last `limit` key-value pairs, in a single
+	/// reverse scan of the underlying datastore. The TiKV client and the in-memory, file, and IndexedDB
+	/// datastores only scan forwards, so on those datastores every key-value pair in the range is read, in
+	/// batches of 1000, and only the last `limit` key-value pairs are kept. The cost of the scan is then
+	/// proportional to the size of the whole range, rather than to `limit`.
+	pub async fn scan_rev<K>(&mut self, rng: Range<K>, limit: u32) -> Result<Vec<(Key, Val)>, Error>
+	where
+		K: Into<Key>,
+	{
+		self.timeout.check()?;
+		self.check_killed().await?;
+		match self {
+			#[cfg(feature = "kv-echodb")]
+			Transaction {
+				inner: Some(Inner::Mem(v)),
+				..
+			} => v.scan_rev(rng, limit),
+			#[cfg(feature = "kv-yokudb")]
+			Transaction {
+				inner: Some(Inner::File(v)),
+				..
+			} => v.scan_rev(rng, limit),
+			#[cfg(feature = "kv-indxdb")]
+			Transaction {
+				inner: Some(Inner::IxDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.scan_rev(rng, limit)).await,
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
+				timeout,
 				..
-			} => v.scan(rng, limit).await,
+			} => timeout.run(v.scan_rev(rng, limit)).await,
 			#[cfg(feature = "kv-tikv")]
 			Transaction {
-				inner: Inner::TiKV(v),
+				inner: Some(Inner::TiKV(v)),
+				timeout,
 				..
-			} => v.scan(rng, limit).await,
+			} => timeout.run(v.scan_rev(rng, limit)).await,
 			#[cfg(feature = "kv-fdb")]
 			Transaction {
-				inner: Inner::FDB(v),
+				inner: Some(Inner::FDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.scan_rev(rng, limit)).await,
+			Transaction {
+				inner: None,
 				..
-			} => v.scan(rng, limit).await,
+			} => Err(Error::TxFinished),
 		}
 	}
 	/// Update a key in the datastore if the current value matches a condition.
@@ -351,32 +1162,50 @@ impl Transaction {
 		K: Into<Key>,
 		V: Into<Val>,
 	{
+		self.timeout.check()?;
+		self.check_readonly()?;
+		self.check_killed().await?;
+		let key = key.into();
+		self.save_undo(&key).await?;
 		match self {
 			#[cfg(feature = "kv-echodb")]
 			Transaction {
-				inner: Inner::Mem(v),
+				inner: Some(Inner::Mem(v)),
 				..
 			} => v.putc(key, val, chk),
 			#[cfg(feature = "kv-yokudb")]
 			Transaction {
-				inner: Inner::File(v),
+				inner: Some(Inner::File(v)),
 				..
 			} => v.putc(key, val, chk),
 			#[cfg(feature = "kv-indxdb")]
 			Transaction {
-				inner: Inner::IxDB(v),
+				inner: Some(Inner::IxDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.putc(key, val, chk)).await,
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
+				timeout,
 				..
-			} => v.putc(key, val, chk).await,
+			} => timeout.run(v.putc(key, val, chk)).await,
 			#[cfg(feature = "kv-tikv")]
 			Transaction {
-				inner: Inner::TiKV(v),
+				inner: Some(Inner::TiKV(v)),
+				timeout,
 				..
-			} => v.putc(key, val, chk).await,
+			} => timeout.run(v.putc(key, val, chk)).await,
 			#[cfg(feature = "kv-fdb")]
 			Transaction {
-				inner: Inner::FDB(v),
+				inner: Some(Inner::FDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.putc(key, val, chk)).await,
+			Transaction {
+				inner: None,
 				..
-			} => v.putc(key, val, chk).await,
+			} => Err(Error::TxFinished),
 		}
 	}
 	/// Delete a key from the datastore if the current value matches a condition.
@@ -385,33 +1214,245 @@ impl Transaction {
 		K: Into<Key>,
 		V: Into<Val>,
 	{
+		self.timeout.check()?;
+		self.check_readonly()?;
+		self.check_killed().await?;
+		let key = key.into();
+		self.save_undo(&key).await?;
 		match self {
 			#[cfg(feature = "kv-echodb")]
 			Transaction {
-				inner: Inner::Mem(v),
+				inner: Some(Inner::Mem(v)),
 				..
 			} => v.delc(key, chk),
 			#[cfg(feature = "kv-yokudb")]
 			Transaction {
-				inner: Inner::File(v),
+				inner: Some(Inner::File(v)),
 				..
 			} => v.delc(key, chk),
 			#[cfg(feature = "kv-indxdb")]
 			Transaction {
-				inner: Inner::IxDB(v),
+				inner: Some(Inner::IxDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.delc(key, chk)).await,
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
+				timeout,
 				..
-			} => v.delc(key, chk).await,
+			} => timeout.run(v.delc(key, chk)).await,
 			#[cfg(feature = "kv-tikv")]
 			Transaction {
-				inner: Inner::TiKV(v),
+				inner: Some(Inner::TiKV(v)),
+				timeout,
 				..
-			} => v.delc(key, chk).await,
+			} => timeout.run(v.delc(key, chk)).await,
 			#[cfg(feature = "kv-fdb")]
 			Transaction {
-				inner: Inner::FDB(v),
+				inner: Some(Inner::FDB(v)),
+				timeout,
 				..
-			} => v.delc(key, chk).await,
-		}
+			} => timeout.run(v.delc(key, chk)).await,
+			Transaction {
+				inner: None,
+				..
+			} => Err(Error::TxFinished),
+		}
+	}
+	/// Pin a consistent read snapshot on the datastore.
+	///
+	/// The returned token can be passed to [`Transaction::scan_with_snapshot`] and
+	/// [`Transaction::getr_with_snapshot`], so that scans across several tables all see
+	/// the datastore at a single point in time. On TiKV the token pins a timestamp from
+	/// the placement driver, and can be shared with other transactions. On FoundationDB
+	/// the token pins the read version of this transaction. The embedded datastores read
+	/// from a single snapshot for the lifetime of a transaction, so the token only needs
+	/// to be used within the transaction which created it.
+	pub async fn begin_consistent_snapshot(&mut self) -> Result<SnapshotToken, Error> {
+		self.timeout.check()?;
+		self.check_killed().await?;
+		if self.closed().await {
+			return Err(Error::TxFinished);
+		}
+		let version = match self {
+			#[cfg(feature = "kv-echodb")]
+			Transaction {
+				inner: Some(Inner::Mem(_)),
+				..
+			} => 0,
+			#[cfg(feature = "kv-yokudb")]
+			Transaction {
+				inner: Some(Inner::File(_)),
+				..
+			} => 0,
+			#[cfg(feature = "kv-indxdb")]
+			Transaction {
+				inner: Some(Inner::IxDB(_)),
+				..
+			} => 0,
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(_)),
+				..
+			} => 0,
+			#[cfg(feature = "kv-tikv")]
+			Transaction {
+				inner: Some(Inner::TiKV(v)),
+				timeout,
+				..
+			} => timeout.run(v.read_version()).await?,
+			#[cfg(feature = "kv-fdb")]
+			Transaction {
+				inner: Some(Inner::FDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.read_version()).await?,
+			Transaction {
+				inner: None,
+				..
+			} => return Err(Error::TxFinished),
+		};
+		Ok(SnapshotToken {
+			version,
+		})
+	}
+	/// Retrieve a specific range of keys from a pinned snapshot of the datastore.
+	///
+	/// This function fetches the full range of key-value pairs, in a single request to the underlying datastore.
+	#[cfg_attr(not(any(feature = "kv-tikv", feature = "kv-fdb")), allow(unused_variables))]
+	pub async fn scan_with_snapshot<K>(
+		&mut self,
+		token: &SnapshotToken,
+		rng: Range<K>,
+		limit: u32,
+	) -> Result<Vec<(Key, Val)>, Error>
+	where
+		K: Into<Key>,
+	{
+		self.timeout.check()?;
+		self.check_killed().await?;
+		match self {
+			#[cfg(feature = "kv-echodb")]
+			Transaction {
+				inner: Some(Inner::Mem(v)),
+				..
+			} => v.scan(rng, limit),
+			#[cfg(feature = "kv-yokudb")]
+			Transaction {
+				inner: Some(Inner::File(v)),
+				..
+			} => v.scan(rng, limit),
+			#[cfg(feature = "kv-indxdb")]
+			Transaction {
+				inner: Some(Inner::IxDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.scan(rng, limit)).await,
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.scan(rng, limit)).await,
+			#[cfg(feature = "kv-tikv")]
+			Transaction {
+				inner: Some(Inner::TiKV(v)),
+				timeout,
+				..
+			} => timeout.run(v.scan_at(token.version, rng, limit)).await,
+			#[cfg(feature = "kv-fdb")]
+			Transaction {
+				inner: Some(Inner::FDB(v)),
+				timeout,
+				..
+			} => timeout.run(v.scan_at(token.version, rng, limit)).await,
+			Transaction {
+				inner: None,
+				..
+			} => Err(Error::TxFinished),
+		}
+	}
+	/// Retrieve the next batch of 1000 key-value pairs from a range of the datastore.
+	///
+	/// The start of the range is moved past the last key retrieved, so that calling
+	/// this repeatedly walks the whole range, until an empty batch is returned.
+	async fn next_batch(&mut self, rng: &mut Range<Key>) -> Result<Vec<(Key, Val)>, Error> {
+		self.poll_killed(false).await?;
+		let res = self.scan(rng.clone(), 1000).await?;
+		if let Some((k, _)) = res.last() {
+			rng.start = k.clone();
+			rng.start.push(0x00);
+		}
+		Ok(res)
+	}
+	/// Retrieve the next batch of 1000 keys from a range of the datastore, without their values.
+	///
+	/// The start of the range is moved past the last key retrieved, so that calling
+	/// this repeatedly walks the whole range, until an empty batch is returned.
+	async fn next_batch_keys(&mut self, rng: &mut Range<Key>) -> Result<Vec<Key>, Error> {
+		self.poll_killed(false).await?;
+		let res = self.scan_keys(rng.clone(), 1000).await?;
+		if let Some(k) = res.last() {
+			rng.start = k.clone();
+			rng.start.push(0x00);
+		}
+		Ok(res)
+	}
+	/// Retrieve a specific range of keys from a pinned snapshot of the datastore.
+	///
+	/// This function fetches key-value pairs from the underlying datastore in batches of 1000.
+	pub async fn getr_with_snapshot<K>(
+		&mut self,
+		token: &SnapshotToken,
+		rng: Range<K>,
+		limit: u32,
+	) -> Result<Vec<(Key, Val)>, Error>
+	where
+		K: Into<Key>,
+	{
+		let beg: Key = rng.start.into();
+		let end: Key = rng.end.into();
+		let mut nxt: Option<Key> = None;
+		let mut num = limit;
+		let mut out: Vec<(Key, Val)> = vec![];
+		// Start processing
+		while num > 0 {
+			// Get records batch
+			let res = match nxt {
+				None => {
+					let min = beg.clone();
+					let max = end.clone();
+					let num = std::cmp::min(1000, num);
+					self.scan_with_snapshot(token, min..max, num).await?
+				}
+				Some(ref mut beg) => {
+					beg.push(0x00);
+					let min = beg.clone();
+					let max = end.clone();
+					let num = std::cmp::min(1000, num);
+					self.scan_with_snapshot(token, min..max, num).await?
+				}
+			};
+			// Get total results
+			let n = res.len();
+			// Exit when settled
+			if n == 0 {
+				break;
+			}
+			// Loop over results
+			for (i, (k, v)) in res.into_iter().enumerate() {
+				// Ready the next
+				if n == i + 1 {
+					nxt = Some(k.clone());
+				}
+				// Collect
+				out.push((k, v));
+				// Count
+				num -= 1;
+			}
+		}
+		Ok(out)
 	}
 	/// Retrieve a specific range of keys from the datastore.
 	///
@@ -463,6 +1504,108 @@ impl Transaction {
 		}
 		Ok(out)
 	}
+	/// Retrieve a specific range of keys from the datastore, without their values.
+	///
+	/// This function fetches keys from the underlying datastore in batches of 1000.
+	pub async fn getr_keys<K>(&mut self, rng: Range<K>, limit: u32) -> Result<Vec<Key>, Error>
+	where
+		K: Into<Key>,
+	{
+		let beg: Key = rng.start.into();
+		let end: Key = rng.end.into();
+		let mut nxt: Option<Key> = None;
+		let mut num = limit;
+		let mut out: Vec<Key> = vec![];
+		// Start processing
+		while num > 0 {
+			// Get keys batch
+			let res = match nxt {
+				None => {
+					let min = beg.clone();
+					let max = end.clone();
+					let num = std::cmp::min(1000, num);
+					self.scan_keys(min..max, num).await?
+				}
+				Some(ref mut beg) => {
+					beg.push(0x00);
+					let min = beg.clone();
+					let max = end.clone();
+					let num = std::cmp::min(1000, num);
+					self.scan_keys(min..max, num).await?
+				}
+			};
+			// Exit when settled
+			match res.last() {
+				Some(k) => nxt = Some(k.clone()),
+				None => break,
+			}
+			// Collect the results
+			num -= res.len() as u32;
+			out.extend(res);
+		}
+		Ok(out)
+	}
+	/// Count the keys in a specific range of the datastore, without fetching their values.
+	///
+	/// This function scans keys from the underlying datastore in batches of 1000.
+	pub async fn count_range<K>(&mut self, rng: Range<K>) -> Result<u64, Error>
+	where
+		K: Into<Key>,
+	{
+		let mut rng: Range<Key> = rng.start.into()..rng.end.into();
+		let mut out: u64 = 0;
+		// Start processing
+		loop {
+			// Get keys batch
+			let res = self.next_batch_keys(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Count the results
+			out += res.len() as u64;
+		}
+		Ok(out)
+	}
+	/// Count the keys with a specific prefix in the datastore, without fetching their values.
+	///
+	/// This function scans keys from the underlying datastore in batches of 1000.
+	pub async fn count_prefix<K>(&mut self, key: K) -> Result<u64, Error>
+	where
+		K: Into<Key>,
+	{
+		let beg: Key = key.into();
+		let end: Key = beg.clone().add(0xff);
+		self.count_range(beg..end).await
+	}
+	/// Retrieve a specific range of keys from the datastore, in descending key order.
+	///
+	/// This function fetches key-value pairs from the underlying datastore in batches of 1000.
+	pub async fn getr_rev<K>(&mut self, rng: Range<K>, limit: u32) -> Result<Vec<(Key, Val)>, Error>
+	where
+		K: Into<Key>,
+	{
+		let beg: Key = rng.start.into();
+		let mut end: Key = rng.end.into();
+		let mut num = limit;
+		let mut out: Vec<(Key, Val)> = vec![];
+		// Start processing
+		while num > 0 {
+			// Get records batch
+			let min = beg.clone();
+			let max = end.clone();
+			let res = self.scan_rev(min..max, std::cmp::min(1000, num)).await?;
+			// Exit when settled
+			match res.last() {
+				Some((k, _)) => end = k.clone(),
+				None => break,
+			}
+			// Collect the results
+			num -= res.len() as u32;
+			out.extend(res);
+		}
+		Ok(out)
+	}
 	/// Delete a range of keys from the datastore.
 	///
 	/// This function fetches key-value pairs from the underlying datastore in batches of 1000.
@@ -562,6 +1705,17 @@ impl Transaction {
 		}
 		Ok(out)
 	}
+	/// Retrieve a specific prefix of keys from the datastore, without their values.
+	///
+	/// This function fetches keys from the underlying datastore in batches of 1000.
+	pub async fn getp_keys<K>(&mut self, key: K, limit: u32) -> Result<Vec<Key>, Error>
+	where
+		K: Into<Key>,
+	{
+		let beg: Key = key.into();
+		let end: Key = beg.clone().add(0xff);
+		self.getr_keys(beg..end, limit).await
+	}
 	/// Delete a prefix of keys from the datastore.
 	///
 	/// This function fetches key-value pairs from the underlying datastore in batches of 1000.
@@ -611,6 +1765,196 @@ impl Transaction {
 		}
 		Ok(())
 	}
+	/// Check that the underlying datastore is responding.
+	///
+	/// This function performs a single lightweight read against the root key of the
+	/// keyspace, which is never written, and returns the round-trip duration.
+	pub async fn health_check(&mut self) -> Result<Duration, Error> {
+		let now = Instant::now();
+		self.exi(crate::key::kv::new()).await?;
+		Ok(now.elapsed())
+	}
+	/// Retrieve information about all of the transactions which are open on the datastore.
+	///
+	/// The transactions are ordered by the time at which they were started, and
+	/// include this transaction if it has not yet been committed or cancelled. On
+	/// distributed datastores, the write transactions which are open on other nodes
+	/// are read from the sentinel key stored by each write transaction, along with
+	/// all of the transactions open on this node. Sentinels expire after
+	/// [`TRANSACTION_SENTINEL_LEASE`](crate::cnf::TRANSACTION_SENTINEL_LEASE) unless
+	/// they are renewed by their transactions, so that the transactions of a failed
+	/// node are no longer listed once their sentinels expire, at which point the
+	/// sentinels are removed. Otherwise only this process can open the datastore,
+	/// so its open transactions are listed.
+	pub async fn list_active_transactions(&mut self) -> Result<Vec<TxInfo>, Error> {
+		let mut out = self.active.list();
+		if self.ds.distributed() {
+			let node = self.active.node();
+			let res = active::scan(&self.ds).await?;
+			out.extend(res.into_iter().filter(|v| v.node_id != node));
+			out.sort_by_key(|v| v.started_at);
+		}
+		Ok(out)
+	}
+	/// Forcefully terminate another transaction which is open on the datastore.
+	///
+	/// The transaction is flagged to be killed, and is cancelled the next time that
+	/// it performs an operation, which then returns an [`Error::TxKilled`] error. On
+	/// distributed datastores, a write transaction open on another node is flagged with
+	/// a stored kill key, which that transaction polls between the batches of a range
+	/// scan, and before it commits. This function fails with an [`Error::TxNotFound`]
+	/// error if the transaction is not open, or has already been committed or cancelled.
+	pub async fn kill_transaction(&mut self, tx_id: uuid::Uuid) -> Result<(), Error> {
+		if self.active.kill(&tx_id) {
+			return Ok(());
+		}
+		if self.ds.distributed() && active::kill(&self.ds, &tx_id).await? {
+			return Ok(());
+		}
+		Err(Error::TxNotFound)
+	}
+	/// Retrieve a snapshot of the health and size of the datastore.
+	pub async fn get_sys_info(&mut self) -> Result<SysInfo, Error> {
+		// Check the datastore is responding
+		let latency = self.health_check().await?;
+		// Count the namespaces and databases
+		let nss = self.all_ns().await?;
+		let mut dbs = 0;
+		for ns in nss.iter() {
+			dbs += self.all_db(&ns.name).await?.len() as u64;
+		}
+		// Get the datastore backend
+		let backend = match self {
+			#[cfg(feature = "kv-echodb")]
+			Transaction {
+				inner: Some(Inner::Mem(_)),
+				..
+			} => "echodb 0.3 (memory)",
+			#[cfg(feature = "kv-yokudb")]
+			Transaction {
+				inner: Some(Inner::File(_)),
+				..
+			} => "echodb 0.3 (file)",
+			#[cfg(feature = "kv-indxdb")]
+			Transaction {
+				inner: Some(Inner::IxDB(_)),
+				..
+			} => "indxdb 0.2",
+			#[cfg(feature = "kv-rocksdb")]
+			Transaction {
+				inner: Some(Inner::RocksDB(_)),
+				..
+			} => "rocksdb 0.19",
+			#[cfg(feature = "kv-tikv")]
+			Transaction {
+				inner: Some(Inner::TiKV(_)),
+				..
+			} => "tikv 0.1",
+			#[cfg(feature = "kv-fdb")]
+			Transaction {
+				inner: Some(Inner::FDB(_)),
+				..
+			} => "foundationdb 0.7",
+			Transaction {
+				inner: None,
+				..
+			} => return Err(Error::TxFinished),
+		};
+		Ok(SysInfo {
+			active_transactions: self.list_active_transactions().await?.len() as u64,
+			total_namespaces: nss.len() as u64,
+			total_databases: dbs,
+			backend_version: backend.to_owned(),
+			uptime_secs: self.active.uptime().as_secs(),
+			backend_latency_ms: latency.as_secs_f64() * 1000.0,
+			memory_used_bytes: memory_used(),
+		})
+	}
+	/// Check that this transaction is not read-only, before writing to the datastore.
+	fn check_readonly(&self) -> Result<(), Error> {
+		match self.readonly {
+			true => Err(Error::TxReadonly),
+			false => Ok(()),
+		}
+	}
+	/// Record the current value of a key, if any savepoints are held.
+	async fn save_undo(&mut self, key: &Key) -> Result<(), Error> {
+		if self.savepoints.is_empty() {
+			return Ok(());
+		}
+		// Scan for the key, so that the read is not recorded in the access log
+		let end = key.clone().add(0x00);
+		let val = self.scan(key.clone()..end, 1).await?.pop().map(|(_, v)| v);
+		self.undo.push((key.clone(), val));
+		Ok(())
+	}
+	/// Cancel this transaction if it has been killed by another transaction.
+	///
+	/// This is checked before every operation, and does not read from the datastore. A
+	/// kill flag stored by another node is checked with [`Transaction::poll_killed`].
+	async fn check_killed(&mut self) -> Result<(), Error> {
+		// Renew the transaction sentinel
+		if self.active.renew() {
+			if let Some(info) = self.active.info() {
+				let exp = active::store(&self.ds, &info).await?;
+				self.active.set_expires(exp);
+			}
+		}
+		if self.active.killed() {
+			let _ = self.cancel().await;
+			return Err(Error::TxKilled);
+		}
+		Ok(())
+	}
+	/// Cancel this transaction if it has been killed by a transaction on another node.
+	///
+	/// Reading the stored kill flag requires a separate transaction, so the flag is only
+	/// polled before committing, and between the batches of a range scan at most once
+	/// every poll interval, rather than before every operation.
+	async fn poll_killed(&mut self, force: bool) -> Result<(), Error> {
+		if self.active.stored()
+			&& (force || self.active.poll())
+			&& active::killed(&self.ds, &self.active.id()).await?
+		{
+			let _ = self.cancel().await;
+			return Err(Error::TxKilled);
+		}
+		Ok(())
+	}
+	/// Record an access to a key within a database, if access logging is enabled.
+	///
+	/// Entries are recorded in memory, and are stored in batches by the datastore.
+	fn log_access(&self, op: &str, key: &Key) {
+		if !self.log {
+			return;
+		}
+		// Only keys within a database are logged
+		let (ns, db) = match key_database(key) {
+			Some(v) => v,
+			None => return,
+		};
+		// Key each log entry by the node and a sequence number
+		let at = Utc::now();
+		let ts = at.timestamp_micros().max(0) as u64;
+		let node = self.active.node().to_string();
+		let log = crate::key::al::new(&ns, &db, op, ts, &node, self.audit.next());
+		let val = AccessLogEntry {
+			at,
+			operation: op.to_owned(),
+			key: key.clone(),
+		};
+		self.audit.record(log, val);
+	}
+	/// Store a batch of access log entries which have been recorded on the datastore.
+	pub(super) async fn store_access_log(
+		&mut self,
+		entries: &[(crate::key::al::Al, AccessLogEntry)],
+	) -> Result<(), Error> {
+		for (key, val) in entries {
+			self.set(key.clone(), val).await?;
+		}
+		Ok(())
+	}
 	/// Retrieve all namespace definitions in a datastore.
 	pub async fn all_ns(&mut self) -> Result<Arc<Vec<DefineNamespaceStatement>>, Error> {
 		let key = crate::key::ns::prefix();
@@ -772,6 +2116,29 @@ impl Transaction {
 			}
 		}
 	}
+	/// Retrieve all scope token definitions for all scopes in a specific database.
+	pub async fn all_sc_tokens_flat(
+		&mut self,
+		ns: &str,
+		db: &str,
+	) -> Result<Arc<Vec<DefineTokenStatement>>, Error> {
+		let key = crate::key::st::all(ns, db);
+		match self.cache.exi(&key) {
+			true => match self.cache.get(&key) {
+				Some(Entry::AllSts(v)) => Ok(v),
+				_ => unreachable!(),
+			},
+			_ => {
+				let mut val = vec![];
+				for sc in self.all_sc(ns, db).await?.iter() {
+					val.extend(self.all_st(ns, db, &sc.name).await?.iter().cloned());
+				}
+				let val = Arc::new(val);
+				self.cache.set(key, Entry::AllSts(val.clone()));
+				Ok(val)
+			}
+		}
+	}
 	/// Retrieve all table definitions for a specific database.
 	pub async fn all_tb(
 		&mut self,
@@ -1060,6 +2427,36 @@ impl Transaction {
 			Ok(v) => Ok(v),
 		}
 	}
+	/// Remove the cached definitions of a namespace, and of everything within it.
+	pub fn invalidate_ns_cache(&mut self, ns: &str) {
+		self.cache.del(&crate::key::ns::prefix());
+		self.cache.del(&crate::key::ns::new(ns).encode().unwrap());
+		self.cache.del_prefix(&crate::key::namespace::new(ns).encode().unwrap());
+		self.invalidate_writes(&crate::key::namespace::new(ns).encode().unwrap());
+	}
+	/// Remove the cached definitions of a database, and of everything within it.
+	pub fn invalidate_db_cache(&mut self, ns: &str, db: &str) {
+		self.cache.del(&crate::key::db::prefix(ns));
+		self.cache.del(&crate::key::db::new(ns, db).encode().unwrap());
+		self.cache.del_prefix(&crate::key::database::new(ns, db).encode().unwrap());
+		self.invalidate_writes(&crate::key::database::new(ns, db).encode().unwrap());
+	}
+	/// Remove the cached definitions of a table, and of everything within it.
+	pub fn invalidate_tb_cache(&mut self, ns: &str, db: &str, tb: &str) {
+		self.cache.del(&crate::key::tb::prefix(ns, db));
+		self.cache.del(&crate::key::tb::new(ns, db, tb).encode().unwrap());
+		self.cache.del_prefix(&crate::key::table::new(ns, db, tb).encode().unwrap());
+		self.writes.remove(&crate::key::table::new(ns, db, tb).encode().unwrap());
+	}
+	/// Remove all cached definitions.
+	pub fn invalidate_all_caches(&mut self) {
+		self.cache.clear();
+		self.writes.clear();
+	}
+	// Remove the kept write definitions of the tables within a prefix
+	fn invalidate_writes(&mut self, prefix: &[u8]) {
+		self.writes.retain(|k, _| !k.starts_with(prefix));
+	}
 	/// Retrieve and cache a specific namespace definition.
 	pub async fn get_and_cache_ns(
 		&mut self,
@@ -1120,12 +2517,92 @@ impl Transaction {
 			}
 		}
 	}
-	/// Add a namespace with a default configuration, only if we are in dynamic mode.
-	pub async fn add_and_cache_ns(
+	/// Retrieve and cache several table definitions at once.
+	///
+	/// Any table definitions which are not already cached are fetched together with
+	/// [`Transaction::mget`], so that on TiKV and FoundationDB the lookups are run
+	/// concurrently, rather than one after another.
+	pub async fn get_and_cache_tb_batch(
 		&mut self,
 		ns: &str,
-		strict: bool,
-	) -> Result<Arc<DefineNamespaceStatement>, Error> {
+		db: &str,
+		tables: &[&str],
+	) -> Result<HashMap<String, Arc<DefineTableStatement>>, Error> {
+		let mut out = HashMap::with_capacity(tables.len());
+		let mut keys = vec![];
+		for tb in tables {
+			let key = crate::key::tb::new(ns, db, tb).encode()?;
+			match self.cache.get(&key) {
+				Some(Entry::Tb(v)) => {
+					out.insert(tb.to_string(), v);
+				}
+				_ => keys.push((tb, key)),
+			}
+		}
+		// Fetch the uncached table definitions together
+		if !keys.is_empty() {
+			let res = self.mget(keys.iter().map(|(_, k)| k.clone()).collect()).await?;
+			for ((tb, key), val) in keys.into_iter().zip(res) {
+				let val: Arc<DefineTableStatement> = Arc::new(val.ok_or(Error::TbNotFound)?.into());
+				self.cache.set(key, Entry::Tb(val.clone()));
+				out.insert(tb.to_string(), val);
+			}
+		}
+		Ok(out)
+	}
+	/// Fetch and cache the schema definitions which will be used by a query.
+	///
+	/// Tables which are referenced but not defined are skipped, so that a query can
+	/// still define them when it runs. Field and index definitions are only cached
+	/// for a table as a whole, so all of the field and index definitions of each table
+	/// in the references are cached, and the names of the referenced fields and indexes
+	/// are not used. The referenced table definitions are fetched together with
+	/// [`Transaction::get_and_cache_tb_batch`].
+	pub async fn prefetch_schema_for_query(
+		&mut self,
+		ns: &str,
+		db: &str,
+		refs: &QuerySchemaRefs,
+	) -> Result<(), Error> {
+		// Cache the defined tables
+		let tbs = self.all_tb(ns, db).await?;
+		let tables = refs
+			.tables
+			.iter()
+			.map(String::as_str)
+			.filter(|v| tbs.iter().any(|t| t.name.as_str() == *v))
+			.collect::<Vec<_>>();
+		self.get_and_cache_tb_batch(ns, db, &tables).await?;
+		// Cache the field definitions
+		for tb in refs.fields.keys() {
+			self.all_fd(ns, db, tb).await?;
+		}
+		// Cache the index definitions
+		for tb in refs.indexes.keys() {
+			self.all_ix(ns, db, tb).await?;
+		}
+		Ok(())
+	}
+	/// Cache all of the definitions which are needed when processing records in a table.
+	///
+	/// This caches the table, field, index, event, and foreign table definitions of
+	/// the table, so that subsequent lookups are served from the cache. The table
+	/// definition is fetched with [`Transaction::get_and_cache_tb_batch`], and the
+	/// other definitions are each fetched with a single range scan.
+	pub async fn warm_table_cache(&mut self, ns: &str, db: &str, tb: &str) -> Result<(), Error> {
+		self.get_and_cache_tb_batch(ns, db, &[tb]).await?;
+		self.all_fd(ns, db, tb).await?;
+		self.all_ix(ns, db, tb).await?;
+		self.all_ev(ns, db, tb).await?;
+		self.all_ft(ns, db, tb).await?;
+		Ok(())
+	}
+	/// Add a namespace with a default configuration, only if we are in dynamic mode.
+	pub async fn add_and_cache_ns(
+		&mut self,
+		ns: &str,
+		strict: bool,
+	) -> Result<Arc<DefineNamespaceStatement>, Error> {
 		match self.get_and_cache_ns(ns).await {
 			Err(Error::NsNotFound) => match strict {
 				false => {
@@ -1152,6 +2629,7 @@ impl Transaction {
 		match self.get_and_cache_db(ns, db).await {
 			Err(Error::DbNotFound) => match strict {
 				false => {
+					self.enforce_quota(ns, db).await?;
 					let key = crate::key::db::new(ns, db);
 					let val = DefineDatabaseStatement {
 						name: db.to_owned().into(),
@@ -1176,6 +2654,7 @@ impl Transaction {
 		match self.get_and_cache_tb(ns, db, tb).await {
 			Err(Error::TbNotFound) => match strict {
 				false => {
+					self.enforce_quota(ns, db).await?;
 					let key = crate::key::tb::new(ns, db, tb);
 					let val = DefineTableStatement {
 						name: tb.to_owned().into(),
@@ -1373,4 +2852,4599 @@ impl Transaction {
 		// Everything exported
 		Ok(())
 	}
+	/// Writes the full database contents as newline-delimited JSON.
+	///
+	/// The first line holds the schema of the database, with each section as an
+	/// array of SurrealQL definitions. Every following line holds a single record,
+	/// along with the name of the table which it belongs to.
+	pub async fn export_json(
+		&mut self,
+		ns: &str,
+		db: &str,
+		chn: Sender<Vec<u8>>,
+	) -> Result<(), Error> {
+		// Render a set of definitions
+		fn defs<T: std::fmt::Display>(v: &[T]) -> Vec<String> {
+			v.iter().map(|v| v.to_string()).collect()
+		}
+		// Fetch the tables
+		let tbs = self.all_tb(ns, db).await?;
+		// Output SCHEMA
+		{
+			let mut fds = vec![];
+			let mut ixs = vec![];
+			let mut evs = vec![];
+			for tb in tbs.iter() {
+				fds.extend(defs(self.all_fd(ns, db, &tb.name).await?.as_slice()));
+				ixs.extend(defs(self.all_ix(ns, db, &tb.name).await?.as_slice()));
+				evs.extend(defs(self.all_ev(ns, db, &tb.name).await?.as_slice()));
+			}
+			let schema = serde_json::json!({
+				"schema": {
+					"logins": defs(self.all_dl(ns, db).await?.as_slice()),
+					"tokens": defs(self.all_dt(ns, db).await?.as_slice()),
+					"scopes": defs(self.all_sc(ns, db).await?.as_slice()),
+					"tables": defs(tbs.as_slice()),
+					"fields": fds,
+					"indexes": ixs,
+					"events": evs,
+				}
+			});
+			chn.send(bytes!(schema)).await?;
+		}
+		// Output TABLE data
+		for tb in tbs.iter() {
+			// Fetch records
+			let mut rng = thing::prefix(ns, db, &tb.name)..thing::suffix(ns, db, &tb.name);
+			loop {
+				let res = self.next_batch(&mut rng).await?;
+				// Exit when settled
+				if res.is_empty() {
+					break;
+				}
+				// Loop over results
+				for (_, v) in res {
+					// Parse the value
+					let v: Value = (&v).into();
+					// Write record
+					let rec = serde_json::json!({
+						"table": tb.name.to_raw(),
+						"record": v,
+					});
+					chn.send(bytes!(rec)).await?;
+				}
+			}
+		}
+		// Everything exported
+		Ok(())
+	}
+	/// Replays a full database export, as produced by [`Transaction::export`].
+	///
+	/// Definitions and records are written directly to the datastore, as with
+	/// `OPTION IMPORT`, so fields, events, and table views are not processed. The
+	/// records are stored with [`Transaction::set_record`], and the index entries
+	/// of imported records are created as the records are written. A `DELETE` of a
+	/// single record, as produced by [`Transaction::export_since`], removes the record
+	/// along with its index entries. Any error is returned as an [`Error::ImportError`],
+	/// along with the line of the input on which the failing statement starts.
+	pub async fn import(&mut self, ns: &str, db: &str, input: &[u8]) -> Result<(), Error> {
+		// Parse the export
+		let txt = std::str::from_utf8(input).map_err(|e| {
+			let line = input[..e.valid_up_to()].iter().filter(|&&c| c == b'\n').count() + 1;
+			Error::ImportError(line, e.to_string())
+		})?;
+		let ast = match sql::parse_lines(txt) {
+			Ok(v) => v,
+			Err(Error::QueryEmpty) => return Ok(()),
+			Err(Error::InvalidQuery {
+				line,
+				sql,
+				..
+			}) => return Err(Error::ImportError(line, sql)),
+			Err(e) => return Err(e),
+		};
+		// Ensure the database exists
+		self.add_ns(ns, false).await?;
+		self.add_db(ns, db, false).await?;
+		// Replay each statement
+		for (line, stm) in ast.iter() {
+			let res = match stm {
+				sql::Statement::Option(_) => continue,
+				sql::Statement::Begin(_) => continue,
+				sql::Statement::Commit(_) => continue,
+				sql::Statement::Define(v) => {
+					let res = self.import_define(ns, db, v).await;
+					self.invalidate_all_caches();
+					res
+				}
+				sql::Statement::Remove(v) => {
+					let res = self.import_remove(ns, db, v).await;
+					self.invalidate_all_caches();
+					res
+				}
+				sql::Statement::Create(v) => {
+					self.import_record(ns, db, &v.what, &v.data, true).await
+				}
+				sql::Statement::Update(v) => {
+					self.import_record(ns, db, &v.what, &v.data, false).await
+				}
+				sql::Statement::Delete(v) => self.import_delete(ns, db, &v.what).await,
+				v => Err(Error::ImportError(0, format!("Unexpected statement '{}'", v))),
+			};
+			// Report the line of the failing statement
+			match res {
+				Err(Error::ImportError(_, msg)) => return Err(Error::ImportError(*line, msg)),
+				Err(e) => return Err(Error::ImportError(*line, e.to_string())),
+				Ok(_) => (),
+			}
+		}
+		Ok(())
+	}
+	// Write an imported definition
+	async fn import_define(
+		&mut self,
+		ns: &str,
+		db: &str,
+		stm: &sql::statements::DefineStatement,
+	) -> Result<(), Error> {
+		use sql::statements::DefineStatement;
+		match stm {
+			DefineStatement::Namespace(v) => self.set(crate::key::ns::new(&v.name), v).await,
+			DefineStatement::Database(v) => self.set(crate::key::db::new(ns, &v.name), v).await,
+			DefineStatement::Login(v) => match v.base {
+				sql::Base::Ns => self.set(crate::key::nl::new(ns, &v.name), v).await,
+				sql::Base::Db => self.set(crate::key::dl::new(ns, db, &v.name), v).await,
+				_ => Err(Error::ImportError(0, format!("Unexpected statement '{}'", v))),
+			},
+			DefineStatement::Token(v) => match v.base {
+				sql::Base::Ns => self.set(crate::key::nt::new(ns, &v.name), v).await,
+				sql::Base::Db => self.set(crate::key::dt::new(ns, db, &v.name), v).await,
+				_ => Err(Error::ImportError(0, format!("Unexpected statement '{}'", v))),
+			},
+			DefineStatement::Scope(v) => self.set(crate::key::sc::new(ns, db, &v.name), v).await,
+			DefineStatement::Table(v) => {
+				self.set(crate::key::tb::new(ns, db, &v.name), v).await?;
+				if let Some(view) = &v.view {
+					for ft in view.what.0.iter() {
+						self.set(crate::key::ft::new(ns, db, ft, &v.name), v).await?;
+					}
+				}
+				Ok(())
+			}
+			DefineStatement::Event(v) => {
+				self.add_tb(ns, db, &v.what, false).await?;
+				self.set(crate::key::ev::new(ns, db, &v.what, &v.name), v).await
+			}
+			DefineStatement::Field(v) => {
+				self.add_tb(ns, db, &v.what, false).await?;
+				let key = crate::key::fd::new(ns, db, &v.what, &v.name.to_string());
+				self.set(key, v).await
+			}
+			DefineStatement::Index(v) => {
+				self.add_tb(ns, db, &v.what, false).await?;
+				self.set(crate::key::ix::new(ns, db, &v.what, &v.name), v).await?;
+				// Remove the index data
+				let beg = crate::key::index::prefix(ns, db, &v.what, &v.name);
+				let end = crate::key::index::suffix(ns, db, &v.what, &v.name);
+				self.delr(beg..end, u32::MAX).await?;
+				// Index any existing records
+				let beg = thing::prefix(ns, db, &v.what);
+				let end = thing::suffix(ns, db, &v.what);
+				for (k, val) in self.getr(beg..end, u32::MAX).await? {
+					let k: crate::key::thing::Thing = (&k).into();
+					let t = Thing::from((k.tb, k.id));
+					let val: Value = (&val).into();
+					self.create_composite_index_entry(ns, db, &v.what, v, &t, &val).await?;
+				}
+				Ok(())
+			}
+			DefineStatement::Quota(v) => {
+				let val = QuotaDefinition {
+					max_records: v.records,
+					max_storage_bytes: v.storage,
+					max_live_queries: v.live,
+				};
+				self.set_quota(ns, &v.db, &val).await
+			}
+		}
+	}
+	// Apply an imported removal
+	async fn import_remove(
+		&mut self,
+		ns: &str,
+		db: &str,
+		stm: &sql::statements::RemoveStatement,
+	) -> Result<(), Error> {
+		use sql::statements::RemoveStatement;
+		match stm {
+			RemoveStatement::Namespace(v) => {
+				self.del(crate::key::ns::new(&v.name)).await?;
+				self.delp(crate::key::namespace::new(&v.name), u32::MAX).await
+			}
+			RemoveStatement::Database(v) => {
+				self.del(crate::key::db::new(ns, &v.name)).await?;
+				self.delp(crate::key::database::new(ns, &v.name), u32::MAX).await
+			}
+			RemoveStatement::Login(v) => match v.base {
+				sql::Base::Ns => self.del(crate::key::nl::new(ns, &v.name)).await,
+				sql::Base::Db => self.del(crate::key::dl::new(ns, db, &v.name)).await,
+				_ => Err(Error::ImportError(0, format!("Unexpected statement '{}'", v))),
+			},
+			RemoveStatement::Token(v) => match v.base {
+				sql::Base::Ns => self.del(crate::key::nt::new(ns, &v.name)).await,
+				sql::Base::Db => self.del(crate::key::dt::new(ns, db, &v.name)).await,
+				_ => Err(Error::ImportError(0, format!("Unexpected statement '{}'", v))),
+			},
+			RemoveStatement::Scope(v) => self.del(crate::key::sc::new(ns, db, &v.name)).await,
+			RemoveStatement::Table(v) => {
+				self.del(crate::key::tb::new(ns, db, &v.name)).await?;
+				self.delp(crate::key::table::new(ns, db, &v.name), u32::MAX).await
+			}
+			RemoveStatement::Event(v) => {
+				self.del(crate::key::ev::new(ns, db, &v.what, &v.name)).await
+			}
+			RemoveStatement::Field(v) => {
+				self.del(crate::key::fd::new(ns, db, &v.what, &v.name)).await
+			}
+			RemoveStatement::Index(v) => {
+				self.del(crate::key::ix::new(ns, db, &v.what, &v.name)).await?;
+				let beg = crate::key::index::prefix(ns, db, &v.what, &v.name);
+				let end = crate::key::index::suffix(ns, db, &v.what, &v.name);
+				self.delr(beg..end, u32::MAX).await
+			}
+		}
+	}
+	// Write an imported record, along with its index entries
+	async fn import_record(
+		&mut self,
+		ns: &str,
+		db: &str,
+		what: &sql::Values,
+		data: &Option<sql::Data>,
+		create: bool,
+	) -> Result<(), Error> {
+		// Fetch the record id and content
+		let (rid, val) = match (what.0.as_slice(), data) {
+			([Value::Thing(rid)], Some(sql::Data::ContentExpression(val))) => (rid, val),
+			_ => {
+				let msg = format!("Unexpected record data for '{}'", what);
+				return Err(Error::ImportError(0, msg));
+			}
+		};
+		// Check the record
+		let key = crate::key::thing::new(ns, db, &rid.tb, &rid.id);
+		let old = match self.get(key).await? {
+			Some(_) if create => {
+				return Err(Error::RecordExists {
+					thing: rid.to_string(),
+				})
+			}
+			Some(v) => Some(Value::from(v)),
+			None => None,
+		};
+		// Store the record data
+		self.add_tb(ns, db, &rid.tb, false).await?;
+		self.set_record(ns, db, rid, val).await?;
+		// Update the index entries
+		for ix in self.all_ix(ns, db, &rid.tb).await?.iter() {
+			if let Some(old) = &old {
+				self.delete_composite_index_entry(ns, db, &rid.tb, ix, rid, old).await?;
+			}
+			self.create_composite_index_entry(ns, db, &rid.tb, ix, rid, val).await?;
+		}
+		Ok(())
+	}
+	// Apply an imported record deletion, removing the index entries of the record
+	async fn import_delete(&mut self, ns: &str, db: &str, what: &sql::Values) -> Result<(), Error> {
+		// Fetch the record id
+		let rid = match what.0.as_slice() {
+			[Value::Thing(rid)] => rid,
+			_ => {
+				let msg = format!("Unexpected record deletion for '{}'", what);
+				return Err(Error::ImportError(0, msg));
+			}
+		};
+		// Check the record
+		let key = crate::key::thing::new(ns, db, &rid.tb, &rid.id);
+		let old = match self.get(key).await? {
+			Some(v) => Value::from(v),
+			None => return Ok(()),
+		};
+		// Remove the index entries
+		for ix in self.all_ix(ns, db, &rid.tb).await?.iter() {
+			self.delete_composite_index_entry(ns, db, &rid.tb, ix, rid, &old).await?;
+		}
+		// Delete the record data
+		self.del_record(ns, db, rid).await
+	}
+	/// Retrieve the records in a table, keeping only the specified fields.
+	///
+	/// Each field may be a nested path such as `address.city`. Every picked
+	/// field is returned in an object keyed by the field path, and fields
+	/// which do not exist on a record are returned as NONE. Records are stored
+	/// in a binary encoding which can not be partially decoded, so each record is
+	/// still fully decoded before its fields are picked. This reduces the memory
+	/// held by the results for wide tables, but not the decoding work.
+	pub async fn scan_with_projection(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		fields: &[&str],
+		limit: u32,
+	) -> Result<Vec<(Thing, Value)>, Error> {
+		// Parse the field paths
+		let paths: Vec<(String, Idiom)> = fields
+			.iter()
+			.map(|f| (f.to_string(), f.split('.').map(Part::from).collect::<Vec<_>>().into()))
+			.collect();
+		// Fetch the records
+		let beg = thing::prefix(ns, db, tb);
+		let end = thing::suffix(ns, db, tb);
+		let res = self.getr(beg..end, limit).await?;
+		// Pick the fields from each record
+		let mut out = Vec::with_capacity(res.len());
+		for (k, v) in res.into_iter() {
+			// Parse the key-value
+			let k: crate::key::thing::Thing = (&k).into();
+			let v: Value = (&v).into();
+			let t = Thing::from((k.tb, k.id));
+			// Pick the requested fields
+			let mut obj = Object::default();
+			for (name, path) in paths.iter() {
+				obj.insert(name.clone(), v.pick(path));
+			}
+			out.push((t, obj.into()));
+		}
+		Ok(out)
+	}
+	/// Retrieve the records in a table, ordered by the specified field.
+	///
+	/// The table is scanned in batches of 1000, and only the best `limit`
+	/// records seen so far are retained in a bounded binary heap, so memory
+	/// usage is O(limit) rather than O(n) in the number of table records.
+	pub async fn scan_with_sort(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		sort_field: &str,
+		asc: bool,
+		limit: u32,
+	) -> Result<Vec<(Thing, Value)>, Error> {
+		// Parse the sort field path
+		let path: Idiom = sort_field.split('.').map(Part::from).collect::<Vec<_>>().into();
+		// Nothing to return
+		if limit == 0 {
+			return Ok(vec![]);
+		}
+		// Fetch the records
+		let mut rng = thing::prefix(ns, db, tb)..thing::suffix(ns, db, tb);
+		let mut seq: usize = 0;
+		let mut heap: BinaryHeap<Sorted> = BinaryHeap::new();
+		loop {
+			// Get records batch
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Parse the key-value
+				let k: crate::key::thing::Thing = (&k).into();
+				let v: Value = (&v).into();
+				let t = Thing::from((k.tb, k.id));
+				// Add the record to the heap
+				heap.push(Sorted {
+					asc,
+					ord: v.pick(&path),
+					seq,
+					rid: t,
+					val: v,
+				});
+				seq += 1;
+				// Evict the worst record
+				if heap.len() > limit as usize {
+					heap.pop();
+				}
+			}
+		}
+		// Output the records in order
+		Ok(heap.into_sorted_vec().into_iter().map(|v| (v.rid, v.val)).collect())
+	}
+	/// Compute statistics for the values of a field across all records in a table.
+	///
+	/// The table is scanned once in batches of 1000. Records where the field is
+	/// missing or NULL are counted as null, and are otherwise excluded from the
+	/// distinct count and the minimum and maximum values.
+	pub async fn get_field_statistics(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		field: &str,
+	) -> Result<FieldStats, Error> {
+		// Get the type name of a value
+		fn kind(v: &Value) -> &'static str {
+			match v {
+				Value::None => "none",
+				Value::Null => "null",
+				Value::False | Value::True => "bool",
+				Value::Number(Number::Int(_)) => "int",
+				Value::Number(Number::Float(_)) => "float",
+				Value::Number(Number::Decimal(_)) => "decimal",
+				Value::Strand(_) => "string",
+				Value::Duration(_) => "duration",
+				Value::Datetime(_) => "datetime",
+				Value::Uuid(_) => "uuid",
+				Value::Array(_) => "array",
+				Value::Object(_) => "object",
+				Value::Geometry(_) => "geometry",
+				Value::Thing(_) => "record",
+				_ => "other",
+			}
+		}
+		// Parse the field path
+		let path: Idiom = field.split('.').map(Part::from).collect::<Vec<_>>().into();
+		// Fetch the records
+		let mut rng = thing::prefix(ns, db, tb)..thing::suffix(ns, db, tb);
+		let mut out = FieldStats::default();
+		let mut distinct: BTreeSet<Value> = BTreeSet::new();
+		loop {
+			// Get records batch
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (_, v) in res {
+				// Pick the field value
+				let v: Value = (&v).into();
+				let v = v.pick(&path);
+				// Update the statistics
+				out.total_records += 1;
+				*out.type_distribution.entry(kind(&v).to_owned()).or_default() += 1;
+				if v.is_null() {
+					out.null_count += 1;
+					continue;
+				}
+				match out.min {
+					Some(ref m) if m <= &v => (),
+					_ => out.min = Some(v.clone()),
+				}
+				match out.max {
+					Some(ref m) if m >= &v => (),
+					_ => out.max = Some(v.clone()),
+				}
+				distinct.insert(v);
+			}
+		}
+		out.distinct_count = distinct.len() as u64;
+		Ok(out)
+	}
+	/// Recommend indexes for the defined fields of a table which are not yet indexed.
+	///
+	/// Filter and sort frequencies are not recorded by the query executor, so each
+	/// field is judged by its value distribution, using [`Transaction::get_field_statistics`].
+	/// The estimated speedup is the number of records visited by a full table scan, divided
+	/// by the average number of records which share a value. Fields with an estimated speedup
+	/// below 2 are not recommended, and the recommendations are ordered by their speedup.
+	pub async fn recommend_indexes(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+	) -> Result<Vec<IndexRecommendation>, Error> {
+		// Find the fields which lead an existing index
+		let indexed: Vec<Idiom> = self
+			.all_ix(ns, db, tb)
+			.await?
+			.iter()
+			.filter_map(|ix| ix.cols.first().cloned())
+			.collect();
+		// Check each of the remaining fields
+		let mut out = vec![];
+		for fd in self.all_fd(ns, db, tb).await?.iter() {
+			if indexed.contains(&fd.name) {
+				continue;
+			}
+			let field = fd.name.to_string();
+			let stats = self.get_field_statistics(ns, db, tb, &field).await?;
+			let values = stats.total_records - stats.null_count;
+			if values == 0 {
+				continue;
+			}
+			let estimated_speedup =
+				stats.total_records as f64 * stats.distinct_count as f64 / values as f64;
+			if estimated_speedup < 2.0 {
+				continue;
+			}
+			let (kind, reason) = match stats.null_count == 0 && stats.distinct_count == values {
+				true => (
+					IndexType::Unique,
+					format!("All {} records have a distinct value", stats.total_records),
+				),
+				false => (
+					IndexType::Standard,
+					format!(
+						"{} distinct values across {} records",
+						stats.distinct_count, stats.total_records
+					),
+				),
+			};
+			out.push(IndexRecommendation {
+				fields: vec![field],
+				type_: kind,
+				reason,
+				estimated_speedup,
+			});
+		}
+		out.sort_by(|a, b| b.estimated_speedup.total_cmp(&a.estimated_speedup));
+		Ok(out)
+	}
+	/// Retrieve the ids of the records in a table where a field is missing or NULL.
+	///
+	/// This function fetches key-value pairs from the underlying datastore in batches of 1000.
+	pub async fn scan_null_fields(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		field: &str,
+	) -> Result<Vec<Thing>, Error> {
+		// Parse the field path
+		let path: Idiom = field.split('.').map(Part::from).collect::<Vec<_>>().into();
+		// Fetch the records
+		let mut rng = thing::prefix(ns, db, tb)..thing::suffix(ns, db, tb);
+		let mut out = vec![];
+		loop {
+			// Get records batch
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Parse the key-value
+				let k: crate::key::thing::Thing = (&k).into();
+				let v: Value = (&v).into();
+				// Check the field value
+				if v.pick(&path).is_null() {
+					out.push(Thing::from((k.tb, k.id)));
+				}
+			}
+		}
+		Ok(out)
+	}
+	/// Set a field to a default value in all records in a table where it is missing or NULL.
+	///
+	/// Each updated record is written back along with its table version and change
+	/// feed entry, but without running events or updating indexes, so any indexes
+	/// which include the field should be rebuilt afterwards. This function fetches
+	/// key-value pairs from the underlying datastore in batches of 1000, and returns
+	/// the number of records which were updated.
+	pub async fn backfill_field(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		field: &str,
+		default_value: &Value,
+	) -> Result<u64, Error> {
+		// Check the table is not locked
+		self.check_table_lock(ns, db, tb).await?;
+		// Parse the field path
+		let path: Idiom = field.split('.').map(Part::from).collect::<Vec<_>>().into();
+		// Fetch the records
+		let mut rng = thing::prefix(ns, db, tb)..thing::suffix(ns, db, tb);
+		let mut num = 0;
+		loop {
+			// Get records batch
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Check the field value
+				let mut v: Value = (&v).into();
+				if !v.pick(&path).is_null() {
+					continue;
+				}
+				// Set the default value
+				v.put(&path, default_value.clone());
+				// Store the record data
+				let t: crate::key::thing::Thing = (&k).into();
+				self.set_record(ns, db, &Thing::from((t.tb, t.id)), &v).await?;
+				// Count
+				num += 1;
+			}
+		}
+		Ok(num)
+	}
+	/// Define several fields on a table at once.
+	///
+	/// Each field definition is stored against the specified table, and the cached
+	/// field definitions for the table are invalidated once all fields are written.
+	pub async fn bulk_define_fields(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		fields: Vec<DefineFieldStatement>,
+	) -> Result<(), Error> {
+		for mut fd in fields {
+			fd.what = tb.into();
+			let key = crate::key::fd::new(ns, db, tb, &fd.name.to_string());
+			self.set(key, fd).await?;
+		}
+		self.cache.del(&crate::key::fd::prefix(ns, db, tb));
+		Ok(())
+	}
+	/// Compare the field definitions on a table with a set of target field definitions.
+	///
+	/// Fields are matched by name. Target fields which are not defined are returned
+	/// to be added, target fields which differ from the defined fields are returned to
+	/// be altered, and defined fields which are not in the target are returned to be
+	/// dropped.
+	pub async fn field_schema_diff(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		target_fields: &[DefineFieldStatement],
+	) -> Result<FieldSchemaDiff, Error> {
+		let fds = self.all_fd(ns, db, tb).await?;
+		let mut out = FieldSchemaDiff::default();
+		for fd in target_fields {
+			let mut fd = fd.clone();
+			fd.what = tb.into();
+			match fds.iter().find(|v| v.name == fd.name) {
+				None => out.to_add.push(fd),
+				Some(v) if *v != fd => out.to_alter.push(fd),
+				Some(_) => (),
+			}
+		}
+		for fd in fds.iter() {
+			if !target_fields.iter().any(|v| v.name == fd.name) {
+				out.to_drop.push(fd.clone());
+			}
+		}
+		Ok(out)
+	}
+	/// Check whether a record referenced by a foreign key exists.
+	pub async fn check_foreign_key(
+		&mut self,
+		ns: &str,
+		db: &str,
+		thing: &Thing,
+	) -> Result<bool, Error> {
+		self.exi(crate::key::thing::new(ns, db, &thing.tb, &thing.id)).await
+	}
+	/// Store a record in the datastore.
+	///
+	/// If foreign key checking is enabled, then the record is only stored
+	/// if the records referenced by its fields of type `record(tb)` exist.
+	pub async fn set_record(
+		&mut self,
+		ns: &str,
+		db: &str,
+		thing: &Thing,
+		val: &Value,
+	) -> Result<(), Error> {
+		// Check the referenced records
+		if self.fk {
+			for fd in self.all_fd(ns, db, &thing.tb).await?.iter() {
+				if let Some(Kind::Record(tbs)) = &fd.kind {
+					for v in record_refs(val.pick(&fd.name)) {
+						let checked = tbs.is_empty() || tbs.iter().any(|tb| tb.0 == v.tb);
+						if checked && !self.check_foreign_key(ns, db, &v).await? {
+							return Err(Error::ForeignKeyViolation {
+								field: fd.name.clone(),
+								missing: v,
+							});
+						}
+					}
+				}
+			}
+		}
+		// Check the table is not locked
+		self.check_table_lock(ns, db, &thing.tb).await?;
+		let key: Key = crate::key::thing::new(ns, db, &thing.tb, &thing.id).into();
+		let val: Val = val.into();
+		let writes = self.writes(ns, db, &thing.tb).await?;
+		// Check the database quota
+		if let Some(quota) = &writes.quota {
+			let old = self.get(key.clone()).await?;
+			let tb = &thing.tb;
+			self.track_quota_usage(ns, db, tb, quota, &key, old.as_ref(), Some(&val)).await?;
+		}
+		// Store the record data
+		self.set(key, val.clone()).await?;
+		// Records derived from other records are not logged
+		if self.rewrite {
+			return Ok(());
+		}
+		// Mark any dependent views as stale
+		self.invalidate_views(ns, db, &writes.views).await?;
+		// Log the change to the table
+		self.log_change(ns, db, thing, val, writes.feed);
+		Ok(())
+	}
+	/// Delete a record from the datastore.
+	pub async fn del_record(&mut self, ns: &str, db: &str, thing: &Thing) -> Result<(), Error> {
+		// Check the table is not locked
+		self.check_table_lock(ns, db, &thing.tb).await?;
+		let key: Key = crate::key::thing::new(ns, db, &thing.tb, &thing.id).into();
+		let writes = self.writes(ns, db, &thing.tb).await?;
+		// Release the database quota
+		if let Some(quota) = &writes.quota {
+			let old = self.get(key.clone()).await?;
+			self.track_quota_usage(ns, db, &thing.tb, quota, &key, old.as_ref(), None).await?;
+		}
+		// Delete the record data
+		self.del(key).await?;
+		// Records derived from other records are not logged
+		if self.rewrite {
+			return Ok(());
+		}
+		// Mark any dependent views as stale
+		self.invalidate_views(ns, db, &writes.views).await?;
+		// Log the change to the table
+		self.log_change(ns, db, thing, (&Value::None).into(), writes.feed);
+		Ok(())
+	}
+	// Fetch the definitions which are checked when a record in a table is written
+	//
+	// The definitions are fetched on the first write to each table, and are then
+	// kept until the definitions of the table or its database are invalidated.
+	async fn writes(&mut self, ns: &str, db: &str, tb: &str) -> Result<Writes, Error> {
+		let key = crate::key::table::new(ns, db, tb).encode()?;
+		if let Some(v) = self.writes.get(&key) {
+			return Ok(v.clone());
+		}
+		let val = Writes {
+			quota: self.get_quota(ns, db).await?,
+			views: self.all_ft(ns, db, tb).await?,
+			feed: match self.get_and_cache_tb(ns, db, tb).await {
+				Ok(tb) => tb.changefeed.is_some(),
+				Err(Error::TbNotFound) => false,
+				Err(e) => return Err(e),
+			},
+		};
+		self.writes.insert(key, val.clone());
+		Ok(val)
+	}
+	// Update the version of a table for a changed record
+	//
+	// If the table is defined with a change feed, then the new state of the record
+	// is logged in the change feed of the database, at the version of the table,
+	// when the transaction is committed.
+	fn log_change(&mut self, ns: &str, db: &str, thing: &Thing, val: Val, feed: bool) {
+		self.changes.push(Change::Record {
+			ns: ns.to_owned(),
+			db: db.to_owned(),
+			tb: thing.tb.to_owned(),
+			feed: feed.then(|| (thing.id.clone(), val)),
+		});
+	}
+	/// Remove the change feed entries which are older than the retention of their tables.
+	///
+	/// Each table keeps its change feed for the duration given by `CHANGEFEED` in its
+	/// definition, and entries for tables which no longer have a change feed are removed.
+	/// The superseded version keys of each table are removed as well. This should be run
+	/// periodically, and returns the number of change feed entries which were removed.
+	pub async fn prune_change_feed(&mut self, ns: &str, db: &str) -> Result<u64, Error> {
+		let now = Utc::now().timestamp_micros() as u64;
+		// Find the oldest version to keep for each table
+		let mut keep: HashMap<String, u64> = HashMap::new();
+		for tb in self.all_tb(ns, db).await?.iter() {
+			if let Some(d) = &tb.changefeed {
+				let ret = d.0.as_micros().min(u64::MAX as u128) as u64;
+				keep.insert(tb.name.to_string(), now.saturating_sub(ret));
+			}
+			// Remove the superseded version keys
+			self.remove_superseded_versions(ns, db, &tb.name).await?;
+		}
+		// Remove the expired change feed entries
+		let beg = crate::key::cf::lsn(ns, db, 0);
+		let end = crate::key::cf::lsn(ns, db, now);
+		let mut num = 0;
+		for k in self.getr_keys(beg..end, u32::MAX).await? {
+			let cf: crate::key::cf::Cf = (&k).into();
+			if keep.get(&cf.tb).is_none_or(|v| cf.lsn < *v) {
+				self.del(k).await?;
+				num += 1;
+			}
+		}
+		Ok(num)
+	}
+	/// Mark a foreign table view as stale, so that it is repopulated on the next refresh.
+	///
+	/// The recorded source table versions of the view are removed, along with any
+	/// cached definitions of the view table.
+	pub async fn invalidate_materialized_view(
+		&mut self,
+		ns: &str,
+		db: &str,
+		view_tb: &str,
+	) -> Result<(), Error> {
+		self.invalidate_tb_cache(ns, db, view_tb);
+		self.stale.insert(crate::key::table::new(ns, db, view_tb).encode()?);
+		let beg = crate::key::vr::prefix(ns, db, view_tb);
+		let end = crate::key::vr::suffix(ns, db, view_tb);
+		self.delr(beg..end, u32::MAX).await
+	}
+	// Mark the views which depend on a table as stale
+	async fn invalidate_dependent_views(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+	) -> Result<(), Error> {
+		let views = self.all_ft(ns, db, tb).await?;
+		self.invalidate_views(ns, db, &views).await
+	}
+	// Mark views as stale, unless this transaction has already done so
+	async fn invalidate_views(
+		&mut self,
+		ns: &str,
+		db: &str,
+		views: &[DefineTableStatement],
+	) -> Result<(), Error> {
+		for ft in views.iter() {
+			if !self.stale.contains(&crate::key::table::new(ns, db, &ft.name).encode()?) {
+				self.invalidate_materialized_view(ns, db, &ft.name).await?;
+			}
+		}
+		Ok(())
+	}
+	/// Fetch a record from the datastore, counting the access to its table.
+	///
+	/// If the record does not exist, then [`Value::None`] is returned.
+	pub async fn get_record(&mut self, ns: &str, db: &str, thing: &Thing) -> Result<Value, Error> {
+		self.count_table_access(ns, db, &thing.tb);
+		let key = crate::key::thing::new(ns, db, &thing.tb, &thing.id);
+		match self.get(key).await? {
+			Some(v) => Ok(Value::from(v)),
+			None => Ok(Value::None),
+		}
+	}
+	/// Retrieve a range of records from a table, counting the access to the table.
+	///
+	/// This function fetches the full range of key-value pairs, in a single request to the underlying datastore.
+	pub async fn scan_records<K>(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		rng: Range<K>,
+		limit: u32,
+	) -> Result<Vec<(Key, Val)>, Error>
+	where
+		K: Into<Key>,
+	{
+		self.count_table_access(ns, db, tb);
+		self.scan(rng, limit).await
+	}
+	/// Find the records which reference a record through fields of type `record(tb)`.
+	///
+	/// Each referencing record is returned along with the table and the name of the
+	/// field through which the record is referenced.
+	pub async fn get_referencing_records(
+		&mut self,
+		ns: &str,
+		db: &str,
+		thing: &Thing,
+	) -> Result<Vec<(String, String, Thing)>, Error> {
+		let mut out = vec![];
+		for (tb, fd) in self.get_referencing_tables(ns, db, &thing.tb).await? {
+			let path: Idiom = fd.split('.').map(Part::from).collect::<Vec<_>>().into();
+			let res = self
+				.scan_matching(ns, db, &tb, u32::MAX, |v| {
+					record_refs(v.pick(&path)).contains(thing)
+				})
+				.await?;
+			for (rid, _) in res {
+				out.push((tb.clone(), fd.clone(), rid));
+			}
+		}
+		Ok(out)
+	}
+	/// Find the tables which reference a table through fields of type `record(tb)`.
+	///
+	/// Each referencing table is returned along with the name of the field through
+	/// which the table is referenced. Fields of type `record` which do not specify
+	/// any tables are considered to reference every table.
+	pub async fn get_referencing_tables(
+		&mut self,
+		ns: &str,
+		db: &str,
+		target_tb: &str,
+	) -> Result<Vec<(String, String)>, Error> {
+		let mut out = vec![];
+		for tb in self.all_tb(ns, db).await?.iter() {
+			for fd in self.all_fd(ns, db, &tb.name).await?.iter() {
+				if let Some(Kind::Record(tbs)) = &fd.kind {
+					if tbs.is_empty() || tbs.iter().any(|v| v.0 == target_tb) {
+						out.push((tb.name.to_string(), fd.name.to_string()));
+					}
+				}
+			}
+		}
+		Ok(out)
+	}
+	/// Delete a record, along with all of the records which reference it through
+	/// fields defined with `ON DELETE CASCADE`.
+	///
+	/// Referencing records are deleted recursively, and each record is only visited
+	/// once, so circular references are handled safely. Returns the total number of
+	/// records which were deleted.
+	pub async fn cascade_delete(
+		&mut self,
+		ns: &str,
+		db: &str,
+		thing: &Thing,
+	) -> Result<u64, Error> {
+		let mut visited: BTreeSet<Key> = BTreeSet::new();
+		let mut stack = vec![thing.clone()];
+		let mut num = 0;
+		while let Some(rid) = stack.pop() {
+			let key: Key = crate::key::thing::new(ns, db, &rid.tb, &rid.id).into();
+			// Skip records which have already been visited
+			if !visited.insert(key.clone()) {
+				continue;
+			}
+			// Skip records which do not exist
+			let val: Value = match self.get(key.clone()).await? {
+				Some(v) => (&v).into(),
+				None => continue,
+			};
+			// Purge the record data and index entries
+			self.del_record(ns, db, &rid).await?;
+			for ix in self.all_ix(ns, db, &rid.tb).await?.iter() {
+				match self.delete_composite_index_entry(ns, db, &rid.tb, ix, &rid, &val).await {
+					Err(Error::TxConditionNotMet) => (),
+					res => res?,
+				}
+			}
+			num += 1;
+			// Queue the records which cascade from this record
+			for (tb, fd, v) in self.get_referencing_records(ns, db, &rid).await? {
+				let fds = self.all_fd(ns, db, &tb).await?;
+				if fds.iter().any(|f| f.cascade && f.name.to_string() == fd) {
+					stack.push(v);
+				}
+			}
+		}
+		Ok(num)
+	}
+	/// Remove all of the records from a table, along with their index entries.
+	///
+	/// The record and index key ranges are deleted directly, rather than deleting
+	/// each record in turn. As with deleting a record, the quota usage of the table is
+	/// released, any dependent views are marked as stale, and the removal of each
+	/// record is logged in the change feed of the table. Returns the number of records
+	/// which were deleted.
+	pub async fn truncate_table(&mut self, ns: &str, db: &str, tb: &str) -> Result<u64, Error> {
+		// Check the table is not locked
+		self.check_table_lock(ns, db, tb).await?;
+		// Check if the table has a change feed
+		let feed = match self.get_and_cache_tb(ns, db, tb).await {
+			Ok(tb) => tb.changefeed.is_some(),
+			Err(Error::TbNotFound) => false,
+			Err(e) => return Err(e),
+		};
+		// Check if the storage usage must be released
+		let quota = self.get_quota(ns, db).await?.is_some();
+		// Count the records
+		let beg = thing::prefix(ns, db, tb);
+		let end = thing::suffix(ns, db, tb);
+		let mut rng = beg.clone()..end.clone();
+		let mut num = 0;
+		let mut len = 0;
+		loop {
+			// Get records batch, fetching the values only to measure them
+			let res: Vec<Key> = match quota {
+				true => {
+					let res = self.next_batch(&mut rng).await?;
+					len += res.iter().map(|(k, v)| (k.len() + v.len()) as u64).sum::<u64>();
+					res.into_iter().map(|(k, _)| k).collect()
+				}
+				false => self.next_batch_keys(&mut rng).await?,
+			};
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			num += res.len() as u64;
+			// Log the removal of each record
+			if feed {
+				for k in res {
+					let k: crate::key::thing::Thing = (&k).into();
+					let thing = Thing::from((k.tb, k.id));
+					self.log_change(ns, db, &thing, Val::from(&Value::None), true);
+				}
+			}
+		}
+		// Purge the record data
+		self.delr(beg..end, u32::MAX).await?;
+		// Purge the index entries
+		for ix in self.all_ix(ns, db, tb).await?.iter() {
+			let beg = crate::key::index::prefix(ns, db, tb, &ix.name);
+			let end = crate::key::index::suffix(ns, db, tb, &ix.name);
+			self.delr(beg..end, u32::MAX).await?;
+		}
+		if num > 0 {
+			// Release the database quota
+			self.release_quota_usage(ns, db, tb, num, len).await?;
+			// Mark any dependent views as stale
+			self.invalidate_dependent_views(ns, db, tb).await?;
+			// Update the table version
+			self.bump_tb_version(ns, db, tb).await?;
+		}
+		Ok(num)
+	}
+	/// Find the records in a table which would violate a unique index on the specified fields.
+	///
+	/// Records are grouped by the values of the fields, and each group of more than one
+	/// record is returned along with the duplicated values. As with unique indexes, records
+	/// where the fields are missing are grouped together.
+	pub async fn check_unique_constraint(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		fields: &[&str],
+	) -> Result<Vec<(Vec<Value>, Vec<Thing>)>, Error> {
+		// Parse the field paths
+		let paths: Vec<Idiom> = fields
+			.iter()
+			.map(|f| f.split('.').map(Part::from).collect::<Vec<_>>().into())
+			.collect();
+		// Fetch the records
+		let mut rng = thing::prefix(ns, db, tb)..thing::suffix(ns, db, tb);
+		let mut grp: BTreeMap<Vec<Value>, Vec<Thing>> = BTreeMap::new();
+		loop {
+			// Get records batch
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Parse the key-value
+				let k: crate::key::thing::Thing = (&k).into();
+				let v: Value = (&v).into();
+				// Group the record by the field values
+				let vals = paths.iter().map(|p| v.pick(p)).collect();
+				grp.entry(vals).or_default().push(Thing::from((k.tb, k.id)));
+			}
+		}
+		// Output the duplicate groups
+		Ok(grp.into_iter().filter(|(_, v)| v.len() > 1).collect())
+	}
+	/// Retrieve the raw entries of an index on a table.
+	///
+	/// Each entry is decoded into the indexed field values and the id of the record
+	/// which the entry points to. This function fetches key-value pairs from the
+	/// underlying datastore in batches of 1000.
+	pub async fn scan_index_entries(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		ix: &str,
+		limit: u32,
+	) -> Result<Vec<IndexEntry>, Error> {
+		let beg = crate::key::index::prefix(ns, db, tb, ix);
+		let end = crate::key::index::suffix(ns, db, tb, ix);
+		let res = self.getr(beg..end, limit).await?;
+		Ok(res
+			.into_iter()
+			.map(|(k, v)| {
+				let k: crate::key::index::Index = (&k).into();
+				let v: Thing = (&v).into();
+				IndexEntry {
+					index_key: k.fd.into(),
+					record_id: v,
+				}
+			})
+			.collect())
+	}
+	/// Count the number of entries in an index on a table.
+	///
+	/// This function fetches key-value pairs from the underlying datastore in batches of 1000.
+	pub async fn get_index_entry_count(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		ix: &str,
+	) -> Result<u64, Error> {
+		let beg = crate::key::index::prefix(ns, db, tb, ix);
+		let end = crate::key::index::suffix(ns, db, tb, ix);
+		self.count_range(beg..end).await
+	}
+	/// Compute statistics about the entries in an index on a table.
+	///
+	/// This function fetches key-value pairs from the underlying datastore in batches of 1000.
+	pub async fn get_index_statistics(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		ix: &str,
+	) -> Result<IndexStats, Error> {
+		let beg = crate::key::index::prefix(ns, db, tb, ix);
+		let end = crate::key::index::suffix(ns, db, tb, ix);
+		let mut rng = beg..end;
+		let mut out = IndexStats::default();
+		let mut last: Option<sql::array::Array> = None;
+		loop {
+			// Get entries batch
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Count the entry
+				out.entry_count += 1;
+				out.size_bytes += (k.len() + v.len()) as u64;
+				// Entries are ordered by the indexed values
+				let e: crate::key::index::Index = (&k).into();
+				if last.as_ref() != Some(&e.fd) {
+					out.distinct_values += 1;
+					last = Some(e.fd);
+				}
+			}
+		}
+		Ok(out)
+	}
+	/// Retrieve all index definitions for a specific table, along with statistics for each index.
+	pub async fn all_ix_with_stats(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+	) -> Result<Vec<(DefineIndexStatement, IndexStats)>, Error> {
+		let ixs = self.all_ix(ns, db, tb).await?;
+		let mut out = Vec::with_capacity(ixs.len());
+		for ix in ixs.iter() {
+			let stats = self.get_index_statistics(ns, db, tb, &ix.name).await?;
+			out.push((ix.clone(), stats));
+		}
+		Ok(out)
+	}
+	/// Count the keys in a range, along with the total size in bytes of the keys and values.
+	async fn measure_keys(&mut self, beg: Key, end: Key) -> Result<(u64, u64), Error> {
+		let mut rng = beg..end;
+		let mut num = 0;
+		let mut len = 0;
+		loop {
+			// Get keys batch
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Measure the keys
+			num += res.len() as u64;
+			len += res.iter().map(|(k, v)| (k.len() + v.len()) as u64).sum::<u64>();
+		}
+		Ok((num, len))
+	}
+	/// Describe the storage operations which a query would perform, without running it.
+	///
+	/// Each iterated target of a statement is mapped to a step, along with an estimate
+	/// of the number of rows visited, which is taken from the current contents of the
+	/// datastore. Statements which do not iterate over any records are ignored.
+	pub async fn explain_query(
+		&mut self,
+		ns: &str,
+		db: &str,
+		query: &str,
+	) -> Result<Vec<ExplainStep>, Error> {
+		// Parse the query
+		let query = sql::parse(query)?;
+		let mut out = vec![];
+		// Loop over the statements
+		for stm in query.iter() {
+			// Get the statement targets
+			let (what, cond, create) = match stm {
+				sql::Statement::Select(v) => (&v.what, &v.cond, false),
+				sql::Statement::Update(v) => (&v.what, &v.cond, false),
+				sql::Statement::Delete(v) => (&v.what, &v.cond, false),
+				sql::Statement::Create(v) => (&v.what, &None, true),
+				_ => continue,
+			};
+			// Flatten any array targets
+			let what = what.iter().flat_map(|v| match v {
+				Value::Array(v) => v.iter().collect::<Vec<_>>(),
+				v => vec![v],
+			});
+			// Plan each of the targets
+			let mut rows = 0;
+			for w in what {
+				let step = match w {
+					Value::Table(v) if create => ExplainStep {
+						operation: "Record creation".to_owned(),
+						target: v.to_string(),
+						estimated_rows: 1,
+						cost: 1,
+					},
+					Value::Table(v) => {
+						let beg = thing::prefix(ns, db, v);
+						let end = thing::suffix(ns, db, v);
+						let n = self.count_range(beg..end).await?;
+						ExplainStep {
+							operation: "Table scan".to_owned(),
+							target: v.to_string(),
+							estimated_rows: n,
+							cost: n,
+						}
+					}
+					Value::Thing(v) => ExplainStep {
+						operation: "Record fetch".to_owned(),
+						target: v.to_string(),
+						estimated_rows: 1,
+						cost: 1,
+					},
+					Value::Edges(v) => {
+						let beg = crate::key::graph::prefix(ns, db, &v.from.tb, &v.from.id);
+						let end = crate::key::graph::suffix(ns, db, &v.from.tb, &v.from.id);
+						let n = self.count_range(beg..end).await?;
+						ExplainStep {
+							operation: "Graph traversal".to_owned(),
+							target: v.to_string(),
+							estimated_rows: n,
+							cost: n,
+						}
+					}
+					Value::Model(v) => {
+						let n = match v {
+							sql::model::Model::Count(_, c) => *c,
+							sql::model::Model::Range(_, b, e) => (e + 1).saturating_sub(*b),
+						};
+						ExplainStep {
+							operation: "Record generation".to_owned(),
+							target: v.to_string(),
+							estimated_rows: n,
+							cost: n,
+						}
+					}
+					v => ExplainStep {
+						operation: "Value iteration".to_owned(),
+						target: v.to_string(),
+						estimated_rows: 1,
+						cost: 1,
+					},
+				};
+				rows += step.estimated_rows;
+				out.push(step);
+			}
+			// Plan the record filtering
+			if let Some(cond) = cond {
+				out.push(ExplainStep {
+					operation: "Filter".to_owned(),
+					target: cond.to_string(),
+					estimated_rows: rows,
+					cost: rows,
+				});
+			}
+		}
+		Ok(out)
+	}
+	/// Estimate the cost of scanning a table for the records which match a set of predicates.
+	///
+	/// An index can be used when each of its fields is compared for equality by one of the
+	/// predicates. The number of matching rows is estimated from the statistics of each usable
+	/// index, as the number of entries per distinct value, and the index with the fewest rows
+	/// is chosen. Otherwise the estimate is for a full scan of the table.
+	pub async fn estimate_scan_cost(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		predicates: &[Predicate],
+	) -> Result<QueryCostEstimate, Error> {
+		// Measure the table
+		let beg = thing::prefix(ns, db, tb);
+		let end = thing::suffix(ns, db, tb);
+		let (total, size) = self.measure_keys(beg, end).await?;
+		let mut out = QueryCostEstimate {
+			estimated_rows: total,
+			estimated_bytes: size,
+			index_used: None,
+			full_scan: true,
+		};
+		// Find the fields which are compared for equality
+		let fields: Vec<&str> = predicates
+			.iter()
+			.filter(|p| matches!(p.op, sql::Operator::Equal | sql::Operator::Exact))
+			.map(|p| p.field.as_str())
+			.collect();
+		// Compare each of the usable indexes
+		for (ix, stats) in self.all_ix_with_stats(ns, db, tb).await? {
+			if !ix.cols.iter().all(|c| fields.contains(&c.to_string().as_str())) {
+				continue;
+			}
+			let rows = match (ix.uniq, stats.distinct_values) {
+				(_, 0) => 0,
+				(true, _) => 1,
+				(false, n) => stats.entry_count.div_ceil(n),
+			};
+			if out.full_scan || rows < out.estimated_rows {
+				out = QueryCostEstimate {
+					estimated_rows: rows,
+					estimated_bytes: match total {
+						0 => 0,
+						n => rows * size / n,
+					},
+					index_used: Some(ix.name.to_string()),
+					full_scan: false,
+				};
+			}
+		}
+		Ok(out)
+	}
+	/// Retrieve the records which are indexed under a specific value in an index.
+	///
+	/// For an index over multiple fields, the value should be an array containing
+	/// a value for each of the indexed fields.
+	pub async fn scan_by_index(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		ix: &str,
+		value: &Value,
+		limit: u32,
+	) -> Result<Vec<(Thing, Value)>, Error> {
+		// Fetch the index definition
+		let key = crate::key::ix::new(ns, db, tb, ix);
+		let val = self.get(key).await?.ok_or(Error::IxNotFound)?;
+		let def: DefineIndexStatement = val.into();
+		// Encode the index entry keys
+		let key = index_key(ns, db, tb, ix, index_values(&def, value));
+		let beg = key.clone();
+		let end = [key, vec![0xff]].concat();
+		// Fetch the indexed records
+		self.get_indexed_records(ns, db, beg..end, true, limit).await
+	}
+	/// Retrieve the records which are indexed within a range of values in an index.
+	///
+	/// Both bounds are inclusive. A `NULL` or `NONE` bound leaves that side of the
+	/// range open, so that all index entries before or after the other bound are used.
+	/// The records are returned in ascending order of their indexed values, or in
+	/// descending order if `asc` is false, in which case the `limit` records with the
+	/// highest values are returned.
+	#[allow(clippy::too_many_arguments)]
+	pub async fn scan_by_index_range(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		ix: &str,
+		low: &Value,
+		high: &Value,
+		asc: bool,
+		limit: u32,
+	) -> Result<Vec<(Thing, Value)>, Error> {
+		// Fetch the index definition
+		let key = crate::key::ix::new(ns, db, tb, ix);
+		let val = self.get(key).await?.ok_or(Error::IxNotFound)?;
+		let def: DefineIndexStatement = val.into();
+		// Encode the index entry keys
+		let beg = match low.is_none() {
+			true => crate::key::index::prefix(ns, db, tb, ix),
+			false => index_key(ns, db, tb, ix, index_values(&def, low)),
+		};
+		let end = match high.is_none() {
+			true => crate::key::index::suffix(ns, db, tb, ix),
+			false => [index_key(ns, db, tb, ix, index_values(&def, high)), vec![0xff]].concat(),
+		};
+		// Fetch the indexed records
+		self.get_indexed_records(ns, db, beg..end, asc, limit).await
+	}
+	/// Retrieve the single record which is indexed under a specific value in a unique index.
+	///
+	/// If the index contains more than one entry for the value, then the index is
+	/// corrupt, and an error is returned.
+	pub async fn get_by_unique_index(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		ix: &str,
+		value: &Value,
+	) -> Result<Option<(Thing, Value)>, Error> {
+		// Fetch the index definition
+		let key = crate::key::ix::new(ns, db, tb, ix);
+		let val = self.get(key).await?.ok_or(Error::IxNotFound)?;
+		let def: DefineIndexStatement = val.into();
+		// Encode the index entry keys
+		let key = index_key(ns, db, tb, ix, index_values(&def, value));
+		let beg = key.clone();
+		let end = [key, vec![0xff]].concat();
+		// Fetch the index entries
+		let mut res = self.getr(beg..end, 2).await?;
+		if res.len() > 1 {
+			return Err(Error::DuplicateIndexValue);
+		}
+		// Fetch the indexed record
+		match res.pop() {
+			Some((_, v)) => {
+				let rid: Thing = (&v).into();
+				let key = thing::new(ns, db, &rid.tb, &rid.id);
+				match self.get(key).await? {
+					Some(v) => Ok(Some((rid, (&v).into()))),
+					None => Ok(None),
+				}
+			}
+			None => Ok(None),
+		}
+	}
+	/// Retrieve the records with a datetime field value within a range of datetimes.
+	///
+	/// Both bounds are inclusive. If the table has an index over the field alone, then
+	/// the records are found by scanning the index, and otherwise all records in the
+	/// table are scanned. This function fetches key-value pairs from the underlying
+	/// datastore in batches of 1000.
+	#[allow(clippy::too_many_arguments)]
+	pub async fn scan_by_datetime_range(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		field: &str,
+		from: DateTime<Utc>,
+		to: DateTime<Utc>,
+		limit: u32,
+	) -> Result<Vec<(Thing, Value)>, Error> {
+		// Check if a value is within the range
+		let within = |v: Option<&Value>| match v {
+			Some(Value::Datetime(v)) => v.0 >= from && v.0 <= to,
+			_ => false,
+		};
+		// Parse the field path
+		let path: Idiom = field.split('.').map(Part::from).collect::<Vec<_>>().into();
+		// Find an index over the field
+		let ixs = self.all_ix(ns, db, tb).await?;
+		let ix = ixs.iter().find(|ix| ix.cols.len() == 1 && ix.cols[0] == path);
+		// Get the key range to scan
+		let (beg, end) = match ix {
+			// Datetimes are encoded as text in index keys, so the sub-second part
+			// does not sort correctly, and the bounds are widened to whole seconds
+			Some(ix) => {
+				let low = from.with_nanosecond(0).unwrap_or(from) - chrono::Duration::seconds(1);
+				let high = to.with_nanosecond(0).unwrap_or(to) + chrono::Duration::seconds(1);
+				let low = Value::from(sql::datetime::Datetime(low)).into();
+				let high = Value::from(sql::datetime::Datetime(high)).into();
+				let beg = index_key(ns, db, tb, &ix.name, low);
+				let end = [index_key(ns, db, tb, &ix.name, high), vec![0xff]].concat();
+				(beg, end)
+			}
+			None => (thing::prefix(ns, db, tb), thing::suffix(ns, db, tb)),
+		};
+		let mut rng = beg..end;
+		let mut out = vec![];
+		loop {
+			// Get entries batch
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Check the field value
+				match ix {
+					Some(_) => {
+						let e: crate::key::index::Index = (&k).into();
+						if within(e.fd.first()) {
+							let rid: Thing = (&v).into();
+							let key = thing::new(ns, db, &rid.tb, &rid.id);
+							if let Some(v) = self.get(key).await? {
+								out.push((rid, (&v).into()));
+							}
+						}
+					}
+					None => {
+						let k: crate::key::thing::Thing = (&k).into();
+						let v: Value = (&v).into();
+						if within(Some(&v.pick(&path))) {
+							out.push((Thing::from((k.tb, k.id)), v));
+						}
+					}
+				}
+				// Check the limit
+				if out.len() >= limit as usize {
+					return Ok(out);
+				}
+			}
+		}
+		Ok(out)
+	}
+	/// Count the records with a datetime field value in each time window within a range.
+	///
+	/// The range is divided into consecutive windows of the specified duration, starting
+	/// at the beginning of the range, and the start of each window is returned with the
+	/// number of records within it. Both bounds are inclusive, and any partial window at
+	/// the end of the range counts the records up until the end of the range. An empty
+	/// range or window returns no windows.
+	#[allow(clippy::too_many_arguments)]
+	pub async fn count_by_window(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		field: &str,
+		from: DateTime<Utc>,
+		to: DateTime<Utc>,
+		window: Duration,
+	) -> Result<Vec<(DateTime<Utc>, u64)>, Error> {
+		// Check the range and window
+		let span = match (to - from).to_std() {
+			Ok(v) if !window.is_zero() => v.as_nanos(),
+			_ => return Ok(vec![]),
+		};
+		let size = window.as_nanos();
+		// Create the empty windows
+		let n = std::cmp::max(1, span.div_ceil(size));
+		let mut out = (0..n)
+			.map(|i| {
+				i.checked_mul(size)
+					.and_then(|v| i64::try_from(v).ok())
+					.and_then(|v| from.checked_add_signed(chrono::Duration::nanoseconds(v)))
+					.map(|v| (v, 0))
+					.ok_or_else(|| Error::WindowOutOfRange {
+						from: from.to_rfc3339(),
+						window: i,
+					})
+			})
+			.collect::<Result<Vec<_>, Error>>()?;
+		// Count the records in each window
+		let path: Idiom = field.split('.').map(Part::from).collect::<Vec<_>>().into();
+		let res = self.scan_by_datetime_range(ns, db, tb, field, from, to, u32::MAX).await?;
+		for (_, v) in res.iter() {
+			if let Value::Datetime(v) = v.pick(&path) {
+				let i = (v.0 - from).to_std().unwrap_or_default().as_nanos() / size;
+				out[std::cmp::min(i, n - 1) as usize].1 += 1;
+			}
+		}
+		Ok(out)
+	}
+	/// Retrieve the records with a geometry field value within a bounding box.
+	///
+	/// A record matches if its geometry intersects the bounding box, so lines and
+	/// polygons which cross the edge of the box are included. All records in the
+	/// table are scanned, as geometry values are not indexed.
+	#[allow(clippy::too_many_arguments)]
+	pub async fn scan_within_bbox(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		field: &str,
+		min_lat: f64,
+		min_lon: f64,
+		max_lat: f64,
+		max_lon: f64,
+		limit: u32,
+	) -> Result<Vec<(Thing, Value)>, Error> {
+		let path: Idiom = field.split('.').map(Part::from).collect::<Vec<_>>().into();
+		let bbox = geo::Rect::new((min_lon, min_lat), (max_lon, max_lat)).to_polygon();
+		let bbox = sql::geometry::Geometry::Polygon(bbox);
+		self.scan_matching(ns, db, tb, limit, |v| match v.pick(&path) {
+			Value::Geometry(v) => v.intersects(&bbox),
+			_ => false,
+		})
+		.await
+	}
+	/// Retrieve the records with a geographic point field value within a distance of a point.
+	///
+	/// The distance is measured in metres along the surface of the earth, using the
+	/// haversine formula. Only point geometries are matched. All records in the table
+	/// are scanned, as geometry values are not indexed.
+	#[allow(clippy::too_many_arguments)]
+	pub async fn scan_within_radius(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		field: &str,
+		lat: f64,
+		lon: f64,
+		radius: f64,
+		limit: u32,
+	) -> Result<Vec<(Thing, Value)>, Error> {
+		let path: Idiom = field.split('.').map(Part::from).collect::<Vec<_>>().into();
+		let centre = geo::Point::new(lon, lat);
+		self.scan_matching(ns, db, tb, limit, |v| match v.pick(&path) {
+			Value::Geometry(sql::geometry::Geometry::Point(v)) => {
+				v.haversine_distance(&centre) <= radius
+			}
+			_ => false,
+		})
+		.await
+	}
+	/// Group the entries of an index over a geographic point field by geohash cell.
+	///
+	/// The precision is the number of characters in each geohash, and is limited to
+	/// between 1 and 12. Index entries which are not a single point are skipped. This
+	/// function fetches key-value pairs from the underlying datastore in batches of 1000.
+	pub async fn scan_geohash_index(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		ix: &str,
+		precision: u8,
+	) -> Result<Vec<(String, Vec<Thing>)>, Error> {
+		// Check the index exists
+		let key = crate::key::ix::new(ns, db, tb, ix);
+		self.get(key).await?.ok_or(Error::IxNotFound)?;
+		// Check the geohash precision
+		let precision = precision.clamp(1, 12) as usize;
+		// Fetch the index entries
+		let beg = crate::key::index::prefix(ns, db, tb, ix);
+		let end = crate::key::index::suffix(ns, db, tb, ix);
+		let mut rng = beg..end;
+		let mut out: BTreeMap<String, Vec<Thing>> = BTreeMap::new();
+		loop {
+			// Get entries batch
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Group the entry by geohash
+				let e: crate::key::index::Index = (&k).into();
+				if let [Value::Geometry(sql::geometry::Geometry::Point(p))] = e.fd.as_slice() {
+					let hash = crate::fnc::util::geo::encode(*p, precision).to_raw();
+					out.entry(hash).or_default().push((&v).into());
+				}
+			}
+		}
+		Ok(out.into_iter().collect())
+	}
+	/// Search the text values in an index, returning the records ranked by relevance.
+	///
+	/// The text of each index entry is split into lowercase alphanumeric terms, and
+	/// each record containing any of the query terms is scored using BM25. The records
+	/// are returned with the highest scoring first.
+	pub async fn search_full_text(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		ix: &str,
+		query: &str,
+		limit: u32,
+	) -> Result<Vec<(Thing, f64)>, Error> {
+		// The BM25 tuning parameters
+		const K1: f64 = 1.2;
+		const B: f64 = 0.75;
+		// Parse the query terms
+		let mut terms = text_terms(query).collect::<Vec<_>>();
+		terms.sort();
+		terms.dedup();
+		// Fetch the term frequencies
+		let stats = self.scan_text_index(ns, db, tb, ix, &terms).await?;
+		let total = stats.total_docs as f64;
+		let avgdl = stats.total_terms as f64 / f64::max(total, 1.0);
+		// Count the documents containing each term
+		let df = (0..terms.len())
+			.map(|i| stats.docs.iter().filter(|(_, tf, _)| tf[i] > 0).count() as f64)
+			.collect::<Vec<_>>();
+		// Score each of the matching documents
+		let mut out = stats
+			.docs
+			.into_iter()
+			.map(|(rid, tf, dl)| {
+				let score = tf
+					.iter()
+					.zip(df.iter())
+					.map(|(&tf, &df)| {
+						let tf = tf as f64;
+						let idf = ((total - df + 0.5) / (df + 0.5) + 1.0).ln();
+						idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * dl as f64 / avgdl))
+					})
+					.sum::<f64>();
+				(rid, score)
+			})
+			.collect::<Vec<_>>();
+		out.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+		out.truncate(limit as usize);
+		Ok(out)
+	}
+	/// Count the records containing a term in the text values in an index.
+	///
+	/// This returns the number of index entries which contain the term, along with
+	/// the total number of index entries, which are used for computing the inverse
+	/// document frequency of the term.
+	pub async fn get_doc_frequency(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		ix: &str,
+		term: &str,
+	) -> Result<(u64, u64), Error> {
+		let stats = self.scan_text_index(ns, db, tb, ix, &[term.to_lowercase()]).await?;
+		Ok((stats.docs.len() as u64, stats.total_docs))
+	}
+	/// Count the occurrences of a set of terms in the text values in an index.
+	///
+	/// This function fetches key-value pairs from the underlying datastore in batches of 1000.
+	async fn scan_text_index(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		ix: &str,
+		terms: &[String],
+	) -> Result<TextStats, Error> {
+		// Check the index exists
+		let key = crate::key::ix::new(ns, db, tb, ix);
+		self.get(key).await?.ok_or(Error::IxNotFound)?;
+		// Fetch the index entries
+		let beg = crate::key::index::prefix(ns, db, tb, ix);
+		let end = crate::key::index::suffix(ns, db, tb, ix);
+		let mut rng = beg..end;
+		let mut out = TextStats::default();
+		loop {
+			// Get entries batch
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Count the terms in the entry
+				let e: crate::key::index::Index = (&k).into();
+				let mut tf = vec![0; terms.len()];
+				let mut dl = 0;
+				for v in e.fd.iter() {
+					if let Value::Strand(v) = v {
+						for t in text_terms(v) {
+							if let Ok(i) = terms.binary_search(&t) {
+								tf[i] += 1;
+							}
+							dl += 1;
+						}
+					}
+				}
+				out.total_docs += 1;
+				out.total_terms += dl;
+				if tf.iter().any(|v| *v > 0) {
+					out.docs.push(((&v).into(), tf, dl));
+				}
+			}
+		}
+		Ok(out)
+	}
+	/// Retrieve the records in a table which match a filter.
+	///
+	/// This function fetches key-value pairs from the underlying datastore in batches of 1000.
+	async fn scan_matching<F>(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		limit: u32,
+		filter: F,
+	) -> Result<Vec<(Thing, Value)>, Error>
+	where
+		F: Fn(&Value) -> bool,
+	{
+		let mut rng = thing::prefix(ns, db, tb)..thing::suffix(ns, db, tb);
+		let mut out = vec![];
+		loop {
+			// Get records batch
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Parse the key-value
+				let k: crate::key::thing::Thing = (&k).into();
+				let v: Value = (&v).into();
+				// Check the record
+				if filter(&v) {
+					out.push((Thing::from((k.tb, k.id)), v));
+					if out.len() >= limit as usize {
+						return Ok(out);
+					}
+				}
+			}
+		}
+		Ok(out)
+	}
+	/// Retrieve the records in a table whose string ids start with a prefix.
+	///
+	/// Only the key range of the matching record ids is scanned, so this does not
+	/// require a full table scan. Records with numeric ids are never matched.
+	pub async fn scan_by_id_prefix(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		id_prefix: &str,
+		limit: u32,
+	) -> Result<Vec<(Thing, Value)>, Error> {
+		let id = sql::id::Id::String(id_prefix.to_owned());
+		let mut beg: Key = thing::new(ns, db, tb, &id).into();
+		// Remove the string terminator
+		beg.pop();
+		let end: Key = beg.clone().add(0xff);
+		let res = self.getr(beg..end, limit).await?;
+		Ok(res
+			.into_iter()
+			.map(|(k, v)| {
+				let k: crate::key::thing::Thing = (&k).into();
+				(Thing::from((k.tb, k.id)), (&v).into())
+			})
+			.collect())
+	}
+	/// Retrieve the records which are pointed to by a range of index entries, in either direction.
+	async fn get_indexed_records(
+		&mut self,
+		ns: &str,
+		db: &str,
+		rng: Range<Key>,
+		asc: bool,
+		limit: u32,
+	) -> Result<Vec<(Thing, Value)>, Error> {
+		let res = match asc {
+			true => self.getr(rng, limit).await?,
+			false => self.scan_rev(rng, limit).await?,
+		};
+		let mut out = Vec::with_capacity(res.len());
+		for (_, v) in res.into_iter() {
+			// Parse the record id
+			let rid: Thing = (&v).into();
+			// Fetch the record
+			let key = thing::new(ns, db, &rid.tb, &rid.id);
+			if let Some(v) = self.get(key).await? {
+				out.push((rid, (&v).into()));
+			}
+		}
+		Ok(out)
+	}
+	/// Add the index entry for a record to an index over one or more fields.
+	///
+	/// The indexed values are taken from the record in the order of the fields in the
+	/// index definition. For a unique index, an [`Error::IndexExists`] error is returned
+	/// when another record is already indexed under the same values. Any other error
+	/// from the datastore is returned unchanged.
+	pub async fn create_composite_index_entry(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		ix: &DefineIndexStatement,
+		thing: &Thing,
+		record: &Value,
+	) -> Result<(), Error> {
+		let fd = ix.cols.iter().map(|v| record.pick(v)).collect::<Vec<_>>().into();
+		let key = match ix.uniq {
+			true => crate::key::index::new(ns, db, tb, &ix.name, fd, None),
+			false => crate::key::index::new(ns, db, tb, &ix.name, fd, Some(&thing.id)),
+		};
+		match self.putc(key, thing, None).await {
+			Err(Error::TxConditionNotMet | Error::TxKeyAlreadyExists) => Err(Error::IndexExists {
+				index: ix.name.to_string(),
+				thing: thing.to_string(),
+			}),
+			res => res,
+		}
+	}
+	/// Remove the index entry for a record from an index over one or more fields.
+	///
+	/// The indexed values are taken from the record in the order of the fields in the
+	/// index definition. The entry is only removed if it points to the specified record.
+	pub async fn delete_composite_index_entry(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		ix: &DefineIndexStatement,
+		thing: &Thing,
+		record: &Value,
+	) -> Result<(), Error> {
+		let fd = ix.cols.iter().map(|v| record.pick(v)).collect::<Vec<_>>().into();
+		let key = match ix.uniq {
+			true => crate::key::index::new(ns, db, tb, &ix.name, fd, None),
+			false => crate::key::index::new(ns, db, tb, &ix.name, fd, Some(&thing.id)),
+		};
+		self.delc(key, Some(thing)).await
+	}
+	/// Rename an index on a table, keeping the existing index entries.
+	///
+	/// The index definition is stored under the new name, and each index entry is
+	/// rewritten under the new index key prefix, so the index does not need to be
+	/// rebuilt. This fails if an index with the new name already exists.
+	pub async fn rename_index(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		old_ix: &str,
+		new_ix: &str,
+	) -> Result<(), Error> {
+		// Fetch the index definition
+		let key = crate::key::ix::new(ns, db, tb, old_ix);
+		let val = self.get(key.clone()).await?.ok_or(Error::IxNotFound)?;
+		let mut ix: DefineIndexStatement = val.into();
+		// Store the renamed definition
+		ix.name = new_ix.into();
+		self.put(crate::key::ix::new(ns, db, tb, new_ix), &ix).await?;
+		// Rewrite the index entries
+		let beg = crate::key::index::prefix(ns, db, tb, old_ix);
+		let end = crate::key::index::suffix(ns, db, tb, old_ix);
+		let mut rng = beg..end;
+		loop {
+			// Get entries batch
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Rewrite the entry key
+				let e: crate::key::index::Index = (&k).into();
+				let e = crate::key::index::new(ns, db, tb, new_ix, e.fd, e.id.as_ref());
+				self.set(e, v).await?;
+				self.del(k).await?;
+			}
+		}
+		// Delete the old definition
+		self.del(key).await?;
+		// Clear the cached index definitions
+		self.invalidate_tb_cache(ns, db, tb);
+		Ok(())
+	}
+	/// Rename a scope on a database, along with all of its token definitions.
+	///
+	/// The scope and token definitions are stored under the new scope name, and
+	/// the definitions under the old scope name are then removed. This fails if a
+	/// scope with the new name already exists.
+	pub async fn rename_scope(
+		&mut self,
+		ns: &str,
+		db: &str,
+		old_sc: &str,
+		new_sc: &str,
+	) -> Result<(), Error> {
+		// Fetch the scope definition
+		let key = crate::key::sc::new(ns, db, old_sc);
+		let val = self.get(key.clone()).await?.ok_or(Error::ScNotFound)?;
+		let mut sc: DefineScopeStatement = val.into();
+		// Store the renamed definition
+		sc.name = new_sc.into();
+		self.put(crate::key::sc::new(ns, db, new_sc), &sc).await?;
+		// Copy the token definitions
+		for tk in self.all_st(ns, db, old_sc).await?.iter() {
+			self.put(crate::key::st::new(ns, db, new_sc, &tk.name), tk).await?;
+		}
+		// Delete the old definitions
+		self.del(key).await?;
+		let beg = crate::key::st::prefix(ns, db, old_sc);
+		let end = crate::key::st::suffix(ns, db, old_sc);
+		self.delr(beg..end, u32::MAX).await?;
+		// Invalidate the cached definitions
+		self.cache.del(&crate::key::sc::prefix(ns, db));
+		self.cache.del(&crate::key::st::prefix(ns, db, old_sc));
+		self.cache.del(&crate::key::st::prefix(ns, db, new_sc));
+		self.cache.del(&crate::key::st::all(ns, db));
+		Ok(())
+	}
+	/// Verify a token with the token definitions of a namespace, and optionally a database.
+	///
+	/// The namespace tokens, database tokens, scope tokens, and scope signing keys are
+	/// each tried in turn, and the claims of the token are returned from the first
+	/// definition which successfully verifies the token.
+	pub async fn decode_jwt(
+		&mut self,
+		ns: &str,
+		db: Option<&str>,
+		token: &str,
+	) -> Result<JwtClaims, Error> {
+		// Collect the token definitions
+		let mut defs: Vec<TokenDef> = vec![];
+		for tk in self.all_nt(ns).await?.iter() {
+			defs.push((None, None, None, tk.kind.clone(), tk.code.clone()));
+		}
+		if let Some(db) = db {
+			for tk in self.all_dt(ns, db).await?.iter() {
+				defs.push((Some(db), None, None, tk.kind.clone(), tk.code.clone()));
+			}
+			for sc in self.all_sc(ns, db).await?.iter() {
+				let name = Some(sc.name.to_string());
+				for tk in self.all_st(ns, db, &sc.name).await?.iter() {
+					defs.push((Some(db), name.clone(), None, tk.kind.clone(), tk.code.clone()));
+				}
+				defs.push((Some(db), name, Some(sc.nonce), Algorithm::Hs512, sc.code.clone()));
+			}
+		}
+		// Verify the token with each definition
+		for (db, sc, nonce, kind, code) in defs {
+			let (key, val) = match super::jwt::config(&kind, &code) {
+				Ok(v) => v,
+				Err(_) => continue,
+			};
+			let mut claims = match jsonwebtoken::decode::<Claims>(token, &key, &val) {
+				Ok(v) => v.claims,
+				Err(_) => continue,
+			};
+			// Check the token has not been revoked
+			if let Some(nonce) = nonce {
+				if claims.remove("nonce").and_then(|v| v.as_u64()).unwrap_or(0) != nonce {
+					continue;
+				}
+			}
+			let text = |v: Option<serde_json::Value>| match v {
+				Some(serde_json::Value::String(v)) => Some(v),
+				_ => None,
+			};
+			let sub = text(claims.remove("sub"));
+			let id = text(claims.remove("id"));
+			let exp = claims.remove("exp").and_then(|v| v.as_u64()).unwrap_or(0);
+			for k in ["ns", "db", "sc"] {
+				claims.remove(k);
+			}
+			return Ok(JwtClaims {
+				subject: sub.or(id).unwrap_or_default(),
+				ns: ns.to_owned(),
+				db: db.map(str::to_owned),
+				sc,
+				exp,
+				extra: sql::json(&serde_json::to_string(&claims).unwrap_or_default())?,
+			});
+		}
+		Err(Error::InvalidToken)
+	}
+	/// Issue a signed token for a subject.
+	///
+	/// When a scope is specified, the token is signed with the signing key of the
+	/// scope, otherwise the token is signed with the first token definition of the
+	/// database. The extra claims, if they are an object, are added to the token.
+	pub async fn issue_jwt(
+		&mut self,
+		ns: &str,
+		db: &str,
+		sc: Option<&str>,
+		subject: &str,
+		extra_claims: Value,
+		expiry: Duration,
+	) -> Result<String, Error> {
+		// Fetch the signing key
+		let (tk, nonce, kind, code) = match sc {
+			Some(sc) => {
+				let sc = self.get_sc(ns, db, sc).await?;
+				(None, Some(sc.nonce), Algorithm::Hs512, sc.code)
+			}
+			None => {
+				let dts = self.all_dt(ns, db).await?;
+				let dt = dts.first().ok_or(Error::DtNotFound)?;
+				(Some(dt.name.to_string()), None, dt.kind.clone(), dt.code.clone())
+			}
+		};
+		// Build the token claims
+		let mut claims = match serde_json::to_value(&extra_claims) {
+			Ok(serde_json::Value::Object(v)) => v.into_iter().collect(),
+			_ => Claims::new(),
+		};
+		let now = Utc::now().timestamp();
+		let exp = now + expiry.as_secs() as i64;
+		claims.insert("iss".to_owned(), "SurrealDB".into());
+		claims.insert("iat".to_owned(), now.into());
+		claims.insert("nbf".to_owned(), now.into());
+		claims.insert("exp".to_owned(), exp.into());
+		claims.insert("ns".to_owned(), ns.into());
+		claims.insert("db".to_owned(), db.into());
+		if let Some(sc) = sc {
+			claims.insert("sc".to_owned(), sc.into());
+		}
+		if let Some(tk) = tk {
+			claims.insert("tk".to_owned(), tk.into());
+		}
+		if let Some(nonce) = nonce {
+			claims.insert("nonce".to_owned(), nonce.into());
+		}
+		claims.insert("id".to_owned(), subject.into());
+		// Sign the token
+		let (key, head) = super::jwt::signer(&kind, &code)?;
+		Ok(jsonwebtoken::encode(&head, &claims, &key)?)
+	}
+	/// Replace the signing secret of a scope.
+	///
+	/// Tokens signed with the previous secret can no longer be verified. If
+	/// `invalidate_existing_tokens` is specified, then the nonce of the scope is
+	/// also incremented. Tokens signed with the secret of a scope, whether issued with
+	/// [`Transaction::issue_jwt`] or by signing in to the scope, carry the nonce of the
+	/// scope, and any tokens issued with a previous nonce are rejected by
+	/// [`Transaction::decode_jwt`] and by token authentication, even if they were
+	/// signed with the new secret. Tokens signed with the token definitions of the
+	/// scope do not carry a nonce, so they are not affected.
+	pub async fn rotate_scope_secret(
+		&mut self,
+		ns: &str,
+		db: &str,
+		sc: &str,
+		new_secret: &str,
+		invalidate_existing_tokens: bool,
+	) -> Result<(), Error> {
+		let mut val = self.get_sc(ns, db, sc).await?;
+		val.code = new_secret.to_owned();
+		if invalidate_existing_tokens {
+			val.nonce += 1;
+		}
+		self.set(crate::key::sc::new(ns, db, sc), val).await?;
+		self.cache.del(&crate::key::sc::prefix(ns, db));
+		Ok(())
+	}
+	/// Remove an index from a table, if no live queries or table views reference it.
+	///
+	/// A live query or table view references the index if its definition mentions the
+	/// index name. If any references exist, the index is left in place, and this
+	/// function returns an [`Error::IndexInUse`] error listing the live query ids and
+	/// view table names which reference it.
+	pub async fn drop_index_safe(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		ix: &str,
+	) -> Result<(), Error> {
+		// Check if a statement mentions the index
+		fn mentions(sql: &str, ix: &str) -> bool {
+			sql.split(|c: char| !(c.is_alphanumeric() || c == '_')).any(|w| w == ix)
+		}
+		// Check the index exists
+		let key = crate::key::ix::new(ns, db, tb, ix);
+		if !self.exi(key.clone()).await? {
+			return Err(Error::IxNotFound);
+		}
+		// Find any references to the index
+		let mut referenced_by = vec![];
+		for lv in self.all_lv(ns, db, tb).await?.iter() {
+			if mentions(&lv.to_string(), ix) {
+				referenced_by.push(lv.id.to_raw());
+			}
+		}
+		for ft in self.all_ft(ns, db, tb).await?.iter() {
+			if mentions(&ft.to_string(), ix) {
+				referenced_by.push(ft.name.to_string());
+			}
+		}
+		if !referenced_by.is_empty() {
+			return Err(Error::IndexInUse {
+				referenced_by,
+			});
+		}
+		// Delete the definition
+		self.del(key).await?;
+		// Remove the index data
+		let beg = crate::key::index::prefix(ns, db, tb, ix);
+		let end = crate::key::index::suffix(ns, db, tb, ix);
+		self.delr(beg..end, u32::MAX).await?;
+		Ok(())
+	}
+	/// Render the field definitions of a table as an OpenAPI component schema.
+	///
+	/// Nested field paths are rendered as nested object properties, and
+	/// fields which are asserted to be set are listed as required. Tables
+	/// which are defined as SCHEMAFULL do not allow additional properties.
+	pub async fn get_tb_openapi_schema(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+	) -> Result<serde_json::Value, Error> {
+		// Convert a field type to a JSON Schema
+		fn kind(v: &Option<Kind>) -> serde_json::Map<String, serde_json::Value> {
+			let (typ, fmt) = match v {
+				Some(Kind::Any) | None => return serde_json::Map::new(),
+				Some(Kind::Array) => ("array", None),
+				Some(Kind::Bool) => ("boolean", None),
+				Some(Kind::Datetime) => ("string", Some("date-time")),
+				Some(Kind::Decimal) => ("number", None),
+				Some(Kind::Duration) => ("string", Some("duration")),
+				Some(Kind::Float) => ("number", Some("double")),
+				Some(Kind::Int) => ("integer", Some("int64")),
+				Some(Kind::Number) => ("number", None),
+				Some(Kind::Object) => ("object", None),
+				Some(Kind::String) => ("string", None),
+				Some(Kind::Record(_)) => ("string", Some("record")),
+				Some(Kind::Geometry(_)) => ("object", Some("geometry")),
+			};
+			let mut out = serde_json::Map::new();
+			out.insert("type".into(), typ.into());
+			if let Some(fmt) = fmt {
+				out.insert("format".into(), fmt.into());
+			}
+			out
+		}
+		// Fetch the table definition
+		let tbv = self.get_tb(ns, db, tb).await?;
+		// Fetch the field definitions
+		let fds = self.all_fd(ns, db, tb).await?;
+		// Create the root schema
+		let mut root = serde_json::json!({
+			"title": tb,
+			"type": "object",
+			"properties": {
+				"id": { "type": "string", "format": "record", "readOnly": true },
+			},
+			"required": ["id"],
+		});
+		if tbv.full {
+			root["additionalProperties"] = false.into();
+		}
+		// Add each field to the schema
+		for fd in fds.iter() {
+			// Walk to the parent schema of this field
+			let mut obj = &mut root;
+			for (i, p) in fd.name.iter().enumerate() {
+				let last = i + 1 == fd.name.len();
+				obj = match p {
+					Part::All => {
+						obj["type"] = "array".into();
+						let items = &mut obj["items"];
+						if items.is_null() {
+							*items = serde_json::json!({});
+						}
+						items
+					}
+					Part::Field(f) => {
+						if !matches!(obj.get("properties"), Some(serde_json::Value::Object(_))) {
+							obj["type"] = "object".into();
+							obj["properties"] = serde_json::json!({});
+						}
+						if last && fd.is_required() {
+							match obj["required"].as_array_mut() {
+								Some(v) => v.push(f.to_string().into()),
+								None => obj["required"] = serde_json::json!([f.to_string()]),
+							}
+						}
+						let prop = &mut obj["properties"][f.to_string()];
+						if prop.is_null() {
+							*prop = serde_json::json!({});
+						}
+						prop
+					}
+					_ => break,
+				};
+			}
+			// Apply the field type to the schema
+			if let Some(obj) = obj.as_object_mut() {
+				obj.extend(kind(&fd.kind));
+			}
+		}
+		Ok(root)
+	}
+	/// Render the field definitions of a table as a GraphQL type definition.
+	///
+	/// Only top-level fields are rendered. Array element types are taken from
+	/// any `field.*` definition, and untyped, object, and geometry fields are
+	/// rendered using a custom `JSON` scalar, which the adapter must declare.
+	pub async fn get_tb_graphql_type(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+	) -> Result<String, Error> {
+		// Convert a table name to a GraphQL type name
+		fn name(v: &str) -> String {
+			v.split(|c: char| !c.is_alphanumeric())
+				.filter(|v| !v.is_empty())
+				.map(|v| {
+					let mut c = v.chars();
+					match c.next() {
+						Some(f) => f.to_uppercase().chain(c).collect::<String>(),
+						None => String::new(),
+					}
+				})
+				.collect()
+		}
+		// Convert a field type to a GraphQL type
+		fn kind(v: &Option<Kind>) -> String {
+			match v {
+				Some(Kind::Bool) => "Boolean".to_owned(),
+				Some(Kind::Int) => "Int".to_owned(),
+				Some(Kind::Float) | Some(Kind::Decimal) | Some(Kind::Number) => "Float".to_owned(),
+				Some(Kind::String) | Some(Kind::Datetime) | Some(Kind::Duration) => {
+					"String".to_owned()
+				}
+				Some(Kind::Record(v)) if v.len() == 1 => name(&v[0]),
+				Some(Kind::Record(_)) => "ID".to_owned(),
+				_ => "JSON".to_owned(),
+			}
+		}
+		// Fetch the field definitions
+		let fds = self.all_fd(ns, db, tb).await?;
+		// Output the type definition
+		let mut out = format!("type {} {{\n\tid: ID!\n", name(tb));
+		for fd in fds.iter() {
+			// Only output top-level fields
+			let f = match &fd.name[..] {
+				[Part::Field(f)] => f,
+				_ => continue,
+			};
+			// Get the GraphQL type of the field
+			let typ = match fd.kind {
+				Some(Kind::Array) => {
+					let all = fd.name.clone().push(Part::All);
+					let typ = fds.iter().find(|v| v.name == all).map(|v| kind(&v.kind));
+					format!("[{}]", typ.unwrap_or_else(|| "JSON".to_owned()))
+				}
+				_ => kind(&fd.kind),
+			};
+			// Mark the field as non-null if required
+			match fd.is_required() {
+				true => out.push_str(&format!("\t{}: {}!\n", f, typ)),
+				false => out.push_str(&format!("\t{}: {}\n", f, typ)),
+			}
+		}
+		out.push('}');
+		Ok(out)
+	}
+	/// Subscribe to the changes made to the records in a table.
+	///
+	/// A live query is registered on the table, and a message is sent on the
+	/// returned channel whenever a record in the table is created, updated, or
+	/// deleted by a transaction on this datastore, once that transaction has been
+	/// committed. An optional `WHERE` condition restricts the matching records.
+	pub async fn subscribe_to_table(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		filter: Option<&str>,
+	) -> Result<(uuid::Uuid, Receiver<LiveMessage>), Error> {
+		// Parse the live query
+		let mut txt = format!("LIVE SELECT * FROM {}", sql::Table::from(tb));
+		if let Some(v) = filter {
+			txt.push_str(&format!(" WHERE {}", v));
+		}
+		let mut lv = match sql::parse(&txt)?.0 .0.as_slice() {
+			[sql::Statement::Live(v)] => v.clone(),
+			_ => {
+				return Err(Error::InvalidQuery {
+					line: 0,
+					char: 0,
+					sql: txt,
+				})
+			}
+		};
+		lv.id = sql::Uuid(uuid::Uuid::new_v4());
+		// Check the database quota
+		self.enforce_live_quota(ns, db).await?;
+		// Insert the live query
+		let key = crate::key::lq::new(ns, db, &lv.id);
+		self.putc(key, tb, None).await?;
+		// Insert the table live query
+		let key = crate::key::lv::new(ns, db, tb, &lv.id);
+		self.putc(key, lv.clone(), None).await?;
+		// Register the subscription
+		let (snd, rcv) = channel::unbounded();
+		self.live.insert(lv.id.0, snd);
+		Ok((lv.id.0, rcv))
+	}
+	/// Unsubscribe from the changes made to the records in a table.
+	///
+	/// This is the counterpart to `KILL` for a subscription opened with
+	/// [`Transaction::subscribe_to_table`]. The live query is removed from the
+	/// table, and the channel of the subscription is closed.
+	pub async fn unsubscribe_from_table(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		live_id: uuid::Uuid,
+	) -> Result<(), Error> {
+		let id = sql::Uuid(live_id);
+		// Check the live query belongs to the table
+		let key = crate::key::lq::new(ns, db, &id);
+		match self.get(key).await? {
+			Some(v) if v.as_slice() == tb.as_bytes() => (),
+			_ => {
+				return Err(Error::KillStatement {
+					value: live_id.to_string(),
+				})
+			}
+		}
+		// Delete the live query
+		let key = crate::key::lq::new(ns, db, &id);
+		self.del(key).await?;
+		// Delete the table live query
+		let key = crate::key::lv::new(ns, db, tb, &id);
+		self.del(key).await?;
+		// Clear the cached table live queries
+		self.cache.del(&crate::key::lv::prefix(ns, db, tb));
+		// Close the subscription
+		self.live.remove(&live_id);
+		Ok(())
+	}
+	/// Pause a live query on a table.
+	///
+	/// While a live query is paused, no messages are sent for the changes made to
+	/// the records in the table. The version of the table is recorded, so that the
+	/// missed changes can be replayed when the live query is resumed.
+	pub async fn pause_live_query(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		live_id: uuid::Uuid,
+	) -> Result<(), Error> {
+		let key = crate::key::lv::new(ns, db, tb, &sql::Uuid(live_id));
+		let mut lv: LiveStatement = self.get(key.clone()).await?.ok_or(Error::LvNotFound)?.into();
+		// Ignore a live query which is already paused
+		if !lv.paused {
+			lv.paused = true;
+			lv.paused_at = self.get_tb_version(ns, db, tb).await?;
+			self.set(key, lv).await?;
+			self.cache.del(&crate::key::lv::prefix(ns, db, tb));
+		}
+		Ok(())
+	}
+	/// Resume a paused live query on a table.
+	///
+	/// When `replay` is enabled, a message is queued for each change made to the
+	/// records in the table while the live query was paused, using the change feed
+	/// of the database. Replayed changes to a record are sent as updates, as the
+	/// change feed does not record whether the record was created, and replayed
+	/// deletions are sent regardless of the live query condition, as the deleted
+	/// record is no longer available. Changes are only replayed for tables which are
+	/// defined with a change feed. Returns the number of replayed messages.
+	pub async fn resume_live_query(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		live_id: uuid::Uuid,
+		replay: bool,
+	) -> Result<u64, Error> {
+		let key = crate::key::lv::new(ns, db, tb, &sql::Uuid(live_id));
+		let mut lv: LiveStatement = self.get(key.clone()).await?.ok_or(Error::LvNotFound)?.into();
+		// Ignore a live query which is not paused
+		if !lv.paused {
+			return Ok(0);
+		}
+		let since = lv.paused_at;
+		lv.paused = false;
+		lv.paused_at = 0;
+		self.set(key, lv.clone()).await?;
+		self.cache.del(&crate::key::lv::prefix(ns, db, tb));
+		// Check if the changes should be replayed
+		if !replay || !self.is_subscribed(&live_id) {
+			return Ok(0);
+		}
+		let res = self.live_changes(ns, db, tb, &lv, since.saturating_add(1)).await?;
+		let num = res.len() as u64;
+		self.notifications.extend(res);
+		Ok(num)
+	}
+	/// Retrieve the changes which a live query would have matched since a point in time.
+	///
+	/// The change feed of the database is replayed from `since`, and the `WHERE`
+	/// condition of the live query is checked against each change to the records
+	/// in the table, so that a client which reconnects can receive the messages it
+	/// missed. As with [`Transaction::resume_live_query`], changes to a record are
+	/// returned as updates, and deletions are returned regardless of the condition.
+	/// Changes are only available for tables which are defined with a change feed.
+	pub async fn get_live_query_diff_since(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		live_id: uuid::Uuid,
+		since: DateTime<Utc>,
+	) -> Result<Vec<LiveMessage>, Error> {
+		let key = crate::key::lv::new(ns, db, tb, &sql::Uuid(live_id));
+		let lv: LiveStatement = self.get(key).await?.ok_or(Error::LvNotFound)?.into();
+		let since = since.timestamp_micros().max(0) as u64;
+		self.live_changes(ns, db, tb, &lv, since).await
+	}
+	// Replay the change feed of a table for a live query
+	async fn live_changes(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		lv: &LiveStatement,
+		since: u64,
+	) -> Result<Vec<LiveMessage>, Error> {
+		let mut out = vec![];
+		let mut rng = crate::key::cf::lsn(ns, db, since)..crate::key::cf::suffix(ns, db);
+		loop {
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Parse the key-value
+				let k: crate::key::cf::Cf = (&k).into();
+				if k.tb != tb {
+					continue;
+				}
+				let v: Value = (&v).into();
+				// Check the live query condition
+				let (act, res) = match v {
+					Value::None => (LiveAction::Delete, Value::Thing(Thing::from((k.tb, k.id)))),
+					v => {
+						if let Some(cond) = &lv.cond {
+							if !live_condition(cond, &v)?.is_truthy() {
+								continue;
+							}
+						}
+						(LiveAction::Update, v)
+					}
+				};
+				// Add the live query message
+				out.push(LiveMessage {
+					id: lv.id.0,
+					action: act,
+					result: res,
+				});
+			}
+		}
+		Ok(out)
+	}
+	/// Check if a live query has a subscription on this datastore.
+	pub fn is_subscribed(&self, id: &uuid::Uuid) -> bool {
+		self.live.contains(id)
+	}
+	/// Queue a message for a table subscription, to be sent once this transaction is committed.
+	pub fn notify(&mut self, id: uuid::Uuid, action: LiveAction, result: Value) {
+		self.notifications.push(LiveMessage {
+			id,
+			action,
+			result,
+		});
+	}
+	/// Queue a change to a record for the live queries on its table.
+	///
+	/// The `WHERE` condition of each live query with a subscription on this datastore
+	/// is checked against the record, and a message is queued for each matching live
+	/// query, to be sent once this transaction is committed. For a deletion, the value
+	/// should be the record before it was deleted. The conditions are evaluated without
+	/// the query layer, so only fields, values, and operators can be used within them.
+	/// Returns the number of live queries which were notified.
+	#[allow(clippy::too_many_arguments)]
+	pub async fn broadcast_live_event(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		thing: &Thing,
+		op: LiveAction,
+		value: &Value,
+	) -> Result<u64, Error> {
+		let mut num = 0;
+		for lv in self.all_lv(ns, db, tb).await?.iter() {
+			// Skip paused live queries
+			if lv.paused {
+				continue;
+			}
+			// Skip live queries without a subscription here
+			if !self.is_subscribed(&lv.id) {
+				continue;
+			}
+			// Check the live query condition
+			if let Some(cond) = &lv.cond {
+				if !live_condition(cond, value)?.is_truthy() {
+					continue;
+				}
+			}
+			// Queue the live query message
+			let res = match op {
+				LiveAction::Delete => Value::Thing(thing.clone()),
+				_ => value.clone(),
+			};
+			self.notify(lv.id.0, op, res);
+			num += 1;
+		}
+		Ok(num)
+	}
+	/// Retrieve the modification version of a specific table.
+	///
+	/// The version is the Unix timestamp in microseconds at which the latest transaction
+	/// which stored or deleted a record in the table was committed. Versions are given
+	/// to transactions as they are committed, so a transaction committed through this
+	/// datastore after another always has a later version. When several nodes share a
+	/// distributed datastore, versions are only ordered between the transactions of each
+	/// node, and rely on the clocks of the nodes between them. Changes made within this
+	/// transaction are not included until it is committed. Tables without records return 0.
+	pub async fn get_tb_version(&mut self, ns: &str, db: &str, tb: &str) -> Result<u64, Error> {
+		let beg = crate::key::vs::prefix(ns, db, tb);
+		let end = crate::key::vs::suffix(ns, db, tb);
+		match self.scan_rev(beg..end, 1).await?.pop() {
+			Some((k, _)) => Ok(crate::key::vs::Vs::from(&k).version),
+			None => Ok(0),
+		}
+	}
+	/// Retrieve the number of times the records of a specific table have been accessed.
+	///
+	/// Each call to [`Transaction::get_record`] and [`Transaction::scan_records`] counts
+	/// as one access. Accesses are counted in memory on each datastore, and are stored
+	/// in batches, so accesses made on other nodes are only included once they have
+	/// been stored. Tables which have not been accessed since the count was last reset
+	/// return 0.
+	pub async fn get_table_access_count(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+	) -> Result<u64, Error> {
+		Ok(self.get_table_access(ns, db, tb).await?.map_or(0, |(num, _)| num))
+	}
+	/// Reset the access count of a specific table.
+	pub async fn reset_table_access_count(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+	) -> Result<(), Error> {
+		let key = crate::key::ac::new(ns, db, tb);
+		self.stats.reset(&key.clone().into());
+		self.del(key).await
+	}
+	/// Retrieve the tables in a database which have not been accessed within a period.
+	///
+	/// Tables which have not been accessed since their access count was last reset
+	/// are also returned, as no access has been recorded for them.
+	pub async fn scan_cold_tables(
+		&mut self,
+		ns: &str,
+		db: &str,
+		idle_threshold: Duration,
+	) -> Result<Vec<String>, Error> {
+		let now = Utc::now().timestamp_micros() as u64;
+		let idle = idle_threshold.as_micros() as u64;
+		let mut out = vec![];
+		for tb in self.all_tb(ns, db).await?.iter() {
+			let cold = match self.get_table_access(ns, db, &tb.name).await? {
+				Some((_, at)) => now.saturating_sub(at) > idle,
+				None => true,
+			};
+			if cold {
+				out.push(tb.name.to_string());
+			}
+		}
+		Ok(out)
+	}
+	// Fetch the access count of a table, along with the Unix timestamp in microseconds of the last access
+	async fn get_table_access(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+	) -> Result<Option<(u64, u64)>, Error> {
+		let key: Key = crate::key::ac::new(ns, db, tb).into();
+		let pending = self.stats.get(&key);
+		let stored = self.get(key).await?.map(|v| decode_table_access(&v));
+		Ok(match (stored, pending) {
+			(Some((n, a)), Some((m, b))) => Some((n + m, a.max(b))),
+			(v, None) | (None, v) => v,
+		})
+	}
+	// Count an access to the records of a specific table
+	fn count_table_access(&mut self, ns: &str, db: &str, tb: &str) {
+		let now = Utc::now().timestamp_micros() as u64;
+		let key = crate::key::ac::new(ns, db, tb);
+		self.stats.count(key.into(), now);
+	}
+	// Add the table accesses counted on a datastore to the stored access counts
+	pub(super) async fn store_table_access(
+		&mut self,
+		tables: &HashMap<Key, (u64, u64)>,
+	) -> Result<(), Error> {
+		for (key, (num, at)) in tables.iter() {
+			let (n, a) = self.get(key.clone()).await?.map_or((0, 0), |v| decode_table_access(&v));
+			let val = [(n + num).to_be_bytes(), a.max(*at).to_be_bytes()].concat();
+			self.set(key.clone(), val).await?;
+		}
+		Ok(())
+	}
+	/// Update the modification version of a specific table.
+	///
+	/// The version of the table is recorded when this transaction is committed,
+	/// and is the version of the transaction, so it is not returned by
+	/// [`Transaction::get_tb_version`] until then. Each transaction records its own
+	/// version key for the table, rather than updating a shared counter, so that
+	/// concurrent writers to the same table do not conflict. Superseded version keys
+	/// are removed by the datastore, once enough have been recorded.
+	pub async fn bump_tb_version(&mut self, ns: &str, db: &str, tb: &str) -> Result<(), Error> {
+		self.check_readonly()?;
+		self.changes.push(Change::Record {
+			ns: ns.to_owned(),
+			db: db.to_owned(),
+			tb: tb.to_owned(),
+			feed: None,
+		});
+		Ok(())
+	}
+	// Remove the version keys of a table which have been superseded by its latest version
+	async fn remove_superseded_versions(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+	) -> Result<(), Error> {
+		let beg = crate::key::vs::prefix(ns, db, tb);
+		let end = crate::key::vs::suffix(ns, db, tb);
+		if let Some((k, _)) = self.scan_rev(beg.clone()..end, 1).await?.pop() {
+			self.delr(beg..k, u32::MAX).await?;
+		}
+		Ok(())
+	}
+	// Remove the superseded version keys of the tables modified on a datastore
+	pub(super) async fn compact_tb_versions(
+		&mut self,
+		tables: &HashSet<Table>,
+	) -> Result<(), Error> {
+		for (ns, db, tb) in tables.iter() {
+			self.remove_superseded_versions(ns, db, tb).await?;
+		}
+		Ok(())
+	}
+	// Record the changes made within this transaction, at the version of the transaction
+	//
+	// The version is a Unix timestamp in microseconds, which is taken while the clock
+	// of the datastore is held, and is later than the version of every transaction
+	// which has already been committed through the datastore.
+	async fn record_changes(&mut self, version: u64) -> Result<(), Error> {
+		let mut tables = HashSet::new();
+		for c in std::mem::take(&mut self.changes) {
+			match c {
+				Change::Record {
+					ns,
+					db,
+					tb,
+					feed,
+				} => {
+					// Update the version of the table
+					if let Some((id, val)) = feed {
+						let key = crate::key::cf::new(&ns, &db, version, &tb, &id);
+						self.set(key, val).await?;
+					}
+					if tables.insert((ns.clone(), db.clone(), tb.clone())) {
+						let key = crate::key::vs::new(&ns, &db, &tb, version);
+						self.set(key, vec![]).await?;
+						self.versions.count(&ns, &db, &tb);
+					}
+				}
+				Change::Refresh {
+					ns,
+					db,
+					tb,
+					ft,
+				} => {
+					// Ignore views which were made stale after the refresh
+					if !self.stale.contains(&crate::key::table::new(&ns, &db, &tb).encode()?) {
+						let key = crate::key::vr::new(&ns, &db, &tb, &ft);
+						self.set(key, version.to_be_bytes().to_vec()).await?;
+					}
+				}
+			}
+		}
+		Ok(())
+	}
+	/// Repopulate a foreign table view by re-running its query on its source tables.
+	///
+	/// The version of each source table is recorded whenever the view is refreshed,
+	/// so views whose source tables have not been modified since the last refresh
+	/// are left untouched, and this returns 0. Otherwise the records of the view are
+	/// removed, the view query is processed for every record in its source tables,
+	/// and this returns the number of records written to the view. The view query is
+	/// processed with root authentication, and is not limited by any timeout.
+	pub async fn refresh_materialized_view(
+		&mut self,
+		ns: &str,
+		db: &str,
+		view_tb: &str,
+	) -> Result<u64, Error> {
+		// Processing the view query needs a shared transaction, so
+		// this transaction is moved into one while the source tables
+		// are reprocessed, and is then moved back again
+		let txn = Arc::new(futures::lock::Mutex::new(self.take()));
+		let ctx = Context::default();
+		let mut opt = Options::new(Auth::Kv);
+		opt.ns = Some(Arc::new(ns.to_owned()));
+		opt.db = Some(Arc::new(db.to_owned()));
+		let res = Transaction::refresh_view(&ctx, &opt, &txn, ns, db, view_tb).await;
+		// Restore this transaction
+		std::mem::swap(self, &mut *txn.lock().await);
+		res
+	}
+	// Repopulate a foreign table view within a shared transaction
+	//
+	// The records which are written while the view query is processed are not
+	// logged, and do not update table versions or mark views as stale, as they
+	// are derived from the source tables. Once the view is repopulated, the
+	// version of the view table is updated, and any views of it are marked stale.
+	pub(crate) async fn refresh_view(
+		ctx: &Context<'_>,
+		opt: &Options,
+		txn: &crate::dbs::Transaction,
+		ns: &str,
+		db: &str,
+		view_tb: &str,
+	) -> Result<u64, Error> {
+		// Remove the view records, if the view is stale
+		let tb = match txn.lock().await.clear_stale_view(ns, db, view_tb).await? {
+			Some(tb) => tb,
+			None => return Ok(0),
+		};
+		// Process the view query
+		let rewrite = std::mem::replace(&mut txn.lock().await.rewrite, true);
+		let res = tb.populate(ctx, opt, txn, None).await;
+		let mut run = txn.lock().await;
+		run.rewrite = rewrite;
+		res?;
+		// Record the refreshed source versions
+		run.set_materialized_view_refreshed(ns, db, view_tb).await?;
+		// Update the version of the view table
+		run.invalidate_dependent_views(ns, db, view_tb).await?;
+		run.bump_tb_version(ns, db, view_tb).await?;
+		// Count the view records
+		let beg = crate::key::thing::prefix(ns, db, view_tb);
+		let end = crate::key::thing::suffix(ns, db, view_tb);
+		run.count_range(beg..end).await
+	}
+	// Remove the records of a foreign table view if any of its source tables have
+	// changed since it was last refreshed, returning the view definition if so
+	async fn clear_stale_view(
+		&mut self,
+		ns: &str,
+		db: &str,
+		view_tb: &str,
+	) -> Result<Option<DefineTableStatement>, Error> {
+		// Fetch the view definition
+		let tb = self.get_tb(ns, db, view_tb).await?;
+		let view = match &tb.view {
+			Some(v) => v,
+			None => {
+				return Err(Error::TableIsNotView {
+					table: view_tb.to_owned(),
+				})
+			}
+		};
+		// Check if any source table has changed
+		let mut stale = false;
+		for ft in view.what.0.iter() {
+			let key = crate::key::vr::new(ns, db, view_tb, ft);
+			let ver = self
+				.get(key)
+				.await?
+				.map(|v| u64::from_be_bytes(v.as_slice().try_into().unwrap_or_default()));
+			if ver != Some(self.get_tb_version(ns, db, ft).await?) {
+				stale = true;
+			}
+		}
+		// Leave an up to date view untouched
+		if !stale {
+			return Ok(None);
+		}
+		// Remove the view records
+		let beg = crate::key::thing::prefix(ns, db, view_tb);
+		let end = crate::key::thing::suffix(ns, db, view_tb);
+		let (num, len) = self.measure_keys(beg.clone(), end.clone()).await?;
+		self.delr(beg..end, u32::MAX).await?;
+		// Release the database quota
+		self.release_quota_usage(ns, db, view_tb, num, len).await?;
+		Ok(Some(tb))
+	}
+	// Move this transaction out, leaving a finished transaction in its place
+	fn take(&mut self) -> Transaction {
+		let tmp = Transaction {
+			inner: None,
+			ds: self.ds.clone(),
+			cache: Cache::new(0),
+			timeout: Timeout::default(),
+			locks: lock::Locks::default().holder(),
+			active: active::Active::default().register(false),
+			live: self.live.clone(),
+			stats: self.stats.clone(),
+			audit: self.audit.clone(),
+			versions: self.versions.clone(),
+			notifications: vec![],
+			savepoint: 0,
+			savepoints: vec![],
+			undo: vec![],
+			readonly: true,
+			log: false,
+			fk: false,
+			rewrite: false,
+			changes: vec![],
+			leases: vec![],
+			writes: HashMap::new(),
+			checked: HashSet::new(),
+			stale: HashSet::new(),
+			quotas: HashMap::new(),
+		};
+		std::mem::replace(self, tmp)
+	}
+	/// Record the current version of each source table of a foreign table view.
+	pub async fn set_materialized_view_refreshed(
+		&mut self,
+		ns: &str,
+		db: &str,
+		view_tb: &str,
+	) -> Result<(), Error> {
+		if let Some(view) = self.get_tb(ns, db, view_tb).await?.view {
+			for ft in view.what.0.iter() {
+				// A source table changed within this transaction is
+				// only given its version when the transaction commits
+				let changed = self.changes.iter().any(|c| {
+					matches!(c, Change::Record { ns: n, db: d, tb: t, .. } if n == ns && d == db && t == &ft.0)
+				});
+				if changed {
+					self.changes.push(Change::Refresh {
+						ns: ns.to_owned(),
+						db: db.to_owned(),
+						tb: view_tb.to_owned(),
+						ft: ft.0.clone(),
+					});
+					continue;
+				}
+				let ver = self.get_tb_version(ns, db, ft).await?;
+				let key = crate::key::vr::new(ns, db, view_tb, ft);
+				self.set(key, ver.to_be_bytes().to_vec()).await?;
+			}
+		}
+		self.stale.remove(&crate::key::table::new(ns, db, view_tb).encode()?);
+		Ok(())
+	}
+	/// Retrieve the schema migration version of a specific database.
+	pub async fn get_migration_version(&mut self, ns: &str, db: &str) -> Result<u64, Error> {
+		let key = crate::key::mv::new(ns, db);
+		match self.get(key).await? {
+			Some(v) => Ok(u64::from_be_bytes(v.as_slice().try_into().unwrap_or_default())),
+			None => Ok(0),
+		}
+	}
+	/// Update the schema migration version of a specific database.
+	///
+	/// The new version must be greater than the current version. The version is
+	/// updated conditionally, so that a concurrent migration can not be overwritten.
+	pub async fn set_migration_version(
+		&mut self,
+		ns: &str,
+		db: &str,
+		version: u64,
+	) -> Result<(), Error> {
+		let key = crate::key::mv::new(ns, db);
+		let chk = self.get(key.clone()).await?;
+		if let Some(v) = &chk {
+			if u64::from_be_bytes(v.as_slice().try_into().unwrap_or_default()) >= version {
+				return Err(Error::MigrationVersionConflict);
+			}
+		}
+		match self.putc(key, version.to_be_bytes().to_vec(), chk).await {
+			Err(Error::TxConditionNotMet) | Err(Error::TxKeyAlreadyExists) => {
+				Err(Error::MigrationVersionConflict)
+			}
+			res => res,
+		}
+	}
+	/// Record a schema migration which has been applied to a specific database.
+	pub async fn log_migration(
+		&mut self,
+		ns: &str,
+		db: &str,
+		migration: &MigrationRecord,
+	) -> Result<(), Error> {
+		let key = crate::key::mg::new(ns, db, migration.version);
+		self.set(key, migration).await
+	}
+	/// Retrieve all schema migrations which have been applied to a specific database.
+	///
+	/// The migrations are ordered by version, from the earliest to the latest.
+	pub async fn list_migrations(
+		&mut self,
+		ns: &str,
+		db: &str,
+	) -> Result<Vec<MigrationRecord>, Error> {
+		let beg = crate::key::mg::prefix(ns, db);
+		let end = crate::key::mg::suffix(ns, db);
+		let val = self.getr(beg..end, u32::MAX).await?;
+		Ok(val.into_iter().map(|(_, v)| v.into()).collect())
+	}
+	/// Revert the migration log and version of the most recently applied migration.
+	///
+	/// The latest migration log entry is removed, and the migration version is set
+	/// back to the version of the previous migration. This does not run the down
+	/// migration script, which is run in the same transaction by
+	/// [`Datastore::rollback_migration`](super::Datastore::rollback_migration).
+	pub async fn revert_migration(&mut self, ns: &str, db: &str) -> Result<(), Error> {
+		// Check the latest migration
+		let mut log = self.list_migrations(ns, db).await?;
+		let last = log.pop().ok_or(Error::NoMigrationToRollback)?;
+		if self.get_migration_version(ns, db).await? != last.version {
+			return Err(Error::MigrationVersionConflict);
+		}
+		// Remove the latest migration log entry
+		self.del(crate::key::mg::new(ns, db, last.version)).await?;
+		// Revert the migration version
+		let key = crate::key::mv::new(ns, db);
+		match log.pop() {
+			Some(v) => self.set(key, v.version.to_be_bytes().to_vec()).await,
+			None => self.del(key).await,
+		}
+	}
+	/// Move a database, along with all of its data, into an archive namespace.
+	///
+	/// The database is copied into the archive namespace as `{db}_archived_{timestamp}`,
+	/// where it is marked as archived, and the original database is then removed.
+	pub async fn archive_database(
+		&mut self,
+		ns: &str,
+		db: &str,
+		archive_ns: &str,
+	) -> Result<(), Error> {
+		// Check the database exists
+		self.get_db(ns, db).await?;
+		// Copy the database into the archive
+		let name = format!("{}_archived_{}", db, Utc::now().timestamp());
+		self.copy_database(ns, db, archive_ns, &name).await?;
+		// Mark the copied database as archived
+		self.set(crate::key::ar::new(archive_ns, &name), vec![]).await?;
+		// Remove the original database
+		self.drop_database(ns, db).await
+	}
+	/// Copy an archived database, along with all of its data, back into a namespace.
+	///
+	/// The target database must not already exist, unless `overwrite` is specified,
+	/// in which case the existing target database is removed first. The archived
+	/// database is left in place, and the restored copy is not marked as archived.
+	pub async fn restore_archived_database(
+		&mut self,
+		archive_ns: &str,
+		archived_db: &str,
+		target_ns: &str,
+		target_db: &str,
+		overwrite: bool,
+	) -> Result<(), Error> {
+		// Check the archived database exists
+		self.get_db(archive_ns, archived_db).await?;
+		// Check the target database does not exist
+		match self.get_db(target_ns, target_db).await {
+			Ok(_) if !overwrite => {
+				return Err(Error::DbExists {
+					db: target_db.to_owned(),
+				})
+			}
+			Ok(_) => self.drop_database(target_ns, target_db).await?,
+			Err(Error::DbNotFound) => (),
+			Err(e) => return Err(e),
+		}
+		// Copy the archived database into the target
+		self.copy_database(archive_ns, archived_db, target_ns, target_db).await?;
+		// Clear the archived marker on the copy
+		self.del(crate::key::ar::new(target_ns, target_db)).await
+	}
+	/// Check whether a database has been archived.
+	pub async fn is_archived(&mut self, ns: &str, db: &str) -> Result<bool, Error> {
+		self.exi(crate::key::ar::new(ns, db)).await
+	}
+	/// Move a database, along with all of its data, into another namespace.
+	///
+	/// The database is stored as `dst_db` in the target namespace, which is defined
+	/// if it does not already exist, and the original database is then removed. This
+	/// fails if the target database already exists.
+	pub async fn migrate_db_across_namespaces(
+		&mut self,
+		src_ns: &str,
+		db: &str,
+		dst_ns: &str,
+		dst_db: &str,
+	) -> Result<(), Error> {
+		// Check the database exists
+		self.get_db(src_ns, db).await?;
+		// Check the target database does not exist
+		match self.get_db(dst_ns, dst_db).await {
+			Ok(_) => {
+				return Err(Error::DbExists {
+					db: dst_db.to_owned(),
+				})
+			}
+			Err(Error::DbNotFound) => (),
+			Err(e) => return Err(e),
+		}
+		// Move the database into the target
+		self.copy_database(src_ns, db, dst_ns, dst_db).await?;
+		self.drop_database(src_ns, db).await
+	}
+	/// Move all of the databases of a namespace into another namespace.
+	///
+	/// Each database is moved with [`Transaction::migrate_db_across_namespaces`], and
+	/// the conflict policy decides what happens to a database which already exists
+	/// in the target namespace. Once every database has been moved, the source namespace
+	/// is removed, along with its logins and tokens. If any databases were skipped, then
+	/// the source namespace is kept, along with the skipped databases. Merging a namespace
+	/// into itself leaves it unchanged.
+	pub async fn merge_namespaces(
+		&mut self,
+		src_ns: &str,
+		dst_ns: &str,
+		conflict: ConflictPolicy,
+	) -> Result<MergeReport, Error> {
+		let mut out = MergeReport::default();
+		// Check the namespace exists
+		self.get_ns(src_ns).await?;
+		if src_ns == dst_ns {
+			return Ok(out);
+		}
+		// Move each of the databases
+		for db in self.all_db(src_ns).await?.iter() {
+			let name = db.name.0.clone();
+			let exists = match self.get_db(dst_ns, &name).await {
+				Ok(_) => true,
+				Err(Error::DbNotFound) => false,
+				Err(e) => return Err(e),
+			};
+			match (&conflict, exists) {
+				(_, false) => {
+					self.migrate_db_across_namespaces(src_ns, &name, dst_ns, &name).await?;
+					out.moved.push(name);
+				}
+				(ConflictPolicy::Skip, true) => {
+					out.skipped.push(name);
+				}
+				(ConflictPolicy::Overwrite, true) => {
+					self.drop_database(dst_ns, &name).await?;
+					self.migrate_db_across_namespaces(src_ns, &name, dst_ns, &name).await?;
+					out.moved.push(name);
+				}
+				(ConflictPolicy::Rename(suffix), true) => {
+					let rename = format!("{}{}", name, suffix);
+					self.migrate_db_across_namespaces(src_ns, &name, dst_ns, &rename).await?;
+					out.renamed.push((name, rename));
+				}
+			}
+		}
+		// Remove the source namespace, unless it still has skipped databases
+		if out.skipped.is_empty() {
+			self.del(crate::key::ns::new(src_ns)).await?;
+			self.delp(crate::key::namespace::new(src_ns), u32::MAX).await?;
+		}
+		self.invalidate_ns_cache(src_ns);
+		self.invalidate_ns_cache(dst_ns);
+		Ok(out)
+	}
+	/// Retrieve the access log entries of a specific database within a time range.
+	///
+	/// The entries are ordered by the time at which each key was accessed, and include
+	/// the entries recorded on this node which have not yet been stored. Entries recorded
+	/// on other nodes are only included once they have been stored.
+	pub async fn get_access_log(
+		&mut self,
+		ns: &str,
+		db: &str,
+		from: DateTime<Utc>,
+		to: DateTime<Utc>,
+	) -> Result<Vec<AccessLogEntry>, Error> {
+		let mut out: Vec<AccessLogEntry> = vec![];
+		if from > to {
+			return Ok(out);
+		}
+		let from = from.timestamp_micros().max(0) as u64;
+		let to = to.timestamp_micros().max(0) as u64 + 1;
+		for op in ["get", "set", "del"] {
+			let beg: Key = crate::key::al::new(ns, db, op, from, "", 0).into();
+			let end: Key = crate::key::al::new(ns, db, op, to, "", 0).into();
+			let val = self.getr(beg..end, u32::MAX).await?;
+			out.extend(val.into_iter().map(|(_, v)| AccessLogEntry::from(v)));
+		}
+		out.extend(self.audit.get(ns, db, from, to));
+		out.sort_by_key(|v| v.at);
+		Ok(out)
+	}
+	/// Remove the access log entries of a specific database from before a point in time.
+	///
+	/// This removes both the stored entries, and the entries recorded on this node
+	/// which have not yet been stored.
+	pub async fn purge_access_log(
+		&mut self,
+		ns: &str,
+		db: &str,
+		before: DateTime<Utc>,
+	) -> Result<(), Error> {
+		let before = before.timestamp_micros().max(0) as u64;
+		for op in ["get", "set", "del"] {
+			let beg: Key = crate::key::al::prefix(ns, db, op);
+			let end: Key = crate::key::al::new(ns, db, op, before, "", 0).into();
+			self.delr(beg..end, u32::MAX).await?;
+		}
+		self.audit.purge(ns, db, before);
+		Ok(())
+	}
+	/// Store an authenticated session of a specific database.
+	pub async fn store_session(
+		&mut self,
+		ns: &str,
+		db: &str,
+		session: &SessionRecord,
+	) -> Result<(), Error> {
+		let key = crate::key::se::new(ns, db, &session.id);
+		self.set(key, session).await
+	}
+	/// Retrieve the sessions which have not expired.
+	///
+	/// If no database is specified, then the sessions of all databases in the
+	/// namespace are returned.
+	pub async fn scan_active_sessions(
+		&mut self,
+		ns: &str,
+		db: Option<&str>,
+	) -> Result<Vec<SessionRecord>, Error> {
+		let dbs = match db {
+			Some(db) => vec![db.to_owned()],
+			None => self.all_db(ns).await?.iter().map(|v| v.name.to_string()).collect(),
+		};
+		let now = Utc::now();
+		let mut out = vec![];
+		for db in dbs {
+			let beg = crate::key::se::prefix(ns, &db);
+			let end = crate::key::se::suffix(ns, &db);
+			let val = self.getr(beg..end, u32::MAX).await?;
+			out.extend(
+				val.into_iter().map(|(_, v)| SessionRecord::from(v)).filter(|v| v.expires_at > now),
+			);
+		}
+		Ok(out)
+	}
+	/// Count the sessions which have not expired.
+	///
+	/// If no database is specified, then the sessions of all databases in the
+	/// namespace are counted.
+	pub async fn count_active_sessions(
+		&mut self,
+		ns: &str,
+		db: Option<&str>,
+	) -> Result<u64, Error> {
+		Ok(self.scan_active_sessions(ns, db).await?.len() as u64)
+	}
+	/// Remove a session from a specific database, so that it is no longer active.
+	pub async fn invalidate_session(
+		&mut self,
+		ns: &str,
+		db: &str,
+		session_id: &str,
+	) -> Result<(), Error> {
+		self.del(crate::key::se::new(ns, db, session_id)).await
+	}
+	/// Remove the sessions of a specific database which have expired.
+	///
+	/// A session has expired once it is no longer active, including when it
+	/// expires at exactly the current time. Returns the number of sessions
+	/// which were removed.
+	pub async fn purge_expired_sessions(&mut self, ns: &str, db: &str) -> Result<u64, Error> {
+		let beg = crate::key::se::prefix(ns, db);
+		let end = crate::key::se::suffix(ns, db);
+		let now = Utc::now();
+		let mut num = 0;
+		for (k, v) in self.getr(beg..end, u32::MAX).await? {
+			if SessionRecord::from(v).expires_at <= now {
+				self.del(k).await?;
+				num += 1;
+			}
+		}
+		Ok(num)
+	}
+	// Copy the definition and all of the data of a database
+	async fn copy_database(
+		&mut self,
+		ns: &str,
+		db: &str,
+		to_ns: &str,
+		to_db: &str,
+	) -> Result<(), Error> {
+		// Define the target namespace and database
+		self.add_ns(to_ns, false).await?;
+		let key = crate::key::db::new(to_ns, to_db);
+		let val = DefineDatabaseStatement {
+			name: to_db.to_owned().into(),
+		};
+		self.put(key, &val).await?;
+		self.cache.del(&crate::key::db::prefix(to_ns));
+		// Copy all keys under the database prefix
+		let src = crate::key::database::new(ns, db).encode()?;
+		let dst = crate::key::database::new(to_ns, to_db).encode()?;
+		let mut rng = src.clone()..src.clone().add(0xff);
+		loop {
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Rewrite the key prefix
+				let key = [dst.as_slice(), &k[src.len()..]].concat();
+				self.set(key, v).await?;
+			}
+		}
+		Ok(())
+	}
+	// Remove the definition and all of the data of a database
+	async fn drop_database(&mut self, ns: &str, db: &str) -> Result<(), Error> {
+		self.del(crate::key::db::new(ns, db)).await?;
+		self.delp(crate::key::database::new(ns, db), u32::MAX).await?;
+		self.cache.del(&crate::key::db::prefix(ns));
+		self.cache.del(&crate::key::db::new(ns, db).encode()?);
+		Ok(())
+	}
+	/// Retrieve the names of the tables which do not have any indexes defined.
+	pub async fn scan_tables_without_indexes(
+		&mut self,
+		ns: &str,
+		db: &str,
+	) -> Result<Vec<String>, Error> {
+		let tbs = self.all_tb(ns, db).await?;
+		let mut out = vec![];
+		for tb in tbs.iter() {
+			if self.all_ix(ns, db, &tb.name).await?.is_empty() {
+				out.push(tb.name.to_string());
+			}
+		}
+		Ok(out)
+	}
+	/// Retrieve the names of the tables which do not have any permissions defined.
+	///
+	/// Tables without permissions allow full access to any authenticated user.
+	pub async fn scan_tables_without_permissions(
+		&mut self,
+		ns: &str,
+		db: &str,
+	) -> Result<Vec<String>, Error> {
+		let tbs = self.all_tb(ns, db).await?;
+		Ok(tbs.iter().filter(|v| v.permissions.is_full()).map(|v| v.name.to_string()).collect())
+	}
+	/// Retrieve the names of the tables which have been modified since a version.
+	///
+	/// This only reads the version key of each table, so it is O(n) in the
+	/// number of tables, rather than in the number of records.
+	pub async fn scan_tables_modified_since(
+		&mut self,
+		ns: &str,
+		db: &str,
+		since_version: u64,
+	) -> Result<Vec<String>, Error> {
+		let tbs = self.all_tb(ns, db).await?;
+		let mut out = vec![];
+		for tb in tbs.iter() {
+			if self.get_tb_version(ns, db, &tb.name).await? > since_version {
+				out.push(tb.name.to_string());
+			}
+		}
+		Ok(out)
+	}
+	/// Writes the records changed since a change feed position as binary SQL.
+	///
+	/// Every stored or deleted record in a table which is defined with a change feed
+	/// is logged in the database change feed, keyed by the version at which the
+	/// transaction which made the change was committed. This exports each change
+	/// after `since_lsn`, in the order in which the changes were committed, so that
+	/// the output can be imported with [`Transaction::import`] on top of an export
+	/// taken at that position. The position of the last exported change is returned,
+	/// or `since_lsn` if no changes have been made since then.
+	///
+	/// Versions are given to transactions as they are committed through a datastore,
+	/// so changes committed after an export was taken always have later positions.
+	/// When several nodes share a distributed datastore, versions are only ordered
+	/// between the transactions of each node, so changes committed on other nodes at
+	/// around the same time may have earlier positions, and are then not exported.
+	pub async fn export_partial(
+		&mut self,
+		ns: &str,
+		db: &str,
+		since_lsn: u64,
+		chn: Sender<Vec<u8>>,
+	) -> Result<u64, Error> {
+		// Output OPTIONS
+		{
+			chn.send(bytes!("-- ------------------------------")).await?;
+			chn.send(bytes!("-- OPTION")).await?;
+			chn.send(bytes!("-- ------------------------------")).await?;
+			chn.send(bytes!("")).await?;
+			chn.send(bytes!("OPTION IMPORT;")).await?;
+			chn.send(bytes!("")).await?;
+		}
+		// Start transaction
+		chn.send(bytes!("-- ------------------------------")).await?;
+		chn.send(bytes!("-- TRANSACTION")).await?;
+		chn.send(bytes!("-- ------------------------------")).await?;
+		chn.send(bytes!("")).await?;
+		chn.send(bytes!("BEGIN TRANSACTION;")).await?;
+		chn.send(bytes!("")).await?;
+		// Output CHANGES
+		chn.send(bytes!("-- ------------------------------")).await?;
+		chn.send(bytes!(format!("-- CHANGES SINCE: {}", since_lsn))).await?;
+		chn.send(bytes!("-- ------------------------------")).await?;
+		chn.send(bytes!("")).await?;
+		// Fetch changes
+		let beg = crate::key::cf::lsn(ns, db, since_lsn.saturating_add(1));
+		let end = crate::key::cf::suffix(ns, db);
+		let mut rng = beg..end;
+		let mut lsn = since_lsn;
+		loop {
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Parse the key-value
+				let k: crate::key::cf::Cf = (&k).into();
+				let v: Value = (&v).into();
+				let t = Thing::from((k.tb, k.id));
+				// Write change
+				match v {
+					Value::None => chn.send(bytes!(format!("DELETE {};", t))).await?,
+					v => chn.send(bytes!(format!("UPDATE {} CONTENT {};", t, v))).await?,
+				}
+				// Mark the position
+				lsn = k.lsn;
+			}
+		}
+		chn.send(bytes!("")).await?;
+		// Commit transaction
+		chn.send(bytes!("-- ------------------------------")).await?;
+		chn.send(bytes!("-- TRANSACTION")).await?;
+		chn.send(bytes!("-- ------------------------------")).await?;
+		chn.send(bytes!("")).await?;
+		chn.send(bytes!("COMMIT TRANSACTION;")).await?;
+		chn.send(bytes!("")).await?;
+		// Everything exported
+		Ok(lsn)
+	}
+	/// Writes the records changed within a period of time as binary SQL.
+	///
+	/// This relies on the database change feed, in which each change to a record is
+	/// keyed by the version of the transaction which made it, and versions are the Unix
+	/// timestamps in microseconds at which the transactions were committed. Records which
+	/// are stored with [`Transaction::set_record`], or deleted with [`Transaction::del_record`]
+	/// or [`Transaction::truncate_table`], in a table which is defined with a change feed
+	/// are logged in the change feed. Each record changed by a transaction committed at
+	/// or after `since` is exported once, either as an `UPDATE` statement with its latest
+	/// content, or as a `DELETE` statement if it was deleted, in the order in which the
+	/// changes were committed.
+	///
+	/// As with [`Transaction::export_partial`], versions are only ordered between the
+	/// transactions committed through the same datastore, so when several nodes share
+	/// a distributed datastore, `since` should allow for the clocks of the nodes.
+	pub async fn export_since(
+		&mut self,
+		ns: &str,
+		db: &str,
+		since: Timestamp,
+		chn: Sender<Vec<u8>>,
+	) -> Result<(), Error> {
+		// Output OPTIONS
+		{
+			chn.send(bytes!("-- ------------------------------")).await?;
+			chn.send(bytes!("-- OPTION")).await?;
+			chn.send(bytes!("-- ------------------------------")).await?;
+			chn.send(bytes!("")).await?;
+			chn.send(bytes!("OPTION IMPORT;")).await?;
+			chn.send(bytes!("")).await?;
+		}
+		// Start transaction
+		chn.send(bytes!("-- ------------------------------")).await?;
+		chn.send(bytes!("-- TRANSACTION")).await?;
+		chn.send(bytes!("-- ------------------------------")).await?;
+		chn.send(bytes!("")).await?;
+		chn.send(bytes!("BEGIN TRANSACTION;")).await?;
+		chn.send(bytes!("")).await?;
+		// Output CHANGES
+		chn.send(bytes!("-- ------------------------------")).await?;
+		chn.send(bytes!(format!("-- CHANGES SINCE: {}", since.0))).await?;
+		chn.send(bytes!("-- ------------------------------")).await?;
+		chn.send(bytes!("")).await?;
+		// Fetch changes
+		let mut rng = crate::key::cf::lsn(ns, db, since.0)..crate::key::cf::suffix(ns, db);
+		let mut out: BTreeMap<Key, (u64, Thing, Value)> = BTreeMap::new();
+		loop {
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Parse the key-value
+				let k: crate::key::cf::Cf = (&k).into();
+				let v: Value = (&v).into();
+				// Keep the latest change to each record
+				let key: Key = thing::new(ns, db, &k.tb, &k.id).into();
+				out.insert(key, (k.lsn, Thing::from((k.tb, k.id)), v));
+			}
+		}
+		// Write the changes in order, and in key order within a transaction
+		let mut out: Vec<_> = out.into_values().collect();
+		out.sort_by_key(|(lsn, _, _)| *lsn);
+		for (_, t, v) in out {
+			match v {
+				Value::None => chn.send(bytes!(format!("DELETE {};", t))).await?,
+				v => chn.send(bytes!(format!("UPDATE {} CONTENT {};", t, v))).await?,
+			}
+		}
+		chn.send(bytes!("")).await?;
+		// Commit transaction
+		chn.send(bytes!("-- ------------------------------")).await?;
+		chn.send(bytes!("-- TRANSACTION")).await?;
+		chn.send(bytes!("-- ------------------------------")).await?;
+		chn.send(bytes!("")).await?;
+		chn.send(bytes!("COMMIT TRANSACTION;")).await?;
+		chn.send(bytes!("")).await?;
+		// Everything exported
+		Ok(())
+	}
+	/// Retrieve a summary of the definitions and records in a database.
+	///
+	/// The namespace and database counts cover the whole datastore and the
+	/// specified namespace respectively, while all other counts are for the
+	/// specified database. Tokens include namespace, database, and scope tokens.
+	pub async fn keyspace_summary(&mut self, ns: &str, db: &str) -> Result<KeyspaceSummary, Error> {
+		let mut out = KeyspaceSummary {
+			namespaces: self.all_ns().await?.len() as u64,
+			databases: self.all_db(ns).await?.len() as u64,
+			..KeyspaceSummary::default()
+		};
+		// Count the tokens
+		out.tokens += self.all_nt(ns).await?.len() as u64;
+		out.tokens += self.all_dt(ns, db).await?.len() as u64;
+		// Count the scopes
+		let scs = self.all_sc(ns, db).await?;
+		out.scopes = scs.len() as u64;
+		for sc in scs.iter() {
+			out.tokens += self.all_st(ns, db, &sc.name).await?.len() as u64;
+		}
+		// Count the tables
+		let tbs = self.all_tb(ns, db).await?;
+		out.tables = tbs.len() as u64;
+		for tb in tbs.iter() {
+			out.fields += self.all_fd(ns, db, &tb.name).await?.len() as u64;
+			out.indexes += self.all_ix(ns, db, &tb.name).await?.len() as u64;
+			out.events += self.all_ev(ns, db, &tb.name).await?.len() as u64;
+			out.live_queries += self.all_lv(ns, db, &tb.name).await?.len() as u64;
+			// Count the records
+			let beg = thing::prefix(ns, db, &tb.name);
+			let end = thing::suffix(ns, db, &tb.name);
+			let num = self.count_range(beg..end).await?;
+			out.records.insert(tb.name.to_string(), num);
+		}
+		Ok(out)
+	}
+	/// Retrieve the resources currently used by a database.
+	///
+	/// Storage is measured as the total size of the record keys and values, and
+	/// index usage as the total size of the index entries, across every table.
+	/// This scans every record and index entry in the database, so it is intended
+	/// for reporting, and is not used when quotas are enforced.
+	pub async fn get_quota_usage(&mut self, ns: &str, db: &str) -> Result<QuotaUsage, Error> {
+		let mut out = QuotaUsage::default();
+		for tb in self.all_tb(ns, db).await?.iter() {
+			// Measure the records
+			let beg = thing::prefix(ns, db, &tb.name);
+			let end = thing::suffix(ns, db, &tb.name);
+			let (num, len) = self.measure_keys(beg, end).await?;
+			out.record_count += num;
+			out.storage_bytes += len;
+			// Measure the indexes
+			for ix in self.all_ix(ns, db, &tb.name).await?.iter() {
+				let beg = crate::key::index::prefix(ns, db, &tb.name, &ix.name);
+				let end = crate::key::index::suffix(ns, db, &tb.name, &ix.name);
+				let (_, len) = self.measure_keys(beg, end).await?;
+				out.index_bytes += len;
+			}
+			// Count the live queries
+			out.active_live_queries += self.all_lv(ns, db, &tb.name).await?.len() as u64;
+		}
+		Ok(out)
+	}
+	/// Retrieve the quota definition of a specific database, if one is defined.
+	///
+	/// The definition, or its absence, is cached within the transaction, as it is
+	/// checked whenever a record is stored or deleted.
+	pub async fn get_quota(
+		&mut self,
+		ns: &str,
+		db: &str,
+	) -> Result<Option<QuotaDefinition>, Error> {
+		let key = crate::key::qu::new(ns, db).encode()?;
+		match self.cache.get(&key) {
+			Some(Entry::Qu(v)) => Ok(v.as_ref().clone()),
+			_ => {
+				let val = self.get(key.clone()).await?.map(QuotaDefinition::from);
+				self.cache.set(key, Entry::Qu(Arc::new(val.clone())));
+				Ok(val)
+			}
+		}
+	}
+	/// Define the resource limits of a specific database, replacing any existing quota.
+	pub async fn set_quota(
+		&mut self,
+		ns: &str,
+		db: &str,
+		quota: &QuotaDefinition,
+	) -> Result<(), Error> {
+		// Measure the current usage once, if it is not yet tracked
+		let beg = crate::key::qc::prefix(ns, db);
+		let end = crate::key::qc::suffix(ns, db);
+		if self.scan_keys(beg.clone()..end, 1).await?.is_empty() {
+			for tb in self.all_tb(ns, db).await?.iter() {
+				let beg = thing::prefix(ns, db, &tb.name);
+				let end = thing::suffix(ns, db, &tb.name);
+				let (records, storage) = self.measure_keys(beg, end).await?;
+				let key = crate::key::qc::new(ns, db, &tb.name);
+				let val = QuotaCounter {
+					records,
+					storage,
+				};
+				self.set(key, val).await?;
+			}
+		}
+		self.cache.del(&beg);
+		let key = crate::key::qu::new(ns, db);
+		self.cache.del(&key.encode()?);
+		self.invalidate_writes(&crate::key::database::new(ns, db).encode()?);
+		self.set(key, quota).await
+	}
+	/// Remove the resource limits of a specific database, leaving it unrestricted.
+	pub async fn del_quota(&mut self, ns: &str, db: &str) -> Result<(), Error> {
+		let beg = crate::key::qc::prefix(ns, db);
+		let end = crate::key::qc::suffix(ns, db);
+		let rng = beg..end;
+		self.quotas.retain(|k, _| !rng.contains(k));
+		self.cache.del(&rng.start);
+		self.delr(rng, u32::MAX).await?;
+		let key = crate::key::qu::new(ns, db);
+		self.cache.del(&key.encode()?);
+		self.invalidate_writes(&crate::key::database::new(ns, db).encode()?);
+		self.del(key).await
+	}
+	/// Check that a database has not used more resources than its quota allows.
+	///
+	/// If no quota is defined for the database, then the usage is not measured. Otherwise
+	/// the usage which is tracked as records are stored and deleted is compared with the
+	/// quota, and the first limit which has been exceeded is returned as an
+	/// [`Error::QuotaExceeded`] error.
+	pub async fn enforce_quota(&mut self, ns: &str, db: &str) -> Result<(), Error> {
+		let quota = match self.get_quota(ns, db).await? {
+			Some(v) => v,
+			None => return Ok(()),
+		};
+		let usage = self.get_quota_counter(ns, db).await?;
+		let checks = [
+			("records", quota.max_records, usage.records),
+			("storage", quota.max_storage_bytes, usage.storage),
+		];
+		for (resource, limit, actual) in checks {
+			if let Some(limit) = limit {
+				if actual > limit {
+					return Err(Error::QuotaExceeded {
+						resource,
+						limit,
+						actual,
+					});
+				}
+			}
+		}
+		Ok(())
+	}
+	/// Check that a new live query can be started in a database without exceeding its quota.
+	pub async fn enforce_live_quota(&mut self, ns: &str, db: &str) -> Result<(), Error> {
+		let limit = match self.get_quota(ns, db).await? {
+			Some(QuotaDefinition {
+				max_live_queries: Some(v),
+				..
+			}) => v,
+			_ => return Ok(()),
+		};
+		let beg = crate::key::lq::prefix(ns, db);
+		let end = crate::key::lq::suffix(ns, db);
+		let actual = self.scan_keys(beg..end, u32::MAX).await?.len() as u64 + 1;
+		if actual > limit {
+			return Err(Error::QuotaExceeded {
+				resource: "live query",
+				limit,
+				actual,
+			});
+		}
+		Ok(())
+	}
+	// Retrieve the tracked quota usage of a database, across all of its tables
+	//
+	// The stored usage is only read once within the transaction, and the
+	// usage changes which are pending in the transaction are added to it.
+	async fn get_quota_counter(&mut self, ns: &str, db: &str) -> Result<QuotaCounter, Error> {
+		let beg = crate::key::qc::prefix(ns, db);
+		let end = crate::key::qc::suffix(ns, db);
+		let mut out = match self.cache.get(&beg) {
+			Some(Entry::Qc(v)) => v,
+			_ => {
+				let mut out = QuotaCounter::default();
+				for (_, v) in self.getr(beg.clone()..end.clone(), u32::MAX).await? {
+					let v: QuotaCounter = v.into();
+					out.records += v.records;
+					out.storage += v.storage;
+				}
+				self.cache.set(beg.clone(), Entry::Qc(out));
+				out
+			}
+		};
+		let rng = beg..end;
+		for (_, v) in self.quotas.iter().filter(|(k, _)| rng.contains(k)) {
+			out = out.apply(*v);
+		}
+		Ok(out)
+	}
+	// Update the tracked quota usage of a table for a changed record
+	//
+	// Only the creation of a new record is checked against the quota, so that
+	// existing records can still be updated or deleted once a limit is reached.
+	// The usage change is kept in the transaction, and is only stored when the
+	// transaction is committed.
+	#[allow(clippy::too_many_arguments)]
+	async fn track_quota_usage(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		quota: &QuotaDefinition,
+		key: &Key,
+		old: Option<&Val>,
+		new: Option<&Val>,
+	) -> Result<(), Error> {
+		let size = |v: &Val| (key.len() + v.len()) as i64;
+		let delta = match (old, new) {
+			(None, Some(v)) => {
+				// Check the usage across all tables
+				let total = self.get_quota_counter(ns, db).await?;
+				let checks = [
+					("records", quota.max_records, total.records + 1),
+					("storage", quota.max_storage_bytes, total.storage + size(v) as u64),
+				];
+				for (resource, limit, actual) in checks {
+					if let Some(limit) = limit {
+						if actual > limit {
+							return Err(Error::QuotaExceeded {
+								resource,
+								limit,
+								actual,
+							});
+						}
+					}
+				}
+				QuotaDelta {
+					records: 1,
+					storage: size(v),
+				}
+			}
+			(Some(o), Some(v)) => QuotaDelta {
+				records: 0,
+				storage: size(v) - size(o),
+			},
+			(Some(o), None) => QuotaDelta {
+				records: -1,
+				storage: -size(o),
+			},
+			(None, None) => return Ok(()),
+		};
+		let qc = crate::key::qc::new(ns, db, tb).encode()?;
+		self.quotas.entry(qc).or_default().add(delta);
+		Ok(())
+	}
+	// Release the tracked quota usage of records which were removed from a table
+	async fn release_quota_usage(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		num: u64,
+		len: u64,
+	) -> Result<(), Error> {
+		if self.get_quota(ns, db).await?.is_none() {
+			return Ok(());
+		}
+		let qc = crate::key::qc::new(ns, db, tb).encode()?;
+		self.quotas.entry(qc).or_default().add(QuotaDelta {
+			records: -(num as i64),
+			storage: -(len as i64),
+		});
+		Ok(())
+	}
+	// Store the quota usage changes which are pending in this transaction
+	//
+	// The stored usage of each changed table is read and written only once.
+	async fn store_quota_usage(&mut self) -> Result<(), Error> {
+		for (key, delta) in std::mem::take(&mut self.quotas) {
+			if delta == QuotaDelta::default() {
+				continue;
+			}
+			let usage: QuotaCounter = match self.get(key.clone()).await? {
+				Some(v) => v.into(),
+				None => QuotaCounter::default(),
+			};
+			self.set(key, usage.apply(delta)).await?;
+		}
+		Ok(())
+	}
+	/// Count the keys in a range, grouped by their first `prefix_len` bytes.
+	///
+	/// Keys which are shorter than `prefix_len` are grouped by the whole key.
+	/// This function fetches keys from the underlying datastore in batches of 1000.
+	pub async fn scan_prefix_histogram(
+		&mut self,
+		mut rng: Range<Key>,
+		prefix_len: usize,
+	) -> Result<HashMap<Vec<u8>, u64>, Error> {
+		let mut out: HashMap<Vec<u8>, u64> = HashMap::new();
+		// Start processing
+		loop {
+			// Get keys batch
+			let res = self.next_batch_keys(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for k in res {
+				// Count the key prefix
+				let p = &k[..std::cmp::min(prefix_len, k.len())];
+				*out.entry(p.to_vec()).or_default() += 1;
+			}
+		}
+		Ok(out)
+	}
+	/// Compute a SHA-256 checksum of all records in a table.
+	///
+	/// Records are hashed in key order, using the record id and value but not the
+	/// namespace or database names, so identical tables in different databases
+	/// produce identical checksums.
+	pub async fn compute_table_checksum(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+	) -> Result<[u8; 32], Error> {
+		let mut rng = thing::prefix(ns, db, tb)..thing::suffix(ns, db, tb);
+		let len = rng.start.len();
+		let mut hsh = Sha256::new();
+		// Start processing
+		loop {
+			// Get records batch
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Hash the record id and value
+				hsh.update(((k.len() - len) as u64).to_be_bytes());
+				hsh.update(&k[len..]);
+				hsh.update((v.len() as u64).to_be_bytes());
+				hsh.update(&v);
+			}
+		}
+		Ok(hsh.finalize().into())
+	}
+	/// Compute a SHA-256 checksum of all records in a database.
+	///
+	/// The checksum of each table is hashed with the table name, and the results are
+	/// combined with XOR, so the checksum does not depend on the order of the tables.
+	pub async fn compute_database_checksum(
+		&mut self,
+		ns: &str,
+		db: &str,
+	) -> Result<[u8; 32], Error> {
+		let mut out = [0u8; 32];
+		for tb in self.all_tb(ns, db).await?.iter() {
+			let sum = self.compute_table_checksum(ns, db, &tb.name).await?;
+			let mut hsh = Sha256::new();
+			hsh.update(tb.name.as_bytes());
+			hsh.update(sum);
+			for (o, v) in out.iter_mut().zip(hsh.finalize()) {
+				*o ^= v;
+			}
+		}
+		Ok(out)
+	}
+	/// Retrieve the records in a table which changed between two versions.
+	///
+	/// This function replays the change feed of the database up to `version_b`, so the
+	/// table must be defined with a change feed which is retained since `version_a`. A record
+	/// which did not exist at `version_a` but exists at `version_b` is reported as added,
+	/// and a record which existed at `version_a` but not at `version_b` is reported as
+	/// removed. A record which existed at both versions, and which was written between
+	/// them, is reported as modified. Records are returned in key order.
+	pub async fn diff_table(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+		version_a: u64,
+		version_b: u64,
+	) -> Result<Vec<RecordDiff>, Error> {
+		// Nothing can change between these versions
+		if version_a >= version_b {
+			return Ok(vec![]);
+		}
+		// The state of each changed record
+		let mut out: BTreeMap<Key, (Thing, bool, bool)> = BTreeMap::new();
+		// The state of each record at the first version
+		let mut old: HashMap<Key, bool> = HashMap::new();
+		// Replay the change feed
+		let beg = crate::key::cf::lsn(ns, db, 0);
+		let end = crate::key::cf::lsn(ns, db, version_b.saturating_add(1));
+		let mut rng = beg..end;
+		loop {
+			let res = self.next_batch(&mut rng).await?;
+			// Exit when settled
+			if res.is_empty() {
+				break;
+			}
+			// Loop over results
+			for (k, v) in res {
+				// Parse the key-value
+				let k: crate::key::cf::Cf = (&k).into();
+				if k.tb != tb {
+					continue;
+				}
+				let v: Value = (&v).into();
+				let key: Key = thing::new(ns, db, tb, &k.id).into();
+				// Replay the change
+				match k.lsn <= version_a {
+					true => {
+						old.insert(key, !v.is_none());
+					}
+					false => {
+						let exi = old.get(&key).copied().unwrap_or(false);
+						let rid = Thing::from((k.tb, k.id));
+						out.insert(key, (rid, exi, !v.is_none()));
+					}
+				}
+			}
+		}
+		// Output the changed records
+		Ok(out
+			.into_values()
+			.filter_map(|(thing, before, after)| {
+				let change = match (before, after) {
+					(false, true) => DiffType::Added,
+					(true, false) => DiffType::Removed,
+					(true, true) => DiffType::Modified,
+					(false, false) => return None,
+				};
+				Some(RecordDiff {
+					thing,
+					change,
+				})
+			})
+			.collect())
+	}
+	/// Acquire a write lock on a table.
+	///
+	/// This function waits until no other transaction holds a write lock on the table,
+	/// failing with an [`Error::TableLockTimeout`] error if the lock can not be acquired
+	/// within [`TABLE_LOCK_TIMEOUT`](crate::cnf::TABLE_LOCK_TIMEOUT). While the lock is
+	/// held, any other transaction which attempts to write to the table will fail with
+	/// an [`Error::TableLocked`] error. Read operations are not affected.
+	///
+	/// A transaction which already holds the lock receives another guard for it, and
+	/// on this datastore the lock is released once all of its guards are dropped. On
+	/// distributed datastores, the lock is also stored as a lease, which is held across
+	/// all nodes until this transaction is committed or cancelled. The lease lasts for
+	/// [`TABLE_LOCK_LEASE`](crate::cnf::TABLE_LOCK_LEASE), and is renewed when this
+	/// transaction writes to the table after half of that time has passed. If the lease
+	/// expires before it is renewed, as when the node running this transaction fails or
+	/// the transaction does not write to the table in time, then other transactions may
+	/// write to the table, and any further write to the table by this transaction fails
+	/// with an [`Error::TableLockExpired`] error.
+	pub async fn write_lock_table(
+		&mut self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+	) -> Result<TableLockGuard, Error> {
+		let key: Key = crate::key::table::new(ns, db, tb).into();
+		let lease: Key = crate::key::lk::new(ns, db, tb).into();
+		let owner = self.active.id();
+		let start = Instant::now();
+		loop {
+			self.timeout.check()?;
+			// Acquire the lock on this datastore
+			if let Some(v) = self.locks.acquire(&key)? {
+				// Acquire the lock across all nodes, unless it is already held
+				if !self.ds.distributed() || self.leases.iter().any(|(k, _)| *k == lease) {
+					return Ok(v);
+				}
+				let res = self.timeout.run(lock::acquire(&self.ds, lease.clone(), owner)).await?;
+				if let Some(exp) = res {
+					self.leases.push((lease, exp));
+					return Ok(v);
+				}
+			}
+			// Give up once the lock has been waited on for too long
+			if start.elapsed() >= crate::cnf::TABLE_LOCK_TIMEOUT {
+				return Err(Error::TableLockTimeout);
+			}
+			Delay::new(Duration::from_millis(10)).await;
+		}
+	}
+	/// Check that a table is not write locked by another transaction.
+	///
+	/// This is checked whenever a record is stored or deleted in the table. On distributed
+	/// datastores, the lease on the lock is read once within this transaction, and a lease
+	/// held by this transaction is renewed if it is due.
+	pub async fn check_table_lock(&mut self, ns: &str, db: &str, tb: &str) -> Result<(), Error> {
+		self.locks.check(&crate::key::table::new(ns, db, tb).into())?;
+		if self.ds.distributed() {
+			let lease: Key = crate::key::lk::new(ns, db, tb).into();
+			let owner = self.active.id();
+			// Renew the lease if it is held by this transaction
+			if let Some((_, exp)) = self.leases.iter_mut().find(|(k, _)| *k == lease) {
+				if Utc::now().timestamp_millis() >= lock::renewal(*exp) {
+					*exp = lock::renew(&self.ds, lease, owner).await?;
+				}
+				return Ok(());
+			}
+			// Check the lease is not held elsewhere, which only
+			// needs reading once, as the transaction snapshot
+			// does not change
+			if !self.checked.contains(&lease) {
+				let val = self.get(lease.clone()).await?;
+				lock::check(val, &owner)?;
+				self.checked.insert(lease);
+			}
+		}
+		Ok(())
+	}
+	// Remove the sentinel of this transaction, which is stored on distributed datastores
+	async fn clear_active(&self) {
+		if self.active.stored() {
+			if let Err(e) = active::clear(&self.ds, &self.active.id()).await {
+				debug!(target: LOG, "Failed to remove transaction sentinel: {}", e);
+			}
+		}
+	}
+	// Release the table write locks held across nodes by this transaction
+	async fn release_leases(&mut self) {
+		let owner = self.active.id();
+		for (key, _) in std::mem::take(&mut self.leases) {
+			if let Err(e) = lock::release(&self.ds, key, owner).await {
+				debug!(target: LOG, "Failed to release table write lock: {}", e);
+			}
+		}
+	}
+	/// Retrieve the partitions of the datastore which hold the records in a table.
+	///
+	/// The embedded datastores hold each table in a single partition. The TiKV client
+	/// does not expose the region metadata held by the placement driver, so TiKV tables
+	/// are also reported as a single partition covering the full record range, with a
+	/// region id of 0 and no known leader node.
+	pub async fn get_table_partitions(
+		&self,
+		ns: &str,
+		db: &str,
+		tb: &str,
+	) -> Result<Vec<PartitionInfo>, Error> {
+		Ok(vec![PartitionInfo {
+			start_key: thing::prefix(ns, db, tb),
+			end_key: thing::suffix(ns, db, tb),
+			region_id: 0,
+			leader_node: String::new(),
+		}])
+	}
+}
+
+// A record retained in the bounded heap used by [`Transaction::scan_with_sort`].
+//
+// Records are ordered so that the greatest entry in the heap is always the
+// worst candidate for the result set, allowing it to be evicted when the heap
+// grows beyond the specified limit. Ties are broken by scan order.
+struct Sorted {
+	asc: bool,
+	ord: Value,
+	seq: usize,
+	rid: Thing,
+	val: Value,
+}
+
+impl PartialEq for Sorted {
+	fn eq(&self, other: &Self) -> bool {
+		self.cmp(other) == Ordering::Equal
+	}
+}
+
+impl Eq for Sorted {}
+
+impl PartialOrd for Sorted {
+	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+		Some(self.cmp(other))
+	}
+}
+
+impl Ord for Sorted {
+	fn cmp(&self, other: &Self) -> Ordering {
+		match self.asc {
+			true => self.ord.cmp(&other.ord),
+			false => other.ord.cmp(&self.ord),
+		}
+		.then(self.seq.cmp(&other.seq))
+	}
+}
+
+/// A summary of the definitions and records in a database.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct KeyspaceSummary {
+	pub namespaces: u64,
+	pub databases: u64,
+	pub tables: u64,
+	pub fields: u64,
+	pub indexes: u64,
+	pub events: u64,
+	pub scopes: u64,
+	pub tokens: u64,
+	pub live_queries: u64,
+	/// The number of records in each table.
+	pub records: HashMap<String, u64>,
+}
+
+/// The resources currently used by a database.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct QuotaUsage {
+	/// The number of records across all tables
+	pub record_count: u64,
+	/// The total size of the records in bytes
+	pub storage_bytes: u64,
+	/// The total size of the index entries in bytes
+	pub index_bytes: u64,
+	/// The number of live queries across all tables
+	pub active_live_queries: u64,
+}
+
+// The resources used by a table, tracked as records are stored and deleted.
+//
+// The usage is tracked separately for each table, so that concurrent writers
+// to different tables do not conflict, and the usage of a database is the sum
+// of the usage of its tables.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, Store)]
+pub(super) struct QuotaCounter {
+	// The number of records in the table
+	records: u64,
+	// The total size of the records in bytes
+	storage: u64,
+}
+
+impl QuotaCounter {
+	// Apply a change in usage, without falling below zero
+	fn apply(self, delta: QuotaDelta) -> QuotaCounter {
+		QuotaCounter {
+			records: self.records.saturating_add_signed(delta.records),
+			storage: self.storage.saturating_add_signed(delta.storage),
+		}
+	}
+}
+
+// A change in the resources used by a table, which is
+// kept until the transaction which made it is committed
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(super) struct QuotaDelta {
+	// The change in the number of records
+	records: i64,
+	// The change in the total size of the records in bytes
+	storage: i64,
+}
+
+impl QuotaDelta {
+	// Combine another change with this change
+	fn add(&mut self, other: QuotaDelta) {
+		self.records += other.records;
+		self.storage += other.storage;
+	}
+}
+
+/// The resource limits of a database.
+///
+/// A limit of [`None`] leaves that resource unrestricted.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, Store)]
+pub struct QuotaDefinition {
+	/// The maximum number of records across all tables
+	pub max_records: Option<u64>,
+	/// The maximum total size of the records in bytes
+	pub max_storage_bytes: Option<u64>,
+	/// The maximum number of live queries across all tables
+	pub max_live_queries: Option<u64>,
+}
+
+/// How to merge a database which already exists in the target namespace.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ConflictPolicy {
+	/// Leave the existing database, and discard the merged database
+	Skip,
+	/// Replace the existing database with the merged database
+	Overwrite,
+	/// Move the merged database under its name with this suffix
+	Rename(String),
+}
+
+/// The databases which were merged into a namespace.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MergeReport {
+	/// The databases which were moved under the same name
+	pub moved: Vec<String>,
+	/// The databases which already existed, and were not moved
+	pub skipped: Vec<String>,
+	/// The databases which were moved under a new name, along with that name
+	pub renamed: Vec<(String, String)>,
+}
+
+/// A point in time, as a Unix timestamp in microseconds.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Timestamp(pub u64);
+
+/// A savepoint created within a transaction.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SavepointId(u32);
+
+/// A read snapshot pinned on the datastore.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SnapshotToken {
+	/// The read version of the snapshot
+	pub version: u64,
+}
+
+/// A record which changed between two versions of a table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RecordDiff {
+	/// The id of the record
+	pub thing: Thing,
+	/// The type of change
+	pub change: DiffType,
+}
+
+/// The type of change made to a record between two versions of a table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DiffType {
+	Added,
+	Removed,
+	Modified,
+}
+
+/// A partition of the datastore which holds a range of keys.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PartitionInfo {
+	/// The first key in the partition
+	pub start_key: Key,
+	/// The key after the last key in the partition
+	pub end_key: Key,
+	/// The id of the region on distributed datastores
+	pub region_id: u64,
+	/// The address of the node which leads the partition
+	pub leader_node: String,
+}
+
+/// Statistics for the values of a field across the records in a table.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FieldStats {
+	/// The number of records in the table
+	pub total_records: u64,
+	/// The number of records where the field is missing or NULL
+	pub null_count: u64,
+	/// The number of distinct values of the field
+	pub distinct_count: u64,
+	/// The smallest value of the field
+	pub min: Option<Value>,
+	/// The largest value of the field
+	pub max: Option<Value>,
+	/// The number of records with each type of value
+	pub type_distribution: HashMap<String, u64>,
+}
+
+// Encode the key prefix of the index entries for a set of index values
+fn index_key(ns: &str, db: &str, tb: &str, ix: &str, fd: sql::array::Array) -> Key {
+	let mut key: Key = crate::key::index::new(ns, db, tb, ix, fd, None).into();
+	// Remove the trailing record id marker
+	key.pop();
+	key
+}
+
+// Convert a lookup value into the values of the indexed fields
+fn index_values(ix: &DefineIndexStatement, value: &Value) -> sql::array::Array {
+	match value {
+		Value::Array(v) if ix.cols.len() > 1 => v.clone(),
+		v => v.clone().into(),
+	}
+}
+
+// Fetch the resident memory of the current process
+#[cfg(target_os = "linux")]
+fn memory_used() -> u64 {
+	let status = std::fs::read_to_string("/proc/self/status").unwrap_or_default();
+	status
+		.lines()
+		.find(|v| v.starts_with("VmRSS:"))
+		.and_then(|v| v.split_whitespace().nth(1))
+		.and_then(|v| v.parse::<u64>().ok())
+		.map(|v| v * 1024)
+		.unwrap_or(0)
+}
+
+// Fetch the resident memory of the current process
+#[cfg(not(target_os = "linux"))]
+fn memory_used() -> u64 {
+	0
+}
+
+// Collect the record ids which are referenced by a field value
+fn record_refs(v: Value) -> Vec<Thing> {
+	match v {
+		Value::Thing(v) => vec![v],
+		Value::Array(v) => v
+			.into_iter()
+			.filter_map(|v| match v {
+				Value::Thing(v) => Some(v),
+				_ => None,
+			})
+			.collect(),
+		_ => vec![],
+	}
+}
+
+// Evaluate a live query condition against a record
+fn live_condition(val: &Value, doc: &Value) -> Result<Value, Error> {
+	match val {
+		Value::Expression(v) => {
+			let l = live_condition(&v.l, doc)?;
+			match v.o {
+				sql::Operator::Or if l.is_truthy() => return Ok(l),
+				sql::Operator::And if !l.is_truthy() => return Ok(l),
+				_ => (),
+			}
+			let r = live_condition(&v.r, doc)?;
+			v.operate(l, r)
+		}
+		Value::Idiom(v) => Ok(doc.pick(v)),
+		Value::Array(v) => {
+			Ok(v.iter().map(|v| live_condition(v, doc)).collect::<Result<Vec<_>, _>>()?.into())
+		}
+		Value::Param(_)
+		| Value::Model(_)
+		| Value::Edges(_)
+		| Value::Function(_)
+		| Value::Subquery(_) => Err(Error::LiveCondition {
+			value: val.to_string(),
+		}),
+		v => Ok(v.clone()),
+	}
+}
+
+// Extract the namespace and database from a key within a database
+fn key_database(key: &[u8]) -> Option<(String, String)> {
+	let key = key.strip_prefix(b"/*")?;
+	let i = key.iter().position(|&v| v == 0x00)?;
+	let (ns, key) = (&key[..i], key[i + 1..].strip_prefix(b"*")?);
+	let i = key.iter().position(|&v| v == 0x00)?;
+	let db = &key[..i];
+	Some((String::from_utf8_lossy(ns).into_owned(), String::from_utf8_lossy(db).into_owned()))
+}
+
+// Split a text value into lowercase alphanumeric terms
+fn text_terms(v: &str) -> impl Iterator<Item = String> + '_ {
+	v.split(|c: char| !c.is_alphanumeric()).filter(|v| !v.is_empty()).map(str::to_lowercase)
+}
+
+// The term frequencies of the text values in an index
+#[derive(Default)]
+struct TextStats {
+	// The total number of index entries
+	total_docs: u64,
+	// The total number of terms across all index entries
+	total_terms: u64,
+	// The record, term counts, and term total of each matching index entry
+	docs: Vec<(Thing, Vec<u64>, u64)>,
+}
+
+/// An index which is recommended for a table.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexRecommendation {
+	/// The fields which should be indexed
+	pub fields: Vec<String>,
+	/// The type of index which should be defined
+	pub type_: IndexType,
+	/// The reason for the recommendation
+	pub reason: String,
+	/// The estimated speedup of a lookup on the fields
+	pub estimated_speedup: f64,
+}
+
+/// The type of an index on a table.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IndexType {
+	Standard,
+	Unique,
+}
+
+/// A decoded entry of an index on a table.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IndexEntry {
+	/// The indexed field values
+	pub index_key: Value,
+	/// The id of the indexed record
+	pub record_id: Thing,
+}
+
+/// Statistics about the entries in an index on a table.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IndexStats {
+	/// The total number of index entries
+	pub entry_count: u64,
+	/// The number of distinct indexed values
+	pub distinct_values: u64,
+	/// The total size of the index entries in bytes
+	pub size_bytes: u64,
+}
+
+/// A planned storage operation of a query.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExplainStep {
+	/// The type of operation which is performed
+	pub operation: String,
+	/// The target of the operation
+	pub target: String,
+	/// The estimated number of rows visited
+	pub estimated_rows: u64,
+	/// The estimated cost of the operation
+	pub cost: u64,
+}
+
+/// A comparison of a record field against a value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Predicate {
+	/// The field which is compared
+	pub field: String,
+	/// The comparison operator
+	pub op: sql::Operator,
+	/// The value which the field is compared against
+	pub value: Value,
+}
+
+/// The estimated cost of the best access path for a table scan.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct QueryCostEstimate {
+	/// The estimated number of rows visited
+	pub estimated_rows: u64,
+	/// The estimated number of bytes read
+	pub estimated_bytes: u64,
+	/// The index which is used, if any
+	pub index_used: Option<String>,
+	/// Whether the whole table is scanned
+	pub full_scan: bool,
+}
+
+/// The schema definitions which are referenced by a query.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct QuerySchemaRefs {
+	/// The tables which are referenced
+	pub tables: Vec<String>,
+	/// The fields which are referenced, grouped by table. Only the tables are used
+	/// when prefetching, as field definitions are cached for a table as a whole.
+	pub fields: HashMap<String, Vec<String>>,
+	/// The indexes which are referenced, grouped by table. Only the tables are used
+	/// when prefetching, as index definitions are cached for a table as a whole.
+	pub indexes: HashMap<String, Vec<String>>,
+}
+
+/// A schema migration which has been applied to a database.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Store)]
+pub struct MigrationRecord {
+	/// The version of the migration
+	pub version: u64,
+	/// The name of the migration
+	pub name: String,
+	/// The time at which the migration was applied
+	pub applied_at: DateTime<Utc>,
+	/// The user or service which applied the migration
+	pub applied_by: String,
+	/// The checksum of the migration script
+	pub checksum: [u8; 32],
+}
+
+/// The claims of a token which has been verified with a token definition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JwtClaims {
+	/// The subject of the token
+	pub subject: String,
+	/// The namespace in which the token was verified
+	pub ns: String,
+	/// The database in which the token was verified
+	pub db: Option<String>,
+	/// The scope in which the token was verified
+	pub sc: Option<String>,
+	/// The expiry time of the token, as a Unix timestamp
+	pub exp: u64,
+	/// The remaining claims of the token
+	pub extra: Value,
+}
+
+/// An authenticated session which has been stored in a database.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Store)]
+pub struct SessionRecord {
+	/// The unique id of the session
+	pub id: String,
+	/// The scope which the session is authenticated with
+	pub sc: Option<String>,
+	/// The subject which the session is authenticated as
+	pub subject: String,
+	/// The time at which the session was created
+	pub created_at: DateTime<Utc>,
+	/// The time at which the session expires
+	pub expires_at: DateTime<Utc>,
+}
+
+/// An access to a key which has been recorded in the access log of a database.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Store)]
+pub struct AccessLogEntry {
+	/// The time at which the key was accessed
+	pub at: DateTime<Utc>,
+	/// The operation which was performed on the key
+	pub operation: String,
+	/// The key which was accessed
+	pub key: Key,
+}
+
+/// The changes needed to bring the field definitions on a table in line with a target.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FieldSchemaDiff {
+	/// The target field definitions which are not yet defined
+	pub to_add: Vec<DefineFieldStatement>,
+	/// The target field definitions which differ from the defined fields
+	pub to_alter: Vec<DefineFieldStatement>,
+	/// The defined fields which are not in the target
+	pub to_drop: Vec<DefineFieldStatement>,
+}
+
+/// A snapshot of the health and size of a datastore.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SysInfo {
+	/// The number of transactions which are open
+	pub active_transactions: u64,
+	/// The number of namespaces which are defined
+	pub total_namespaces: u64,
+	/// The number of databases which are defined across all namespaces
+	pub total_databases: u64,
+	/// The name and version of the underlying datastore
+	pub backend_version: String,
+	/// The number of seconds since the datastore was started
+	pub uptime_secs: u64,
+	/// The round-trip latency of the underlying datastore in milliseconds
+	pub backend_latency_ms: f64,
+	/// The resident memory of the process in bytes, or 0 where this is not available
+	pub memory_used_bytes: u64,
 }
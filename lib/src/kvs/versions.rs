@@ -0,0 +1,67 @@
+use futures::lock::Mutex as AsyncMutex;
+use futures::lock::MutexGuard;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+// The number of table versions which are recorded before superseded versions are removed
+const COMPACT_THRESHOLD: u64 = 1000;
+
+// A table, identified by its namespace, database, and name
+pub type Table = (String, String, String);
+
+/// The tables whose versions have been updated on a datastore since their
+/// superseded versions were last removed.
+///
+/// Each writing transaction records its own version key for every table which
+/// it modifies, so that concurrent writers to a table do not conflict. Only the
+/// latest version key of a table is needed, so once enough versions have been
+/// recorded, the superseded version keys of the modified tables are removed in
+/// a separate write transaction.
+///
+/// The datastore also keeps the latest version which has been given to a
+/// transaction. Versions are given to transactions as they are committed, while
+/// the clock is held, so that transactions which are committed later through
+/// this datastore always have later versions.
+#[derive(Clone, Default)]
+pub struct Versions {
+	pending: Arc<Mutex<Pending>>,
+	clock: Arc<AsyncMutex<u64>>,
+}
+
+#[derive(Default)]
+struct Pending {
+	// The total number of versions recorded
+	total: u64,
+	// The tables which have recorded versions
+	tables: HashSet<Table>,
+}
+
+impl Versions {
+	// Count a version recorded for a table
+	pub fn count(&self, ns: &str, db: &str, tb: &str) {
+		let mut p = self.pending.lock().unwrap();
+		p.tables.insert((ns.to_owned(), db.to_owned(), tb.to_owned()));
+		p.total += 1;
+	}
+	// Check if enough versions have been recorded for them to be compacted
+	pub fn full(&self) -> bool {
+		self.pending.lock().unwrap().total >= COMPACT_THRESHOLD
+	}
+	// Take all of the tables with recorded versions, so that they can be compacted
+	pub fn take(&self) -> HashSet<Table> {
+		let mut p = self.pending.lock().unwrap();
+		p.total = 0;
+		std::mem::take(&mut p.tables)
+	}
+	// Return tables which could not be compacted to the pending tables
+	pub fn restore(&self, tables: HashSet<Table>) {
+		let mut p = self.pending.lock().unwrap();
+		p.total += tables.len() as u64;
+		p.tables.extend(tables);
+	}
+	// Hold the clock, until the transaction which is given the next version is committed
+	pub async fn clock(&self) -> MutexGuard<'_, u64> {
+		self.clock.lock().await
+	}
+}
@@ -24,14 +24,48 @@ mod kvs;
 // SQL
 pub mod sql;
 
+// Tokens
+pub use kvs::jwt;
+
 // Exports
 pub use dbs::Auth;
 pub use dbs::Response;
 pub use dbs::Session;
 pub use err::Error;
+pub use kvs::AccessLogEntry;
+pub use kvs::ConflictPolicy;
 pub use kvs::Datastore;
+pub use kvs::DiffType;
+pub use kvs::ExplainStep;
+pub use kvs::FieldSchemaDiff;
+pub use kvs::FieldStats;
+pub use kvs::IndexEntry;
+pub use kvs::IndexRecommendation;
+pub use kvs::IndexStats;
+pub use kvs::IndexType;
+pub use kvs::JwtClaims;
 pub use kvs::Key;
+pub use kvs::KeyspaceSummary;
+pub use kvs::LiveAction;
+pub use kvs::LiveMessage;
+pub use kvs::MergeReport;
+pub use kvs::MigrationRecord;
+pub use kvs::PartitionInfo;
+pub use kvs::Predicate;
+pub use kvs::QueryCostEstimate;
+pub use kvs::QuerySchemaRefs;
+pub use kvs::QuotaDefinition;
+pub use kvs::QuotaUsage;
+pub use kvs::RecordDiff;
+pub use kvs::SavepointId;
+pub use kvs::SessionRecord;
+pub use kvs::SnapshotToken;
+pub use kvs::SysInfo;
+pub use kvs::TableLockGuard;
+pub use kvs::Timestamp;
 pub use kvs::Transaction;
+pub use kvs::TransactionType;
+pub use kvs::TxInfo;
 pub use kvs::Val;
 
 // Re-exports
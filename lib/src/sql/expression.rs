@@ -79,6 +79,10 @@ impl Expression {
 			_ => {} // Continue
 		}
 		let r = self.r.compute(ctx, opt, txn, doc).await?;
+		self.operate(l, r)
+	}
+	// Apply the operator to the computed operands
+	pub(crate) fn operate(&self, l: Value, r: Value) -> Result<Value, Error> {
 		match self.o {
 			Operator::Or => fnc::operate::or(l, r),
 			Operator::And => fnc::operate::and(l, r),
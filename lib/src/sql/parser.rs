@@ -1,6 +1,9 @@
 use crate::err::Error;
+use crate::sql::comment::mightbespace;
+use crate::sql::common::colons;
 use crate::sql::error::Error::ParserError;
 use crate::sql::query::{query, Query};
+use crate::sql::statement::{statement, Statement};
 use crate::sql::thing::Thing;
 use crate::sql::value::Value;
 use nom::Err;
@@ -36,6 +39,29 @@ pub fn parse(input: &str) -> Result<Query, Error> {
 	}
 }
 
+pub fn parse_lines(input: &str) -> Result<Vec<(usize, Statement)>, Error> {
+	let query = parse(input)?;
+	let mut out = Vec::with_capacity(query.len());
+	let mut i = input;
+	for stm in query.0 .0 {
+		// Skip any separators before the statement
+		while let Ok((s, _)) = colons(i) {
+			i = s;
+		}
+		if let Ok((s, _)) = mightbespace(i) {
+			i = s;
+		}
+		// Find the line on which the statement starts
+		let line = input[..input.len() - i.len()].matches('\n').count() + 1;
+		out.push((line, stm));
+		// Skip past the statement
+		if let Ok((s, _)) = statement(i) {
+			i = s;
+		}
+	}
+	Ok(out)
+}
+
 pub fn thing(input: &str) -> Result<Thing, Error> {
 	match input.trim().len() {
 		0 => Err(Error::QueryEmpty),
@@ -154,6 +180,14 @@ mod tests {
 		assert!(res.is_ok());
 	}
 
+	#[test]
+	fn parse_statement_lines() {
+		let sql = "SELECT * FROM test;\n\n-- comment\nSELECT *\nFROM test;; SELECT * FROM test";
+		let res = parse_lines(sql).unwrap();
+		let lines: Vec<usize> = res.iter().map(|(l, _)| *l).collect();
+		assert_eq!(lines, vec![1, 4, 5]);
+	}
+
 	#[test]
 	fn parse_complex_failure() {
 		let sql = "    SELECT    *    FROM    { }} ";
@@ -5,6 +5,7 @@ use crate::err::Error;
 use crate::sql::comment::{comment, mightbespace};
 use crate::sql::common::colons;
 use crate::sql::error::IResult;
+use crate::sql::statements::analyze::{analyze, AnalyzeStatement};
 use crate::sql::statements::begin::{begin, BeginStatement};
 use crate::sql::statements::cancel::{cancel, CancelStatement};
 use crate::sql::statements::commit::{commit, CommitStatement};
@@ -62,6 +63,7 @@ pub enum Statement {
 	Use(UseStatement),
 	Set(SetStatement),
 	Info(InfoStatement),
+	Analyze(AnalyzeStatement),
 	Live(LiveStatement),
 	Kill(KillStatement),
 	Begin(BeginStatement),
@@ -98,6 +100,7 @@ impl Statement {
 			Statement::Use(_) => false,
 			Statement::Set(v) => v.writeable(),
 			Statement::Info(_) => false,
+			Statement::Analyze(_) => false,
 			Statement::Live(_) => true,
 			Statement::Kill(_) => true,
 			Statement::Output(v) => v.writeable(),
@@ -125,6 +128,7 @@ impl Statement {
 		match self {
 			Statement::Set(v) => v.compute(ctx, opt, txn, doc).await,
 			Statement::Info(v) => v.compute(ctx, opt, txn, doc).await,
+			Statement::Analyze(v) => v.compute(ctx, opt, txn, doc).await,
 			Statement::Live(v) => v.compute(ctx, opt, txn, doc).await,
 			Statement::Kill(v) => v.compute(ctx, opt, txn, doc).await,
 			Statement::Output(v) => v.compute(ctx, opt, txn, doc).await,
@@ -148,6 +152,7 @@ impl fmt::Display for Statement {
 			Statement::Use(v) => write!(f, "{}", v),
 			Statement::Set(v) => write!(f, "{}", v),
 			Statement::Info(v) => write!(f, "{}", v),
+			Statement::Analyze(v) => write!(f, "{}", v),
 			Statement::Live(v) => write!(f, "{}", v),
 			Statement::Kill(v) => write!(f, "{}", v),
 			Statement::Begin(v) => write!(f, "{}", v),
@@ -175,6 +180,7 @@ pub fn statement(i: &str) -> IResult<&str, Statement> {
 			map(set, Statement::Set),
 			map(yuse, Statement::Use),
 			map(info, Statement::Info),
+			map(analyze, Statement::Analyze),
 			map(live, Statement::Live),
 			map(kill, Statement::Kill),
 			map(begin, Statement::Begin),
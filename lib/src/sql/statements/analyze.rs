@@ -0,0 +1,103 @@
+use crate::ctx::Context;
+use crate::dbs::Level;
+use crate::dbs::Options;
+use crate::dbs::Transaction;
+use crate::err::Error;
+use crate::kvs::IndexRecommendation;
+use crate::kvs::IndexType;
+use crate::sql::comment::shouldbespace;
+use crate::sql::error::IResult;
+use crate::sql::ident::{ident, Ident};
+use crate::sql::object::Object;
+use crate::sql::value::Value;
+use derive::Store;
+use nom::branch::alt;
+use nom::bytes::complete::tag_no_case;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, Store)]
+pub enum AnalyzeStatement {
+	Indexes(Ident),
+}
+
+impl AnalyzeStatement {
+	pub(crate) async fn compute(
+		&self,
+		_ctx: &Context<'_>,
+		opt: &Options,
+		txn: &Transaction,
+		_doc: Option<&Value>,
+	) -> Result<Value, Error> {
+		match self {
+			AnalyzeStatement::Indexes(tb) => {
+				// Selected DB?
+				opt.needs(Level::Db)?;
+				// Allowed to run?
+				opt.check(Level::Db)?;
+				// Clone transaction
+				let run = txn.clone();
+				// Claim transaction
+				let mut run = run.lock().await;
+				// Process the recommendations
+				let mut res: Vec<Value> = vec![];
+				for v in run.recommend_indexes(opt.ns(), opt.db(), tb).await? {
+					res.push(recommendation(v));
+				}
+				// Ok all good
+				Value::from(res).ok()
+			}
+		}
+	}
+}
+
+impl fmt::Display for AnalyzeStatement {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			AnalyzeStatement::Indexes(ref t) => write!(f, "ANALYZE TABLE {} RECOMMEND INDEXES", t),
+		}
+	}
+}
+
+fn recommendation(v: IndexRecommendation) -> Value {
+	let mut res = Object::default();
+	let fields: Vec<&str> = v.fields.iter().map(String::as_str).collect();
+	res.insert("fields".to_owned(), fields.into());
+	let kind = match v.type_ {
+		IndexType::Standard => "standard",
+		IndexType::Unique => "unique",
+	};
+	res.insert("type".to_owned(), kind.into());
+	res.insert("reason".to_owned(), v.reason.into());
+	res.insert("estimated_speedup".to_owned(), v.estimated_speedup.into());
+	res.into()
+}
+
+pub fn analyze(i: &str) -> IResult<&str, AnalyzeStatement> {
+	let (i, _) = tag_no_case("ANALYZE")(i)?;
+	let (i, _) = shouldbespace(i)?;
+	let (i, _) = alt((tag_no_case("TABLE"), tag_no_case("TB")))(i)?;
+	let (i, _) = shouldbespace(i)?;
+	let (i, table) = ident(i)?;
+	let (i, _) = shouldbespace(i)?;
+	let (i, _) = tag_no_case("RECOMMEND")(i)?;
+	let (i, _) = shouldbespace(i)?;
+	let (i, _) = tag_no_case("INDEXES")(i)?;
+	Ok((i, AnalyzeStatement::Indexes(table)))
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+
+	#[test]
+	fn analyze_query_indexes() {
+		let sql = "ANALYZE TABLE test RECOMMEND INDEXES";
+		let res = analyze(sql);
+		assert!(res.is_ok());
+		let out = res.unwrap().1;
+		assert_eq!(out, AnalyzeStatement::Indexes(Ident::from("test")));
+		assert_eq!("ANALYZE TABLE test RECOMMEND INDEXES", format!("{}", out));
+	}
+}
@@ -3,9 +3,12 @@ use crate::dbs::Level;
 use crate::dbs::Options;
 use crate::dbs::Transaction;
 use crate::err::Error;
+use crate::kvs::QuotaDefinition;
 use crate::sql::algorithm::{algorithm, Algorithm};
+use crate::sql::array::Array;
 use crate::sql::base::{base, Base};
 use crate::sql::comment::shouldbespace;
+use crate::sql::common::take_u64;
 use crate::sql::duration::{duration, Duration};
 use crate::sql::error::IResult;
 use crate::sql::escape::escape_strand;
@@ -13,9 +16,11 @@ use crate::sql::ident::{ident, Ident};
 use crate::sql::idiom;
 use crate::sql::idiom::{Idiom, Idioms};
 use crate::sql::kind::{kind, Kind};
+use crate::sql::operator::Operator;
 use crate::sql::permission::{permissions, Permissions};
 use crate::sql::statements::UpdateStatement;
 use crate::sql::strand::strand_raw;
+use crate::sql::subquery::Subquery;
 use crate::sql::value::{value, values, Value, Values};
 use crate::sql::view::{view, View};
 use argon2::password_hash::{PasswordHasher, SaltString};
@@ -43,6 +48,7 @@ pub enum DefineStatement {
 	Event(DefineEventStatement),
 	Field(DefineFieldStatement),
 	Index(DefineIndexStatement),
+	Quota(DefineQuotaStatement),
 }
 
 impl DefineStatement {
@@ -53,7 +59,8 @@ impl DefineStatement {
 		txn: &Transaction,
 		doc: Option<&Value>,
 	) -> Result<Value, Error> {
-		match self {
+		// Process the statement
+		let res = match self {
 			DefineStatement::Namespace(ref v) => v.compute(ctx, opt, txn, doc).await,
 			DefineStatement::Database(ref v) => v.compute(ctx, opt, txn, doc).await,
 			DefineStatement::Login(ref v) => v.compute(ctx, opt, txn, doc).await,
@@ -63,7 +70,35 @@ impl DefineStatement {
 			DefineStatement::Event(ref v) => v.compute(ctx, opt, txn, doc).await,
 			DefineStatement::Field(ref v) => v.compute(ctx, opt, txn, doc).await,
 			DefineStatement::Index(ref v) => v.compute(ctx, opt, txn, doc).await,
+			DefineStatement::Quota(ref v) => v.compute(ctx, opt, txn, doc).await,
+		}?;
+		// Clone transaction
+		let run = txn.clone();
+		// Claim transaction
+		let mut run = run.lock().await;
+		// Clear any cached definitions
+		match self {
+			DefineStatement::Namespace(v) => run.invalidate_ns_cache(&v.name),
+			DefineStatement::Database(v) => run.invalidate_db_cache(opt.ns(), &v.name),
+			DefineStatement::Login(v) => match v.base {
+				Base::Kv => run.invalidate_all_caches(),
+				Base::Ns => run.invalidate_ns_cache(opt.ns()),
+				Base::Db => run.invalidate_db_cache(opt.ns(), opt.db()),
+			},
+			DefineStatement::Token(v) => match v.base {
+				Base::Kv => run.invalidate_all_caches(),
+				Base::Ns => run.invalidate_ns_cache(opt.ns()),
+				Base::Db => run.invalidate_db_cache(opt.ns(), opt.db()),
+			},
+			DefineStatement::Scope(_) => run.invalidate_db_cache(opt.ns(), opt.db()),
+			DefineStatement::Table(_) => run.invalidate_db_cache(opt.ns(), opt.db()),
+			DefineStatement::Event(v) => run.invalidate_tb_cache(opt.ns(), opt.db(), &v.what),
+			DefineStatement::Field(v) => run.invalidate_tb_cache(opt.ns(), opt.db(), &v.what),
+			DefineStatement::Index(v) => run.invalidate_tb_cache(opt.ns(), opt.db(), &v.what),
+			DefineStatement::Quota(_) => (),
 		}
+		// Ok all good
+		Ok(res)
 	}
 }
 
@@ -79,6 +114,7 @@ impl fmt::Display for DefineStatement {
 			DefineStatement::Event(v) => write!(f, "{}", v),
 			DefineStatement::Field(v) => write!(f, "{}", v),
 			DefineStatement::Index(v) => write!(f, "{}", v),
+			DefineStatement::Quota(v) => write!(f, "{}", v),
 		}
 	}
 }
@@ -94,6 +130,7 @@ pub fn define(i: &str) -> IResult<&str, DefineStatement> {
 		map(event, DefineStatement::Event),
 		map(field, DefineStatement::Field),
 		map(index, DefineStatement::Index),
+		map(quota, DefineStatement::Quota),
 	))(i)
 }
 
@@ -441,6 +478,8 @@ pub struct DefineScopeStatement {
 	pub session: Option<Duration>,
 	pub signup: Option<Value>,
 	pub signin: Option<Value>,
+	#[serde(default)]
+	pub nonce: u64,
 }
 
 impl DefineScopeStatement {
@@ -513,6 +552,7 @@ fn scope(i: &str) -> IResult<&str, DefineScopeStatement> {
 				DefineScopeOption::Signin(ref v) => Some(v.to_owned()),
 				_ => None,
 			}),
+			nonce: 0,
 		},
 	))
 }
@@ -563,6 +603,8 @@ pub struct DefineTableStatement {
 	pub full: bool,
 	pub view: Option<View>,
 	pub permissions: Permissions,
+	#[serde(default)]
+	pub changefeed: Option<Duration>,
 }
 
 impl DefineTableStatement {
@@ -571,7 +613,7 @@ impl DefineTableStatement {
 		ctx: &Context<'_>,
 		opt: &Options,
 		txn: &Transaction,
-		doc: Option<&Value>,
+		_doc: Option<&Value>,
 	) -> Result<Value, Error> {
 		// Selected DB?
 		opt.needs(Level::Db)?;
@@ -599,9 +641,26 @@ impl DefineTableStatement {
 			}
 			// Release the transaction
 			drop(run);
-			// Force tables to reprocess
-			let opt = &opt.force(true);
-			// Process each foreign table
+			// Populate the view data
+			let (ns, db) = (opt.ns(), opt.db());
+			crate::kvs::Transaction::refresh_view(ctx, opt, txn, ns, db, &self.name).await?;
+		}
+		// Ok all good
+		Ok(Value::None)
+	}
+	/// Process every record in the source tables of a foreign table view,
+	/// so that the view data is written for each of them.
+	pub(crate) async fn populate(
+		&self,
+		ctx: &Context<'_>,
+		opt: &Options,
+		txn: &Transaction,
+		doc: Option<&Value>,
+	) -> Result<(), Error> {
+		// Force tables to reprocess
+		let opt = &opt.force(true);
+		// Process each foreign table
+		if let Some(view) = &self.view {
 			for v in view.what.0.iter() {
 				// Process the view data
 				let stm = UpdateStatement {
@@ -611,8 +670,7 @@ impl DefineTableStatement {
 				stm.compute(ctx, opt, txn, doc).await?;
 			}
 		}
-		// Ok all good
-		Ok(Value::None)
+		Ok(())
 	}
 }
 
@@ -631,6 +689,9 @@ impl fmt::Display for DefineTableStatement {
 		if let Some(ref v) = self.view {
 			write!(f, " {}", v)?
 		}
+		if let Some(ref v) = self.changefeed {
+			write!(f, " CHANGEFEED {}", v)?
+		}
 		if !self.permissions.is_full() {
 			write!(f, " {}", self.permissions)?;
 		}
@@ -675,6 +736,10 @@ fn table(i: &str) -> IResult<&str, DefineTableStatement> {
 					_ => None,
 				})
 				.unwrap_or_default(),
+			changefeed: opts.iter().find_map(|x| match x {
+				DefineTableOption::Changefeed(ref v) => Some(v.to_owned()),
+				_ => None,
+			}),
 		},
 	))
 }
@@ -686,10 +751,18 @@ pub enum DefineTableOption {
 	Schemaless,
 	Schemafull,
 	Permissions(Permissions),
+	Changefeed(Duration),
 }
 
 fn table_opts(i: &str) -> IResult<&str, DefineTableOption> {
-	alt((table_drop, table_view, table_schemaless, table_schemafull, table_permissions))(i)
+	alt((
+		table_drop,
+		table_view,
+		table_schemaless,
+		table_schemafull,
+		table_permissions,
+		table_changefeed,
+	))(i)
 }
 
 fn table_drop(i: &str) -> IResult<&str, DefineTableOption> {
@@ -722,6 +795,14 @@ fn table_permissions(i: &str) -> IResult<&str, DefineTableOption> {
 	Ok((i, DefineTableOption::Permissions(v)))
 }
 
+fn table_changefeed(i: &str) -> IResult<&str, DefineTableOption> {
+	let (i, _) = shouldbespace(i)?;
+	let (i, _) = tag_no_case("CHANGEFEED")(i)?;
+	let (i, _) = shouldbespace(i)?;
+	let (i, v) = duration(i)?;
+	Ok((i, DefineTableOption::Changefeed(v)))
+}
+
 // --------------------------------------------------
 // --------------------------------------------------
 // --------------------------------------------------
@@ -813,6 +894,8 @@ pub struct DefineFieldStatement {
 	pub value: Option<Value>,
 	pub assert: Option<Value>,
 	pub permissions: Permissions,
+	#[serde(default)]
+	pub cascade: bool,
 }
 
 impl DefineFieldStatement {
@@ -840,6 +923,34 @@ impl DefineFieldStatement {
 		// Ok all good
 		Ok(Value::None)
 	}
+	/// Check if the ASSERT clause requires this field to be set.
+	///
+	/// A field is considered required when its assertion contains
+	/// `$value != NONE` or `$value != NULL`, optionally combined
+	/// with other conditions using `AND`.
+	pub(crate) fn is_required(&self) -> bool {
+		fn check(v: &Value) -> bool {
+			match v {
+				Value::Expression(e) => match e.o {
+					Operator::And => check(&e.l) || check(&e.r),
+					Operator::NotEqual => {
+						matches!(&e.l, Value::Param(p) if p.to_string() == "$value")
+							&& matches!(e.r, Value::None | Value::Null)
+					}
+					_ => false,
+				},
+				Value::Subquery(s) => match s.as_ref() {
+					Subquery::Value(v) => check(v),
+					_ => false,
+				},
+				_ => false,
+			}
+		}
+		match &self.assert {
+			Some(v) => check(v),
+			None => false,
+		}
+	}
 }
 
 impl fmt::Display for DefineFieldStatement {
@@ -854,6 +965,9 @@ impl fmt::Display for DefineFieldStatement {
 		if let Some(ref v) = self.assert {
 			write!(f, " ASSERT {}", v)?
 		}
+		if self.cascade {
+			write!(f, " ON DELETE CASCADE")?
+		}
 		if !self.permissions.is_full() {
 			write!(f, " {}", self.permissions)?;
 		}
@@ -897,6 +1011,7 @@ fn field(i: &str) -> IResult<&str, DefineFieldStatement> {
 					_ => None,
 				})
 				.unwrap_or_default(),
+			cascade: opts.iter().any(|x| matches!(x, DefineFieldOption::Cascade)),
 		},
 	))
 }
@@ -906,11 +1021,12 @@ pub enum DefineFieldOption {
 	Kind(Kind),
 	Value(Value),
 	Assert(Value),
+	Cascade,
 	Permissions(Permissions),
 }
 
 fn field_opts(i: &str) -> IResult<&str, DefineFieldOption> {
-	alt((field_kind, field_value, field_assert, field_permissions))(i)
+	alt((field_kind, field_value, field_assert, field_cascade, field_permissions))(i)
 }
 
 fn field_kind(i: &str) -> IResult<&str, DefineFieldOption> {
@@ -937,6 +1053,16 @@ fn field_assert(i: &str) -> IResult<&str, DefineFieldOption> {
 	Ok((i, DefineFieldOption::Assert(v)))
 }
 
+fn field_cascade(i: &str) -> IResult<&str, DefineFieldOption> {
+	let (i, _) = shouldbespace(i)?;
+	let (i, _) = tag_no_case("ON")(i)?;
+	let (i, _) = shouldbespace(i)?;
+	let (i, _) = tag_no_case("DELETE")(i)?;
+	let (i, _) = shouldbespace(i)?;
+	let (i, _) = tag_no_case("CASCADE")(i)?;
+	Ok((i, DefineFieldOption::Cascade))
+}
+
 fn field_permissions(i: &str) -> IResult<&str, DefineFieldOption> {
 	let (i, _) = shouldbespace(i)?;
 	let (i, v) = permissions(i)?;
@@ -976,6 +1102,25 @@ impl DefineIndexStatement {
 		run.add_ns(opt.ns(), opt.strict).await?;
 		run.add_db(opt.ns(), opt.db(), opt.strict).await?;
 		run.add_tb(opt.ns(), opt.db(), &self.what, opt.strict).await?;
+		// Check existing records are unique
+		if self.uniq {
+			let cols: Vec<String> = self.cols.iter().map(|v| v.to_string()).collect();
+			let cols: Vec<&str> = cols.iter().map(String::as_str).collect();
+			let dup = run.check_unique_constraint(opt.ns(), opt.db(), &self.what, &cols).await?;
+			if !dup.is_empty() {
+				return Err(Error::IndexDuplicates {
+					index: self.name.to_string(),
+					duplicates: dup
+						.into_iter()
+						.map(|(v, t)| {
+							let t: Vec<String> = t.iter().map(|v| v.to_string()).collect();
+							format!("{} in {}", Array::from(v), t.join(", "))
+						})
+						.collect::<Vec<_>>()
+						.join("; "),
+				});
+			}
+		}
 		run.set(key, self).await?;
 		// Remove the index data
 		let beg = crate::key::index::prefix(opt.ns(), opt.db(), &self.what, &self.name);
@@ -983,6 +1128,8 @@ impl DefineIndexStatement {
 		run.delr(beg..end, u32::MAX).await?;
 		// Release the transaction
 		drop(run);
+		// Force tables to reprocess
+		let opt = &opt.force(true);
 		// Update the index data
 		let stm = UpdateStatement {
 			what: Values(vec![Value::Table(self.what.clone().into())]),
@@ -1030,3 +1177,188 @@ fn index(i: &str) -> IResult<&str, DefineIndexStatement> {
 		},
 	))
 }
+
+// --------------------------------------------------
+// --------------------------------------------------
+// --------------------------------------------------
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, Store)]
+pub struct DefineQuotaStatement {
+	pub db: Ident,
+	pub records: Option<u64>,
+	pub storage: Option<u64>,
+	pub live: Option<u64>,
+}
+
+impl DefineQuotaStatement {
+	pub(crate) async fn compute(
+		&self,
+		_ctx: &Context<'_>,
+		opt: &Options,
+		txn: &Transaction,
+		_doc: Option<&Value>,
+	) -> Result<Value, Error> {
+		// Selected NS?
+		opt.needs(Level::Ns)?;
+		// Allowed to run?
+		opt.check(Level::Ns)?;
+		// Clone transaction
+		let run = txn.clone();
+		// Claim transaction
+		let mut run = run.lock().await;
+		// Process the statement
+		let val = QuotaDefinition {
+			max_records: self.records,
+			max_storage_bytes: self.storage,
+			max_live_queries: self.live,
+		};
+		run.add_ns(opt.ns(), opt.strict).await?;
+		run.set_quota(opt.ns(), &self.db, &val).await?;
+		// Ok all good
+		Ok(Value::None)
+	}
+}
+
+impl fmt::Display for DefineQuotaStatement {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "DEFINE QUOTA ON DATABASE {}", self.db)?;
+		if let Some(v) = self.records {
+			write!(f, " RECORDS {}", v)?
+		}
+		if let Some(v) = self.storage {
+			write!(f, " STORAGE {}", v)?
+		}
+		if let Some(v) = self.live {
+			write!(f, " LIVE {}", v)?
+		}
+		Ok(())
+	}
+}
+
+fn quota(i: &str) -> IResult<&str, DefineQuotaStatement> {
+	let (i, _) = tag_no_case("DEFINE")(i)?;
+	let (i, _) = shouldbespace(i)?;
+	let (i, _) = tag_no_case("QUOTA")(i)?;
+	let (i, _) = shouldbespace(i)?;
+	let (i, _) = tag_no_case("ON")(i)?;
+	let (i, _) = shouldbespace(i)?;
+	let (i, _) = alt((tag_no_case("DB"), tag_no_case("DATABASE")))(i)?;
+	let (i, _) = shouldbespace(i)?;
+	let (i, db) = ident(i)?;
+	let (i, opts) = many0(quota_opts)(i)?;
+	Ok((
+		i,
+		DefineQuotaStatement {
+			db,
+			records: opts.iter().find_map(|x| match x {
+				DefineQuotaOption::Records(v) => Some(*v),
+				_ => None,
+			}),
+			storage: opts.iter().find_map(|x| match x {
+				DefineQuotaOption::Storage(v) => Some(*v),
+				_ => None,
+			}),
+			live: opts.iter().find_map(|x| match x {
+				DefineQuotaOption::Live(v) => Some(*v),
+				_ => None,
+			}),
+		},
+	))
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DefineQuotaOption {
+	Records(u64),
+	Storage(u64),
+	Live(u64),
+}
+
+fn quota_opts(i: &str) -> IResult<&str, DefineQuotaOption> {
+	alt((quota_records, quota_storage, quota_live))(i)
+}
+
+fn quota_records(i: &str) -> IResult<&str, DefineQuotaOption> {
+	let (i, _) = shouldbespace(i)?;
+	let (i, _) = tag_no_case("RECORDS")(i)?;
+	let (i, _) = shouldbespace(i)?;
+	let (i, v) = take_u64(i)?;
+	Ok((i, DefineQuotaOption::Records(v)))
+}
+
+fn quota_storage(i: &str) -> IResult<&str, DefineQuotaOption> {
+	let (i, _) = shouldbespace(i)?;
+	let (i, _) = tag_no_case("STORAGE")(i)?;
+	let (i, _) = shouldbespace(i)?;
+	let (i, v) = take_u64(i)?;
+	Ok((i, DefineQuotaOption::Storage(v)))
+}
+
+fn quota_live(i: &str) -> IResult<&str, DefineQuotaOption> {
+	let (i, _) = shouldbespace(i)?;
+	let (i, _) = tag_no_case("LIVE")(i)?;
+	let (i, _) = shouldbespace(i)?;
+	let (i, v) = take_u64(i)?;
+	Ok((i, DefineQuotaOption::Live(v)))
+}
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+
+	// A scope definition, as it was stored before the nonce was added
+	#[derive(Serialize, Deserialize, Store)]
+	struct DefineScopeStatementWithoutNonce {
+		name: Ident,
+		code: String,
+		session: Option<Duration>,
+		signup: Option<Value>,
+		signin: Option<Value>,
+	}
+
+	// A field definition, as it was stored before cascading deletes were added
+	#[derive(Serialize, Deserialize, Store)]
+	struct DefineFieldStatementWithoutCascade {
+		name: Idiom,
+		what: Ident,
+		kind: Option<Kind>,
+		value: Option<Value>,
+		assert: Option<Value>,
+		permissions: Permissions,
+	}
+
+	#[test]
+	fn define_scope_decode_without_nonce() {
+		let sql = "DEFINE SCOPE test SESSION 1h SIGNUP (CREATE user) SIGNIN (SELECT * FROM user)";
+		let (_, old) = scope(sql).unwrap();
+		let enc: Vec<u8> = DefineScopeStatementWithoutNonce {
+			name: old.name.clone(),
+			code: old.code.clone(),
+			session: old.session.clone(),
+			signup: old.signup.clone(),
+			signin: old.signin.clone(),
+		}
+		.into();
+		let dec = DefineScopeStatement::from(enc);
+		assert_eq!(dec, old);
+		assert_eq!(dec.nonce, 0);
+	}
+
+	#[test]
+	fn define_field_decode_without_cascade() {
+		let sql = "DEFINE FIELD author ON post TYPE record(person) VALUE $value OR person:tobie ASSERT $value != NONE";
+		let (_, old) = field(sql).unwrap();
+		let enc: Vec<u8> = DefineFieldStatementWithoutCascade {
+			name: old.name.clone(),
+			what: old.what.clone(),
+			kind: old.kind.clone(),
+			value: old.value.clone(),
+			assert: old.assert.clone(),
+			permissions: old.permissions.clone(),
+		}
+		.into();
+		let dec = DefineFieldStatement::from(enc);
+		assert_eq!(dec, old);
+		assert!(!dec.cascade);
+	}
+}
@@ -28,6 +28,10 @@ pub struct LiveStatement {
 	pub what: Value,
 	pub cond: Option<Cond>,
 	pub fetch: Option<Fetchs>,
+	#[serde(default)]
+	pub paused: bool,
+	#[serde(default)]
+	pub paused_at: u64,
 }
 
 impl LiveStatement {
@@ -51,6 +55,8 @@ impl LiveStatement {
 		// Process the live query table
 		match self.what.compute(ctx, opt, txn, doc).await? {
 			Value::Table(tb) => {
+				// Check the database quota
+				run.enforce_live_quota(opt.ns(), opt.db()).await?;
 				// Insert the live query
 				let key = crate::key::lq::new(opt.ns(), opt.db(), &self.id);
 				run.putc(key, tb.as_str(), None).await?;
@@ -100,6 +106,41 @@ pub fn live(i: &str) -> IResult<&str, LiveStatement> {
 			what,
 			cond,
 			fetch,
+			paused: false,
+			paused_at: 0,
 		},
 	))
 }
+
+#[cfg(test)]
+mod tests {
+
+	use super::*;
+
+	// A live query, as it was stored before live queries could be paused
+	#[derive(Serialize, Deserialize, Store)]
+	struct LiveStatementWithoutPauseState {
+		id: Uuid,
+		expr: Fields,
+		what: Value,
+		cond: Option<Cond>,
+		fetch: Option<Fetchs>,
+	}
+
+	#[test]
+	fn live_decode_without_pause_state() {
+		let (_, old) = live("LIVE SELECT * FROM person WHERE age > 18").unwrap();
+		let enc: Vec<u8> = LiveStatementWithoutPauseState {
+			id: old.id.clone(),
+			expr: old.expr.clone(),
+			what: old.what.clone(),
+			cond: old.cond.clone(),
+			fetch: old.fetch.clone(),
+		}
+		.into();
+		let dec = LiveStatement::from(enc);
+		assert_eq!(dec, old);
+		assert!(!dec.paused);
+		assert_eq!(dec.paused_at, 0);
+	}
+}
@@ -1,3 +1,4 @@
+pub(crate) mod analyze;
 pub(crate) mod begin;
 pub(crate) mod cancel;
 pub(crate) mod commit;
@@ -18,6 +19,7 @@ pub(crate) mod set;
 pub(crate) mod update;
 pub(crate) mod yuse;
 
+pub use self::analyze::AnalyzeStatement;
 pub use self::begin::BeginStatement;
 pub use self::cancel::CancelStatement;
 pub use self::commit::CommitStatement;
@@ -44,6 +46,8 @@ pub use self::define::DefineIndexStatement;
 pub use self::define::DefineLoginOption;
 pub use self::define::DefineLoginStatement;
 pub use self::define::DefineNamespaceStatement;
+pub use self::define::DefineQuotaOption;
+pub use self::define::DefineQuotaStatement;
 pub use self::define::DefineScopeOption;
 pub use self::define::DefineScopeStatement;
 pub use self::define::DefineStatement;
@@ -37,7 +37,8 @@ impl RemoveStatement {
 		txn: &Transaction,
 		doc: Option<&Value>,
 	) -> Result<Value, Error> {
-		match self {
+		// Process the statement
+		let res = match self {
 			RemoveStatement::Namespace(ref v) => v.compute(ctx, opt, txn, doc).await,
 			RemoveStatement::Database(ref v) => v.compute(ctx, opt, txn, doc).await,
 			RemoveStatement::Login(ref v) => v.compute(ctx, opt, txn, doc).await,
@@ -47,7 +48,33 @@ impl RemoveStatement {
 			RemoveStatement::Event(ref v) => v.compute(ctx, opt, txn, doc).await,
 			RemoveStatement::Field(ref v) => v.compute(ctx, opt, txn, doc).await,
 			RemoveStatement::Index(ref v) => v.compute(ctx, opt, txn, doc).await,
+		}?;
+		// Clone transaction
+		let run = txn.clone();
+		// Claim transaction
+		let mut run = run.lock().await;
+		// Clear any cached definitions
+		match self {
+			RemoveStatement::Namespace(v) => run.invalidate_ns_cache(&v.name),
+			RemoveStatement::Database(v) => run.invalidate_db_cache(opt.ns(), &v.name),
+			RemoveStatement::Login(v) => match v.base {
+				Base::Kv => run.invalidate_all_caches(),
+				Base::Ns => run.invalidate_ns_cache(opt.ns()),
+				Base::Db => run.invalidate_db_cache(opt.ns(), opt.db()),
+			},
+			RemoveStatement::Token(v) => match v.base {
+				Base::Kv => run.invalidate_all_caches(),
+				Base::Ns => run.invalidate_ns_cache(opt.ns()),
+				Base::Db => run.invalidate_db_cache(opt.ns(), opt.db()),
+			},
+			RemoveStatement::Scope(_) => run.invalidate_db_cache(opt.ns(), opt.db()),
+			RemoveStatement::Table(_) => run.invalidate_db_cache(opt.ns(), opt.db()),
+			RemoveStatement::Event(v) => run.invalidate_tb_cache(opt.ns(), opt.db(), &v.what),
+			RemoveStatement::Field(v) => run.invalidate_tb_cache(opt.ns(), opt.db(), &v.what),
+			RemoveStatement::Index(v) => run.invalidate_tb_cache(opt.ns(), opt.db(), &v.what),
 		}
+		// Ok all good
+		Ok(res)
 	}
 }
 
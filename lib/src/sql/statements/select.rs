@@ -11,6 +11,7 @@ use crate::sql::cond::{cond, Cond};
 use crate::sql::error::IResult;
 use crate::sql::fetch::{fetch, Fetchs};
 use crate::sql::field::{fields, Field, Fields};
+use crate::sql::function::Function;
 use crate::sql::group::{group, Groups};
 use crate::sql::limit::{limit, Limit};
 use crate::sql::order::{order, Orders};
@@ -59,6 +60,27 @@ impl SelectStatement {
 		}
 	}
 
+	/// Check if the statement only counts the records in one or more tables
+	fn is_count_only(&self) -> bool {
+		// Check the selected fields
+		let count = match self.expr.0.as_slice() {
+			[Field::Alone(Value::Function(f))] => match f.as_ref() {
+				Function::Normal(name, args) => name == "count" && args.is_empty(),
+				_ => false,
+			},
+			_ => false,
+		};
+		// Check the statement clauses
+		count
+			&& self.cond.is_none()
+			&& self.split.is_none()
+			&& self.group.is_none()
+			&& self.order.is_none()
+			&& self.fetch.is_none()
+			&& self.version.is_none()
+			&& self.what.0.iter().all(|v| matches!(v, Value::Table(_)))
+	}
+
 	pub(crate) fn writeable(&self) -> bool {
 		if self.expr.iter().any(|v| match v {
 			Field::All => false,
@@ -84,6 +106,31 @@ impl SelectStatement {
 		opt.needs(Level::Db)?;
 		// Allowed to run?
 		opt.check(Level::No)?;
+		// Count the table records without fetching them
+		if self.is_count_only() && !(opt.perms && opt.auth.perms()) {
+			// Clone transaction
+			let run = txn.clone();
+			// Claim transaction
+			let mut run = run.lock().await;
+			// Count the records in each table
+			let mut num = 0;
+			for w in self.what.0.iter() {
+				if let Value::Table(v) = w {
+					run.check_ns_db_tb(opt.ns(), opt.db(), v, opt.strict).await?;
+					let beg = crate::key::thing::prefix(opt.ns(), opt.db(), v);
+					let end = crate::key::thing::suffix(opt.ns(), opt.db(), v);
+					num += run.count_range(beg..end).await? as usize;
+				}
+			}
+			// Apply the start and limit clauses
+			let mut num = num.saturating_sub(self.start());
+			if self.limit.is_some() {
+				num = std::cmp::min(num, self.limit());
+			}
+			// Each record is counted once
+			let val = Value::from(map! { String::from("count") => Value::from(1) });
+			return Ok(vec![val; num].into());
+		}
 		// Create a new iterator
 		let mut i = Iterator::new();
 		// Ensure futures are processed
@@ -0,0 +1,118 @@
+use chrono::Utc;
+use std::time::Duration;
+use surrealdb::sql::Thing;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+#[tokio::test]
+async fn table_access_counted_in_readonly_transactions() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let rid = Thing::from(("person", "tobie"));
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	txn.get_record("test", "test", &rid).await?;
+	txn.get_record("test", "test", &rid).await?;
+	txn.cancel().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.get_table_access_count("test", "test", "person").await?, 2);
+	txn.cancel().await?;
+	// Store the counted accesses
+	dbs.flush_table_access().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	txn.get_record("test", "test", &rid).await?;
+	assert_eq!(txn.get_table_access_count("test", "test", "person").await?, 3);
+	txn.cancel().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.reset_table_access_count("test", "test", "person").await?;
+	assert_eq!(txn.get_table_access_count("test", "test", "person").await?, 0);
+	txn.commit().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn table_access_stored_by_maintenance() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let rid = Thing::from(("person", "tobie"));
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	for _ in 0..1000 {
+		txn.get_record("test", "test", &rid).await?;
+	}
+	txn.cancel().await?;
+	// Store the counted accesses
+	dbs.tick().await?;
+	dbs.tick().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.get_table_access_count("test", "test", "person").await?, 1000);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn access_log_records_readonly_transactions() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let rid = Thing::from(("person", "tobie"));
+	let from = Utc::now();
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?.with_access_log();
+	txn.get_record("test", "test", &rid).await?;
+	txn.cancel().await?;
+	// The entry is visible before it is stored
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let log = txn.get_access_log("test", "test", from, Utc::now()).await?;
+	assert_eq!(log.len(), 1);
+	assert_eq!(log[0].operation, "get");
+	txn.cancel().await?;
+	// Store the recorded entries
+	dbs.flush_access_log().await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let log = txn.get_access_log("test", "test", from, Utc::now()).await?;
+	assert_eq!(log.len(), 1);
+	txn.purge_access_log("test", "test", Utc::now()).await?;
+	assert!(txn.get_access_log("test", "test", from, Utc::now()).await?.is_empty());
+	txn.commit().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn cold_tables_not_recently_accessed() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute("DEFINE TABLE person; DEFINE TABLE team;", &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.reset_table_access_count("test", "test", "person").await?;
+	txn.reset_table_access_count("test", "test", "team").await?;
+	txn.commit().await?;
+	// Tables without any recorded access are cold
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.scan_cold_tables("test", "test", Duration::from_secs(3600)).await?;
+	assert_eq!(res, vec!["person".to_owned(), "team".to_owned()]);
+	txn.get_record("test", "test", &Thing::from(("person", "tobie"))).await?;
+	txn.cancel().await?;
+	// Pending and stored accesses are both taken into account
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.scan_cold_tables("test", "test", Duration::from_secs(3600)).await?;
+	assert_eq!(res, vec!["team".to_owned()]);
+	txn.cancel().await?;
+	dbs.flush_table_access().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.scan_cold_tables("test", "test", Duration::from_secs(3600)).await?;
+	assert_eq!(res, vec!["team".to_owned()]);
+	txn.cancel().await?;
+	// Accesses older than the threshold are cold
+	std::thread::sleep(Duration::from_millis(10));
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.scan_cold_tables("test", "test", Duration::from_millis(1)).await?;
+	assert_eq!(res, vec!["person".to_owned(), "team".to_owned()]);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
@@ -0,0 +1,27 @@
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::TransactionType;
+
+#[tokio::test]
+async fn kill_open_transaction() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let mut other = dbs.transaction(TransactionType::Read, false).await?;
+	let list = other.list_active_transactions().await?;
+	assert_eq!(list.len(), 2);
+	let id = list.iter().find(|v| v.write).unwrap().id;
+	other.kill_transaction(id).await?;
+	other.cancel().await?;
+	// The killed transaction fails on its next operation
+	assert!(matches!(txn.set("test", "test").await, Err(Error::TxKilled)));
+	assert!(txn.closed().await);
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.list_active_transactions().await?.len(), 1);
+	// Transactions which are not open can not be killed
+	assert!(matches!(txn.kill_transaction(id).await, Err(Error::TxNotFound)));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
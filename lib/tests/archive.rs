@@ -0,0 +1,101 @@
+use surrealdb::sql::Value;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+async fn setup(dbs: &Datastore) -> Result<(), Error> {
+	let sql = "
+		USE NS test DB app;
+		CREATE person:1;
+		CREATE person:2;
+	";
+	let ses = Session::for_kv();
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	Ok(())
+}
+
+async fn records(dbs: &Datastore, ns: &str, db: &str) -> Result<usize, Error> {
+	let sql = "SELECT * FROM person";
+	let ses = Session::for_kv().with_ns(ns).with_db(db);
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	match res.into_iter().next().unwrap().result? {
+		Value::Array(v) => Ok(v.len()),
+		v => panic!("unexpected value {}", v),
+	}
+}
+
+// Archive the test database, returning the name of the archived database
+async fn archive(dbs: &Datastore) -> Result<String, Error> {
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.archive_database("test", "app", "archive").await?;
+	txn.commit().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.all_db("archive").await?;
+	txn.cancel().await?;
+	assert_eq!(res.len(), 1);
+	Ok(res[0].name.to_string())
+}
+
+#[tokio::test]
+async fn archive_database_moves_data() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	setup(&dbs).await?;
+	let name = archive(&dbs).await?;
+	assert!(name.starts_with("app_archived_"));
+	// The original database is removed
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert!(matches!(txn.get_db("test", "app").await, Err(Error::DbNotFound)));
+	assert!(txn.is_archived("archive", &name).await?);
+	assert!(!txn.is_archived("test", "app").await?);
+	txn.cancel().await?;
+	assert_eq!(records(&dbs, "archive", &name).await?, 2);
+	// Only existing databases can be archived
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert!(matches!(txn.archive_database("test", "app", "archive").await, Err(Error::DbNotFound)));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn restore_archived_database_with_and_without_overwrite() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	setup(&dbs).await?;
+	let name = archive(&dbs).await?;
+	// The restored database is not marked as archived
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.restore_archived_database("archive", &name, "test", "app", false).await?;
+	assert!(!txn.is_archived("test", "app").await?);
+	assert!(txn.is_archived("archive", &name).await?);
+	txn.commit().await?;
+	assert_eq!(records(&dbs, "test", "app").await?, 2);
+	assert_eq!(records(&dbs, "archive", &name).await?, 2);
+	// An existing database is only replaced when overwriting
+	let ses = Session::for_kv().with_ns("test").with_db("app");
+	let res = dbs.execute("CREATE person:3;", &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert!(matches!(
+		txn.restore_archived_database("archive", &name, "test", "app", false).await,
+		Err(Error::DbExists { .. })
+	));
+	txn.cancel().await?;
+	assert_eq!(records(&dbs, "test", "app").await?, 3);
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.restore_archived_database("archive", &name, "test", "app", true).await?;
+	txn.commit().await?;
+	assert_eq!(records(&dbs, "test", "app").await?, 2);
+	// The archived database must exist
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert!(matches!(
+		txn.restore_archived_database("archive", "other", "test", "app", true).await,
+		Err(Error::DbNotFound)
+	));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
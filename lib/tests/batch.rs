@@ -0,0 +1,97 @@
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::TransactionType;
+
+#[tokio::test]
+async fn set_batch_writes_all_keys() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.set_batch(vec![("a", "1"), ("b", "2"), ("c", "3")]).await?;
+	txn.commit().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.mget(vec!["a", "b", "c"]).await?;
+	assert_eq!(res, vec![Some(b"1".to_vec()), Some(b"2".to_vec()), Some(b"3".to_vec())]);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn set_batch_rejects_invalid_keys() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let res = txn.set_batch(vec![("a", "1"), ("", "2"), ("c", "3")]).await;
+	assert!(matches!(res, Err(Error::TxKeyEmpty)));
+	assert_eq!(txn.get("a").await?, None);
+	assert_eq!(txn.get("c").await?, None);
+	txn.cancel().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.set_batch(vec![("a", "1")]).await;
+	assert!(matches!(res, Err(Error::TxReadonly)));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn scan_rev_returns_the_last_keys() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let keys: Vec<String> = (0..2500).map(|i| format!("k{:04}", i)).collect();
+	txn.set_batch(keys.iter().map(|k| (k.as_str(), "v")).collect()).await?;
+	txn.commit().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.scan_rev("k0100".."k2400", 3).await?;
+	let res: Vec<Vec<u8>> = res.into_iter().map(|(k, _)| k).collect();
+	assert_eq!(res, vec![b"k2399".to_vec(), b"k2398".to_vec(), b"k2397".to_vec()]);
+	let res = txn.scan_rev("k0000".."k9999", 1200).await?;
+	assert_eq!(res.len(), 1200);
+	assert_eq!(res[0].0, b"k2499".to_vec());
+	assert_eq!(res[1199].0, b"k1300".to_vec());
+	assert!(txn.scan_rev("k0000".."k9999", 0).await?.is_empty());
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn scan_keys_returns_keys_in_order() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.set_batch(vec![("a", "1"), ("b", "2"), ("c", "3"), ("d", "4")]).await?;
+	txn.commit().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.scan_keys("b".."d", 10).await?;
+	assert_eq!(res, vec![b"b".to_vec(), b"c".to_vec()]);
+	let res = txn.scan_keys("a".."z", 3).await?;
+	assert_eq!(res, vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]);
+	assert!(txn.scan_keys("e".."z", 10).await?.is_empty());
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn count_range_and_prefix_across_batches() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let keys: Vec<String> = (0..2500).map(|i| format!("k{:04}", i)).collect();
+	txn.set_batch(keys.iter().map(|k| (k.as_str(), "v")).collect()).await?;
+	txn.set_batch(vec![("j", "v"), ("l", "v")]).await?;
+	txn.commit().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.count_range("k0100".."k2400").await?, 2300);
+	assert_eq!(txn.count_range("a".."z").await?, 2502);
+	assert_eq!(txn.count_range("x".."z").await?, 0);
+	assert_eq!(txn.count_prefix("k").await?, 2500);
+	assert_eq!(txn.count_prefix("k01").await?, 100);
+	assert_eq!(txn.count_prefix("m").await?, 0);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
@@ -0,0 +1,164 @@
+mod parse;
+use parse::Parse;
+use std::collections::HashMap;
+use surrealdb::sql::Value;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::QuerySchemaRefs;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+#[tokio::test]
+async fn cache_smaller_than_working_set() -> Result<(), Error> {
+	let sql = "
+		BEGIN TRANSACTION;
+		DEFINE TABLE one SCHEMALESS;
+		DEFINE TABLE two SCHEMALESS;
+		DEFINE FIELD name ON one TYPE string;
+		CREATE one:1 SET name = 'one';
+		CREATE two:1 SET name = 'two';
+		SELECT * FROM one, two;
+		INFO FOR TABLE one;
+		COMMIT TRANSACTION;
+	";
+	let dbs = Datastore::new("memory").await?.with_cache_capacity(1);
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = &mut dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 7);
+	//
+	let tmp = res.remove(0).result;
+	assert!(tmp.is_ok());
+	//
+	let tmp = res.remove(0).result;
+	assert!(tmp.is_ok());
+	//
+	let tmp = res.remove(0).result;
+	assert!(tmp.is_ok());
+	//
+	let tmp = res.remove(0).result?;
+	let val = Value::parse("[{ id: one:1, name: 'one' }]");
+	assert_eq!(tmp, val);
+	//
+	let tmp = res.remove(0).result?;
+	let val = Value::parse("[{ id: two:1, name: 'two' }]");
+	assert_eq!(tmp, val);
+	//
+	let tmp = res.remove(0).result?;
+	let val = Value::parse("[{ id: one:1, name: 'one' }, { id: two:1, name: 'two' }]");
+	assert_eq!(tmp, val);
+	//
+	let tmp = res.remove(0).result?;
+	let val = Value::parse(
+		"{
+			ev: {},
+			fd: { name: 'DEFINE FIELD name ON one TYPE string' },
+			ft: {},
+			ix: {},
+		}",
+	);
+	assert_eq!(tmp, val);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn rename_index_clears_cached_indexes() -> Result<(), Error> {
+	let sql = "
+		DEFINE INDEX name ON person FIELDS name UNIQUE;
+		CREATE person:tobie SET name = 'Tobie';
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 2);
+	// Write to the table after renaming the cached index
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert_eq!(txn.all_ix("test", "test", "person").await?.len(), 1);
+	txn.rename_index("test", "test", "person", "name", "fullname").await?;
+	let ixs = txn.all_ix("test", "test", "person").await?;
+	assert_eq!(ixs.len(), 1);
+	assert_eq!(ixs[0].name.to_string(), "fullname");
+	txn.import("test", "test", b"CREATE person:jaime CONTENT { name: 'Jaime' };").await?;
+	txn.commit().await?;
+	// The new record is indexed under the new name
+	let sql = "CREATE person:other SET name = 'Jaime';";
+	let res = &mut dbs.execute(&sql, &ses, None, false).await?;
+	let tmp = res.remove(0).result;
+	assert!(matches!(tmp, Err(Error::IndexExists { index, .. }) if index == "fullname"));
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn prefetch_schema_for_query_caches_definitions() -> Result<(), Error> {
+	let sql = "
+		DEFINE TABLE person SCHEMAFULL;
+		DEFINE FIELD name ON person TYPE string;
+		DEFINE INDEX name ON person FIELDS name;
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 3);
+	//
+	let refs = QuerySchemaRefs {
+		tables: vec!["person".to_owned(), "other".to_owned()],
+		fields: HashMap::from([("person".to_owned(), vec!["name".to_owned()])]),
+		indexes: HashMap::from([("person".to_owned(), vec!["name".to_owned()])]),
+	};
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.prefetch_schema_for_query("test", "test", &refs).await?;
+	// Remove the stored definitions, without clearing the cache
+	txn.del(b"/*test\x00*test\x00!tbperson\x00".to_vec()).await?;
+	txn.del(b"/*test\x00*test\x00*person\x00!fdname\x00".to_vec()).await?;
+	txn.del(b"/*test\x00*test\x00*person\x00!ixname\x00".to_vec()).await?;
+	// The definitions are served from the cache
+	assert!(txn.get_and_cache_tb("test", "test", "person").await.is_ok());
+	assert_eq!(txn.all_fd("test", "test", "person").await?.len(), 1);
+	assert_eq!(txn.all_ix("test", "test", "person").await?.len(), 1);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn invalidate_cached_definitions() -> Result<(), Error> {
+	let sql = "
+		DEFINE TABLE person SCHEMAFULL;
+		DEFINE TABLE team SCHEMALESS;
+		DEFINE FIELD name ON person TYPE string;
+		DEFINE INDEX name ON person FIELDS name;
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 4);
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert_eq!(txn.all_db("test").await?.len(), 1);
+	assert_eq!(txn.all_tb("test", "test").await?.len(), 2);
+	assert_eq!(txn.all_fd("test", "test", "person").await?.len(), 1);
+	assert_eq!(txn.all_ix("test", "test", "person").await?.len(), 1);
+	// Remove the stored definitions of a table, without clearing the cache
+	txn.del(b"/*test\x00*test\x00*person\x00!fdname\x00".to_vec()).await?;
+	txn.del(b"/*test\x00*test\x00*person\x00!ixname\x00".to_vec()).await?;
+	assert_eq!(txn.all_fd("test", "test", "person").await?.len(), 1);
+	txn.invalidate_tb_cache("test", "test", "person");
+	assert_eq!(txn.all_fd("test", "test", "person").await?.len(), 0);
+	assert_eq!(txn.all_ix("test", "test", "person").await?.len(), 0);
+	// Remove the stored definition of a table, without clearing the cache
+	assert_eq!(txn.all_tb("test", "test").await?.len(), 2);
+	txn.del(b"/*test\x00*test\x00!tbteam\x00".to_vec()).await?;
+	assert_eq!(txn.all_tb("test", "test").await?.len(), 2);
+	txn.invalidate_db_cache("test", "test");
+	assert_eq!(txn.all_tb("test", "test").await?.len(), 1);
+	// Remove the stored definition of a database, without clearing the cache
+	assert_eq!(txn.all_db("test").await?.len(), 1);
+	txn.del(b"/*test\x00!dbtest\x00".to_vec()).await?;
+	assert_eq!(txn.all_db("test").await?.len(), 1);
+	txn.invalidate_all_caches();
+	assert_eq!(txn.all_db("test").await?.len(), 0);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
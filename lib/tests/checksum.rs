@@ -0,0 +1,60 @@
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+async fn run(dbs: &Datastore, db: &str, sql: &str) -> Result<(), Error> {
+	let ses = Session::for_kv().with_ns("test").with_db(db);
+	for v in dbs.execute(sql, &ses, None, false).await?.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	Ok(())
+}
+
+#[tokio::test]
+async fn table_checksum_of_identical_tables() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let sql = "
+		CREATE person:1 SET name = 'Tobie';
+		CREATE person:2 SET name = 'Jaime';
+	";
+	run(&dbs, "one", sql).await?;
+	run(&dbs, "two", sql).await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let one = txn.compute_table_checksum("test", "one", "person").await?;
+	let two = txn.compute_table_checksum("test", "two", "person").await?;
+	txn.cancel().await?;
+	assert_eq!(one, two);
+	// A changed value changes the checksum
+	run(&dbs, "two", "UPDATE person:2 SET name = 'Lana';").await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let two = txn.compute_table_checksum("test", "two", "person").await?;
+	txn.cancel().await?;
+	assert_ne!(one, two);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn database_checksum_of_identical_databases() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let sql = "CREATE person:1 SET name = 'Tobie'; CREATE animal:1 SET name = 'Rex';";
+	run(&dbs, "one", sql).await?;
+	let sql = "CREATE animal:1 SET name = 'Rex'; CREATE person:1 SET name = 'Tobie';";
+	run(&dbs, "two", sql).await?;
+	let sql = "CREATE person:1 SET name = 'Rex'; CREATE animal:1 SET name = 'Tobie';";
+	run(&dbs, "three", sql).await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let one = txn.compute_database_checksum("test", "one").await?;
+	let two = txn.compute_database_checksum("test", "two").await?;
+	let three = txn.compute_database_checksum("test", "three").await?;
+	txn.cancel().await?;
+	// The order in which the tables were created does not matter
+	assert_eq!(one, two);
+	// The records are checked along with the tables they belong to
+	assert_ne!(one, three);
+	//
+	Ok(())
+}
@@ -0,0 +1,119 @@
+mod parse;
+use chrono::DateTime;
+use chrono::Utc;
+use parse::Parse;
+use std::time::Duration;
+use surrealdb::sql::Thing;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+// Parse an RFC 3339 datetime
+fn at(v: &str) -> DateTime<Utc> {
+	v.parse().unwrap()
+}
+
+async fn datastore(sql: &str) -> Result<Datastore, Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = format!(
+		"{}
+		CREATE event:1 SET at = '2022-01-01T00:00:00Z';
+		CREATE event:2 SET at = '2022-01-01T00:30:00.500000000Z';
+		CREATE event:3 SET at = '2022-01-01T01:00:00Z';
+		CREATE event:4 SET at = '2022-01-01T02:15:00Z';
+		CREATE event:5 SET at = 'never';
+		CREATE event:6;",
+		sql
+	);
+	for v in dbs.execute(&sql, &ses, None, false).await?.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	Ok(dbs)
+}
+
+// Scan the events within a range of datetimes
+async fn scan(dbs: &Datastore, from: &str, to: &str, limit: u32) -> Result<Vec<Thing>, Error> {
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn
+		.scan_by_datetime_range("test", "test", "event", "at", at(from), at(to), limit)
+		.await?
+		.into_iter()
+		.map(|(t, _)| t)
+		.collect();
+	txn.cancel().await?;
+	Ok(res)
+}
+
+// Count the events in each window within a range of datetimes
+async fn count(
+	dbs: &Datastore,
+	from: &str,
+	to: &str,
+	window: Duration,
+) -> Result<Vec<(DateTime<Utc>, u64)>, Error> {
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.count_by_window("test", "test", "event", "at", at(from), at(to), window).await?;
+	txn.cancel().await?;
+	Ok(res)
+}
+
+#[tokio::test]
+async fn scan_by_datetime_range_with_and_without_index() -> Result<(), Error> {
+	for sql in ["", "DEFINE INDEX at ON event COLUMNS at;"] {
+		let dbs = datastore(sql).await?;
+		// Both bounds are inclusive
+		let res = scan(&dbs, "2022-01-01T00:00:00Z", "2022-01-01T01:00:00Z", u32::MAX).await?;
+		let val = vec![Thing::parse("event:1"), Thing::parse("event:2"), Thing::parse("event:3")];
+		assert_eq!(res, val);
+		let res = scan(&dbs, "2022-01-01T00:00:00Z", "2022-01-01T01:00:00Z", 2).await?;
+		assert_eq!(res, vec![Thing::parse("event:1"), Thing::parse("event:2")]);
+		// Bounds are compared to the nanosecond
+		let res = scan(&dbs, "2022-01-01T00:30:00.5Z", "2022-01-01T00:59:59Z", u32::MAX).await?;
+		assert_eq!(res, vec![Thing::parse("event:2")]);
+		let res = scan(&dbs, "2022-01-01T00:30:00.6Z", "2022-01-01T00:59:59Z", u32::MAX).await?;
+		assert!(res.is_empty());
+		let res = scan(&dbs, "2022-01-01T00:00:01Z", "2022-01-01T00:30:00.4Z", u32::MAX).await?;
+		assert!(res.is_empty());
+	}
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn count_by_window_with_partial_window() -> Result<(), Error> {
+	let dbs = datastore("").await?;
+	let hour = Duration::from_secs(3600);
+	// The final window ends with the range
+	let res = count(&dbs, "2022-01-01T00:00:00Z", "2022-01-01T02:15:00Z", hour).await?;
+	let val = vec![
+		(at("2022-01-01T00:00:00Z"), 2),
+		(at("2022-01-01T01:00:00Z"), 1),
+		(at("2022-01-01T02:00:00Z"), 1),
+	];
+	assert_eq!(res, val);
+	let res = count(&dbs, "2022-01-01T00:00:00Z", "2022-01-01T02:00:00Z", hour).await?;
+	let val = vec![(at("2022-01-01T00:00:00Z"), 2), (at("2022-01-01T01:00:00Z"), 1)];
+	assert_eq!(res, val);
+	// A range shorter than the window has a single window
+	let res = count(&dbs, "2022-01-01T00:00:00Z", "2022-01-01T00:10:00Z", hour).await?;
+	assert_eq!(res, vec![(at("2022-01-01T00:00:00Z"), 1)]);
+	// An empty range or window has no windows
+	let res = count(&dbs, "2022-01-01T01:00:00Z", "2022-01-01T00:00:00Z", hour).await?;
+	assert!(res.is_empty());
+	let res = count(&dbs, "2022-01-01T00:00:00Z", "2022-01-01T01:00:00Z", Duration::ZERO).await?;
+	assert!(res.is_empty());
+	// A window which starts too far from the beginning of the range is an error
+	let window = Duration::from_secs(300 * 365 * 86400);
+	let res = count(&dbs, "1700-01-01T00:00:00Z", "2300-01-01T00:00:00Z", window).await;
+	assert!(matches!(
+		res,
+		Err(Error::WindowOutOfRange {
+			window: 1,
+			..
+		})
+	));
+	//
+	Ok(())
+}
@@ -1,9 +1,11 @@
 mod parse;
 use parse::Parse;
+use surrealdb::sql::Thing;
 use surrealdb::sql::Value;
 use surrealdb::Datastore;
 use surrealdb::Error;
 use surrealdb::Session;
+use surrealdb::TransactionType;
 
 #[tokio::test]
 async fn define_statement_namespace() -> Result<(), Error> {
@@ -665,3 +667,179 @@ async fn define_statement_index_multiple_unique() -> Result<(), Error> {
 	//
 	Ok(())
 }
+
+#[tokio::test]
+async fn define_statement_index_entry_errors() -> Result<(), Error> {
+	let sql = "
+		DEFINE INDEX test ON user FIELDS email UNIQUE;
+		CREATE user:1 SET email = 'test@surrealdb.com';
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 2);
+	//
+	let rid = Thing::from(("user", "2"));
+	let val = Value::parse("{ email: 'test@surrealdb.com' }");
+	// Duplicate values are reported as an existing entry
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let ix = txn.all_ix("test", "test", "user").await?[0].clone();
+	let res = txn.create_composite_index_entry("test", "test", "user", &ix, &rid, &val).await;
+	assert!(matches!(res, Err(Error::IndexExists { .. })));
+	txn.cancel().await?;
+	// Other errors are returned unchanged
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.create_composite_index_entry("test", "test", "user", &ix, &rid, &val).await;
+	assert!(matches!(res, Err(Error::TxReadonly)));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn define_statement_quota_records() -> Result<(), Error> {
+	let sql = "
+		DEFINE QUOTA ON DATABASE test RECORDS 1;
+		CREATE user:1;
+		CREATE user:2;
+		CREATE user:3;
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = &mut dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 4);
+	//
+	let tmp = res.remove(0).result;
+	assert!(tmp.is_ok());
+	//
+	let tmp = res.remove(0).result?;
+	let val = Value::parse("[{ id: user:1 }]");
+	assert_eq!(tmp, val);
+	//
+	let tmp = res.remove(0).result;
+	assert!(matches!(
+		tmp.err(),
+		Some(Error::QuotaExceeded {
+			resource: "records",
+			limit: 1,
+			actual: 2
+		})
+	));
+	//
+	let tmp = res.remove(0).result;
+	assert!(matches!(
+		tmp.err(),
+		Some(Error::QuotaExceeded {
+			resource: "records",
+			limit: 1,
+			actual: 2
+		})
+	));
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn define_statement_quota_records_update() -> Result<(), Error> {
+	let sql = "
+		DEFINE QUOTA ON DATABASE test RECORDS 1;
+		CREATE user:1;
+		UPDATE user:1 SET name = 'Tobie';
+		DELETE user:1;
+		CREATE user:2;
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = &mut dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 5);
+	//
+	let tmp = res.remove(0).result;
+	assert!(tmp.is_ok());
+	//
+	let tmp = res.remove(0).result?;
+	let val = Value::parse("[{ id: user:1 }]");
+	assert_eq!(tmp, val);
+	//
+	let tmp = res.remove(0).result?;
+	let val = Value::parse("[{ id: user:1, name: 'Tobie' }]");
+	assert_eq!(tmp, val);
+	//
+	let tmp = res.remove(0).result;
+	assert!(tmp.is_ok());
+	//
+	let tmp = res.remove(0).result?;
+	let val = Value::parse("[{ id: user:2 }]");
+	assert_eq!(tmp, val);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn define_statement_quota_records_across_tables() -> Result<(), Error> {
+	let sql = "
+		CREATE user:1;
+		CREATE team:1;
+		DEFINE QUOTA ON DATABASE test RECORDS 3;
+		CREATE user:2;
+		CREATE team:2;
+		DELETE team:1;
+		CREATE team:3;
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = &mut dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 7);
+	//
+	for _ in 0..4 {
+		let tmp = res.remove(0).result;
+		assert!(tmp.is_ok());
+	}
+	// The limit applies to the records of every table
+	let tmp = res.remove(0).result;
+	assert!(matches!(
+		tmp.err(),
+		Some(Error::QuotaExceeded {
+			resource: "records",
+			limit: 3,
+			actual: 4
+		})
+	));
+	//
+	let tmp = res.remove(0).result;
+	assert!(tmp.is_ok());
+	//
+	let tmp = res.remove(0).result?;
+	let val = Value::parse("[{ id: team:3 }]");
+	assert_eq!(tmp, val);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn define_statement_table_view_existing_records() -> Result<(), Error> {
+	let sql = "
+		CREATE person:1 SET age = 20;
+		CREATE person:2 SET age = 30;
+		DEFINE TABLE adult AS SELECT age FROM person WHERE age > 25;
+		SELECT age FROM adult;
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = &mut dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 4);
+	//
+	let tmp = res.remove(0).result;
+	assert!(tmp.is_ok());
+	//
+	let tmp = res.remove(0).result;
+	assert!(tmp.is_ok());
+	//
+	let tmp = res.remove(0).result;
+	assert!(tmp.is_ok());
+	//
+	let tmp = res.remove(0).result?;
+	let val = Value::parse("[{ age: 30 }]");
+	assert_eq!(tmp, val);
+	//
+	Ok(())
+}
@@ -0,0 +1,62 @@
+mod parse;
+use parse::Parse;
+use surrealdb::sql::Thing;
+use surrealdb::Datastore;
+use surrealdb::DiffType;
+use surrealdb::Error;
+use surrealdb::RecordDiff;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+#[tokio::test]
+async fn diff_table_between_versions() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE TABLE person CHANGEFEED 1h;
+		CREATE person:1 SET age = 1;
+		CREATE person:2 SET age = 2;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 3);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let version_a = txn.get_tb_version("test", "test", "person").await?;
+	txn.cancel().await?;
+	//
+	let sql = "
+		DELETE person:1;
+		UPDATE person:2 SET age = 3;
+		CREATE person:3 SET age = 4;
+		CREATE person:4 SET age = 5;
+		DELETE person:4;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 5);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let version_b = txn.get_tb_version("test", "test", "person").await?;
+	let res = txn.diff_table("test", "test", "person", version_a, version_b).await?;
+	// Nothing changes between the same versions
+	assert!(txn.diff_table("test", "test", "person", version_b, version_b).await?.is_empty());
+	txn.cancel().await?;
+	//
+	let diff = |thing: &str, change: DiffType| RecordDiff {
+		thing: Thing::parse(thing),
+		change,
+	};
+	assert_eq!(
+		res,
+		vec![
+			diff("person:1", DiffType::Removed),
+			diff("person:2", DiffType::Modified),
+			diff("person:3", DiffType::Added),
+		]
+	);
+	//
+	Ok(())
+}
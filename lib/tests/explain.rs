@@ -0,0 +1,53 @@
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::ExplainStep;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+// Create a planned step of a query
+fn step(operation: &str, target: &str, rows: u64) -> ExplainStep {
+	ExplainStep {
+		operation: operation.to_owned(),
+		target: target.to_owned(),
+		estimated_rows: rows,
+		cost: rows,
+	}
+}
+
+#[tokio::test]
+async fn explain_query_plans_statements() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		CREATE person:1 SET age = 33;
+		CREATE person:2 SET age = 28;
+		CREATE person:3 SET age = 12;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 3);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	//
+	let sql = "
+		SELECT * FROM person WHERE age > 18;
+		SELECT * FROM person:1, user;
+		CREATE person SET age = 40;
+		CREATE |person:2..5|;
+		INFO FOR DB;
+	";
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.explain_query("test", "test", sql).await?;
+	txn.cancel().await?;
+	let val = vec![
+		step("Table scan", "person", 3),
+		step("Filter", "WHERE age > 18", 3),
+		step("Record fetch", "person:1", 1),
+		step("Table scan", "user", 0),
+		step("Record creation", "person", 1),
+		step("Record generation", "|person:2..5|", 4),
+	];
+	assert_eq!(res, val);
+	//
+	Ok(())
+}
@@ -0,0 +1,326 @@
+use surrealdb::channel;
+use surrealdb::sql::Thing;
+use surrealdb::sql::Value;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::Timestamp;
+use surrealdb::TransactionType;
+
+async fn export(dbs: &Datastore) -> Result<Vec<u8>, Error> {
+	let (snd, rcv) = channel::new(1000);
+	dbs.export("test".to_owned(), "test".to_owned(), snd).await?;
+	let mut out = vec![];
+	while let Ok(v) = rcv.try_recv() {
+		out.extend(v);
+	}
+	Ok(out)
+}
+
+#[tokio::test]
+async fn export_since_timestamp() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE TABLE person CHANGEFEED 1h;
+		CREATE person:1 SET age = 1;
+		CREATE person:2 SET age = 2;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 3);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	// Take the timestamp after the last change
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let since = Timestamp(txn.get_tb_version("test", "test", "person").await? + 1);
+	txn.cancel().await?;
+	//
+	let sql = "
+		UPDATE person:2 SET age = 3;
+		UPDATE person:2 SET age = 4;
+		DELETE person:1;
+		CREATE person:3 SET age = 5;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 4);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	//
+	let (snd, rcv) = channel::new(1000);
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	txn.export_since("test", "test", since, snd).await?;
+	txn.cancel().await?;
+	let mut out = vec![];
+	while let Ok(v) = rcv.try_recv() {
+		out.push(String::from_utf8(v).unwrap());
+	}
+	let out: Vec<&str> = out
+		.iter()
+		.map(|v| v.trim())
+		.filter(|v| v.starts_with("UPDATE") || v.starts_with("DELETE"))
+		.collect();
+	assert_eq!(
+		out,
+		vec![
+			"UPDATE person:2 CONTENT { age: 4, id: person:2 };",
+			"DELETE person:1;",
+			"UPDATE person:3 CONTENT { age: 5, id: person:3 };",
+		]
+	);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn export_since_without_changefeed() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		CREATE person:1 SET age = 1;
+		CREATE person:2 SET age = 2;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 2);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	//
+	let (snd, rcv) = channel::new(1000);
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	txn.export_since("test", "test", Timestamp(0), snd).await?;
+	txn.cancel().await?;
+	while let Ok(v) = rcv.try_recv() {
+		let v = String::from_utf8(v).unwrap();
+		assert!(!v.starts_with("UPDATE") && !v.starts_with("DELETE"));
+	}
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn prune_change_feed_after_retention() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE TABLE person CHANGEFEED 1ms;
+		CREATE person:1 SET age = 1;
+		CREATE person:2 SET age = 2;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 3);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	std::thread::sleep(std::time::Duration::from_millis(10));
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let version = txn.get_tb_version("test", "test", "person").await?;
+	assert_eq!(txn.prune_change_feed("test", "test").await?, 2);
+	assert_eq!(txn.prune_change_feed("test", "test").await?, 0);
+	assert_eq!(txn.get_tb_version("test", "test", "person").await?, version);
+	txn.commit().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn export_since_import_round_trip() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE TABLE person CHANGEFEED 1h;
+		DEFINE INDEX email ON TABLE person COLUMNS email UNIQUE;
+		CREATE person:1 SET email = 'one@surrealdb.com';
+		CREATE person:2 SET email = 'two@surrealdb.com';
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 4);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	// Restore a full export
+	let imp = Datastore::new("memory").await?;
+	let mut txn = imp.transaction(TransactionType::Write, false).await?;
+	txn.import("test", "test", &export(&dbs).await?).await?;
+	txn.commit().await?;
+	// Take the timestamp after the last change
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let since = Timestamp(txn.get_tb_version("test", "test", "person").await? + 1);
+	txn.cancel().await?;
+	//
+	let sql = "
+		UPDATE person:2 SET email = 'other@surrealdb.com';
+		DELETE person:1;
+		CREATE person:3 SET email = 'three@surrealdb.com';
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 3);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	// Apply the incremental export on top of the full export
+	let (snd, rcv) = channel::new(1000);
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	txn.export_since("test", "test", since, snd).await?;
+	txn.cancel().await?;
+	let mut out = vec![];
+	while let Ok(v) = rcv.try_recv() {
+		out.extend(v);
+	}
+	let mut txn = imp.transaction(TransactionType::Write, false).await?;
+	txn.import("test", "test", &out).await?;
+	txn.commit().await?;
+	//
+	assert_eq!(export(&imp).await?, export(&dbs).await?);
+	// The index entries of the deleted record are removed
+	let sql = "CREATE person:4 SET email = 'one@surrealdb.com';";
+	let res = &mut imp.execute(&sql, &ses, None, false).await?;
+	assert!(res.remove(0).result.is_ok());
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn export_partial_import_round_trip() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE TABLE person CHANGEFEED 1h;
+		CREATE person:1 SET age = 1;
+		CREATE person:2 SET age = 2;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 3);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	// Restore a full export at the current position
+	let imp = Datastore::new("memory").await?;
+	let mut txn = imp.transaction(TransactionType::Write, false).await?;
+	txn.import("test", "test", &export(&dbs).await?).await?;
+	txn.commit().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let mut lsn = txn.get_tb_version("test", "test", "person").await?;
+	txn.cancel().await?;
+	// Apply each set of changes from the previous position
+	let changes = [
+		"UPDATE person:2 SET age = 3; DELETE person:1;",
+		"CREATE person:1 SET age = 4; DELETE person:2;",
+		"",
+	];
+	for sql in changes {
+		if !sql.is_empty() {
+			let res = dbs.execute(&sql, &ses, None, false).await?;
+			for v in res.into_iter() {
+				assert!(v.result.is_ok());
+			}
+		}
+		let (snd, rcv) = channel::new(1000);
+		let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+		let pos = txn.export_partial("test", "test", lsn, snd).await?;
+		txn.cancel().await?;
+		assert_eq!(pos > lsn, !sql.is_empty());
+		lsn = pos;
+		let mut out = vec![];
+		while let Ok(v) = rcv.try_recv() {
+			out.extend(v);
+		}
+		let mut txn = imp.transaction(TransactionType::Write, false).await?;
+		txn.import("test", "test", &out).await?;
+		txn.commit().await?;
+		//
+		assert_eq!(export(&imp).await?, export(&dbs).await?);
+	}
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn export_json_schema_and_records() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE TABLE person SCHEMALESS;
+		DEFINE TABLE team SCHEMALESS;
+		DEFINE FIELD age ON person TYPE int;
+		DEFINE INDEX age ON person FIELDS age;
+		CREATE person:1 SET age = 20;
+		CREATE person:2 SET age = 30;
+		CREATE team:1 SET name = 'SurrealDB', members = [person:1, person:2];
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 7);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	//
+	let (snd, rcv) = channel::new(1000);
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	txn.export_json("test", "test", snd).await?;
+	txn.cancel().await?;
+	let mut out = vec![];
+	while let Ok(v) = rcv.try_recv() {
+		out.push(serde_json::from_slice::<serde_json::Value>(&v).unwrap());
+	}
+	// Each line holds a single JSON value
+	assert_eq!(
+		out,
+		vec![
+			serde_json::json!({
+				"schema": {
+					"logins": [],
+					"tokens": [],
+					"scopes": [],
+					"tables": [
+						"DEFINE TABLE person SCHEMALESS",
+						"DEFINE TABLE team SCHEMALESS",
+					],
+					"fields": ["DEFINE FIELD age ON person TYPE int"],
+					"indexes": ["DEFINE INDEX age ON person FIELDS age"],
+					"events": [],
+				}
+			}),
+			serde_json::json!({ "table": "person", "record": { "id": "person:1", "age": 20 } }),
+			serde_json::json!({ "table": "person", "record": { "id": "person:2", "age": 30 } }),
+			serde_json::json!({
+				"table": "team",
+				"record": {
+					"id": "team:1",
+					"name": "SurrealDB",
+					"members": ["person:1", "person:2"],
+				}
+			}),
+		]
+	);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn export_since_timestamp_uses_commit_time() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute("DEFINE TABLE person CHANGEFEED 1h", &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	// Take the timestamp after the change is made, but before it is committed
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.set_record("test", "test", &Thing::from(("person", "1")), &Value::from(1)).await?;
+	let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap();
+	let since = Timestamp(now.as_micros() as u64);
+	txn.commit().await?;
+	// The change is exported, as it was committed after the timestamp
+	let (snd, rcv) = channel::new(1000);
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	txn.export_since("test", "test", since, snd).await?;
+	txn.cancel().await?;
+	let mut out = vec![];
+	while let Ok(v) = rcv.try_recv() {
+		out.push(String::from_utf8(v).unwrap());
+	}
+	let out: Vec<&str> = out.iter().map(|v| v.trim()).filter(|v| v.starts_with("UPDATE")).collect();
+	assert_eq!(out, vec!["UPDATE person:1 CONTENT 1;"]);
+	//
+	Ok(())
+}
@@ -1,9 +1,28 @@
 mod parse;
 use parse::Parse;
+use std::collections::HashMap;
+use surrealdb::sql::statements::DefineFieldStatement;
+use surrealdb::sql::statements::DefineStatement;
+use surrealdb::sql::Statement;
+use surrealdb::sql::Thing;
 use surrealdb::sql::Value;
 use surrealdb::Datastore;
 use surrealdb::Error;
+use surrealdb::FieldStats;
 use surrealdb::Session;
+use surrealdb::TransactionType;
+
+// Parse a set of field definitions
+fn fields(sql: &str) -> Vec<DefineFieldStatement> {
+	let stms = surrealdb::sql::parse(sql).unwrap().0;
+	stms.0
+		.into_iter()
+		.map(|v| match v {
+			Statement::Define(DefineStatement::Field(v)) => v,
+			v => panic!("unexpected statement: {}", v),
+		})
+		.collect()
+}
 
 #[tokio::test]
 async fn field_definition_value_assert_failure() -> Result<(), Error> {
@@ -81,3 +100,167 @@ async fn field_definition_value_assert_success() -> Result<(), Error> {
 	//
 	Ok(())
 }
+
+#[tokio::test]
+async fn field_statistics_of_table() -> Result<(), Error> {
+	let sql = "
+		CREATE person:1 SET age = 10;
+		CREATE person:2 SET age = 20;
+		CREATE person:3 SET age = 20;
+		CREATE person:4 SET age = 15.5;
+		CREATE person:5 SET age = NULL;
+		CREATE person:6;
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 6);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.get_field_statistics("test", "test", "person", "age").await?;
+	txn.cancel().await?;
+	let val = FieldStats {
+		total_records: 6,
+		null_count: 2,
+		distinct_count: 3,
+		min: Some(Value::from(10)),
+		max: Some(Value::from(20)),
+		type_distribution: HashMap::from([
+			("int".to_owned(), 3),
+			("decimal".to_owned(), 1),
+			("null".to_owned(), 1),
+			("none".to_owned(), 1),
+		]),
+	};
+	assert_eq!(res, val);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn field_null_values_in_table() -> Result<(), Error> {
+	let sql = "
+		CREATE person:1 SET address.city = 'London';
+		CREATE person:2 SET address.city = NULL;
+		CREATE person:3 SET address = NULL;
+		CREATE person:4;
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 4);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.scan_null_fields("test", "test", "person", "address.city").await?;
+	assert_eq!(
+		res,
+		vec![Thing::parse("person:2"), Thing::parse("person:3"), Thing::parse("person:4")]
+	);
+	let res = txn.scan_null_fields("test", "test", "person", "address").await?;
+	assert_eq!(res, vec![Thing::parse("person:3"), Thing::parse("person:4")]);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn field_backfill_missing_values() -> Result<(), Error> {
+	let sql = "
+		CREATE person:1 SET name = 'Tobie';
+		CREATE person:2 SET name = NULL;
+		CREATE person:3;
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 3);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	// Only the missing values are set
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let val = Value::from("Unknown");
+	assert_eq!(txn.backfill_field("test", "test", "person", "name", &val).await?, 2);
+	assert_eq!(txn.backfill_field("test", "test", "person", "name", &val).await?, 0);
+	txn.commit().await?;
+	//
+	let res = &mut dbs.execute("SELECT * FROM person;", &ses, None, false).await?;
+	let tmp = res.remove(0).result?;
+	let val = Value::parse(
+		"[
+			{ id: person:1, name: 'Tobie' },
+			{ id: person:2, name: 'Unknown' },
+			{ id: person:3, name: 'Unknown' },
+		]",
+	);
+	assert_eq!(tmp, val);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn field_bulk_definitions() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert!(txn.all_fd("test", "test", "person").await?.is_empty());
+	// The fields are defined on the specified table
+	let fds = fields("DEFINE FIELD name ON other TYPE string; DEFINE FIELD age ON other TYPE int;");
+	txn.bulk_define_fields("test", "test", "person", fds).await?;
+	let res: Vec<String> =
+		txn.all_fd("test", "test", "person").await?.iter().map(|v| v.to_string()).collect();
+	let val = vec![
+		"DEFINE FIELD age ON person TYPE int".to_owned(),
+		"DEFINE FIELD name ON person TYPE string".to_owned(),
+	];
+	assert_eq!(res, val);
+	assert!(txn.all_fd("test", "test", "other").await?.is_empty());
+	txn.commit().await?;
+	// The fields are applied to new records
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "CREATE person:test SET name = 1, age = '22';";
+	let res = &mut dbs.execute(sql, &ses, None, false).await?;
+	let tmp = res.remove(0).result?;
+	let val = Value::parse("[{ id: person:test, name: '1', age: 22 }]");
+	assert_eq!(tmp, val);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn field_schema_diff_of_table() -> Result<(), Error> {
+	let sql = "
+		DEFINE FIELD name ON person TYPE string;
+		DEFINE FIELD age ON person TYPE int;
+		DEFINE FIELD email ON person TYPE string;
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 3);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	//
+	let fds = fields(
+		"
+		DEFINE FIELD name ON person TYPE string;
+		DEFINE FIELD age ON person TYPE number;
+		DEFINE FIELD nickname ON other TYPE string;
+		",
+	);
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.field_schema_diff("test", "test", "person", &fds).await?;
+	txn.cancel().await?;
+	let names = |v: &[DefineFieldStatement]| v.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+	assert_eq!(names(&res.to_add), vec!["DEFINE FIELD nickname ON person TYPE string"]);
+	assert_eq!(names(&res.to_alter), vec!["DEFINE FIELD age ON person TYPE number"]);
+	assert_eq!(names(&res.to_drop), vec!["DEFINE FIELD email ON person TYPE string"]);
+	//
+	Ok(())
+}
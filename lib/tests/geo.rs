@@ -0,0 +1,90 @@
+mod parse;
+use parse::Parse;
+use surrealdb::sql::Thing;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+async fn datastore() -> Result<Datastore, Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE INDEX location ON place COLUMNS location;
+		CREATE place:london SET location = (-0.118092, 51.509865);
+		CREATE place:paris SET location = (2.352222, 48.856613);
+		CREATE place:newyork SET location = (-74.005974, 40.712776);
+		CREATE place:route SET location = { type: 'LineString', coordinates: [[-10.0, 51.5], [0.0, 51.5]] };
+		CREATE place:unknown;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 6);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	Ok(dbs)
+}
+
+// Take the record ids of a set of records
+fn ids<T>(res: Vec<(Thing, T)>) -> Vec<Thing> {
+	res.into_iter().map(|(t, _)| t).collect()
+}
+
+#[tokio::test]
+async fn scan_within_bbox_and_radius() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	// Geometries which cross the bounding box are included
+	let res = txn
+		.scan_within_bbox("test", "test", "place", "location", 49.0, -8.0, 59.0, 2.0, 10)
+		.await?;
+	assert_eq!(ids(res), vec![Thing::parse("place:london"), Thing::parse("place:route")]);
+	let res =
+		txn.scan_within_bbox("test", "test", "place", "location", 49.0, -8.0, 59.0, 2.0, 1).await?;
+	assert_eq!(ids(res), vec![Thing::parse("place:london")]);
+	// Only points are matched within a radius
+	let res = txn
+		.scan_within_radius("test", "test", "place", "location", 51.5, -0.1, 400_000.0, 10)
+		.await?;
+	assert_eq!(ids(res), vec![Thing::parse("place:london"), Thing::parse("place:paris")]);
+	let res = txn
+		.scan_within_radius("test", "test", "place", "location", 51.5, -0.1, 300_000.0, 10)
+		.await?;
+	assert_eq!(ids(res), vec![Thing::parse("place:london")]);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn scan_geohash_index_groups_points() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "CREATE place:westminster SET location = (-0.135700, 51.497500);";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let mut res = txn.scan_geohash_index("test", "test", "place", "location", 3).await?;
+	for (_, v) in res.iter_mut() {
+		v.sort_by_key(|t| t.to_string());
+	}
+	let val = vec![
+		("dr5".to_owned(), vec![Thing::parse("place:newyork")]),
+		("gcp".to_owned(), vec![Thing::parse("place:london"), Thing::parse("place:westminster")]),
+		("u09".to_owned(), vec![Thing::parse("place:paris")]),
+	];
+	assert_eq!(res, val);
+	// The precision is limited to at least one character
+	let res = txn.scan_geohash_index("test", "test", "place", "location", 0).await?;
+	let res: Vec<(String, usize)> = res.into_iter().map(|(k, v)| (k, v.len())).collect();
+	let val = vec![("d".to_owned(), 1), ("g".to_owned(), 2), ("u".to_owned(), 1)];
+	assert_eq!(res, val);
+	assert!(matches!(
+		txn.scan_geohash_index("test", "test", "place", "other", 3).await,
+		Err(Error::IxNotFound)
+	));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+#[tokio::test]
+async fn health_check_responds() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	// An empty datastore is healthy
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert!(txn.health_check().await? < Duration::from_secs(10));
+	txn.cancel().await?;
+	// A datastore with data is healthy
+	let res = dbs.execute("CREATE person:tobie;", &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert!(txn.health_check().await? < Duration::from_secs(10));
+	txn.cancel().await?;
+	// The check fails once the transaction is closed
+	assert!(matches!(txn.health_check().await, Err(Error::TxFinished)));
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn sys_info_of_datastore() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE NAMESPACE other;
+		DEFINE DATABASE one;
+		DEFINE DATABASE two;
+	";
+	let res = dbs.execute(sql, &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let mut other = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.get_sys_info().await?;
+	assert_eq!(res.active_transactions, 2);
+	assert_eq!(res.total_namespaces, 2);
+	assert_eq!(res.total_databases, 2);
+	assert_eq!(res.backend_version, "echodb 0.3 (memory)");
+	assert!(res.backend_latency_ms >= 0.0);
+	txn.cancel().await?;
+	other.cancel().await?;
+	//
+	Ok(())
+}
@@ -0,0 +1,81 @@
+use surrealdb::channel;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+async fn export(dbs: &Datastore) -> Result<Vec<u8>, Error> {
+	let (snd, rcv) = channel::new(1000);
+	dbs.export("test".to_owned(), "test".to_owned(), snd).await?;
+	let mut out = vec![];
+	while let Ok(v) = rcv.try_recv() {
+		out.extend(v);
+	}
+	Ok(out)
+}
+
+#[tokio::test]
+async fn import_round_trip() -> Result<(), Error> {
+	let sql = "
+		DEFINE TABLE person SCHEMAFULL;
+		DEFINE FIELD name ON person TYPE string;
+		DEFINE FIELD age ON person TYPE int;
+		DEFINE INDEX name ON person FIELDS name UNIQUE;
+		DEFINE EVENT test ON person WHEN true THEN (CREATE log);
+		CREATE person:tobie SET name = 'Tobie', age = 30;
+		CREATE person:jaime SET name = 'Jaime', age = 25;
+		CREATE temp:1 SET value = [1, 2, 3];
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 8);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	let out = export(&dbs).await?;
+	//
+	let imp = Datastore::new("memory").await?;
+	let mut txn = imp.transaction(TransactionType::Write, false).await?;
+	txn.import("test", "test", &out).await?;
+	txn.commit().await?;
+	//
+	assert_eq!(export(&imp).await?, out);
+	//
+	let sql = "CREATE person:other SET name = 'Tobie', age = 20;";
+	let res = &mut imp.execute(&sql, &ses, None, false).await?;
+	let tmp = res.remove(0).result;
+	assert!(matches!(tmp, Err(Error::IndexExists { .. })));
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn import_parse_error() -> Result<(), Error> {
+	let sql = "OPTION IMPORT;\n\nUPDATE person:tobie CONTENT {;\n";
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let res = txn.import("test", "test", sql.as_bytes()).await;
+	assert!(matches!(res, Err(Error::ImportError(..))));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn import_statement_error() -> Result<(), Error> {
+	let sql = "OPTION IMPORT;\nBEGIN TRANSACTION;\n\nCREATE person:tobie CONTENT { name: 'Tobie' };\nCREATE person:tobie CONTENT {\n\tname: 'Tobie'\n};\nCOMMIT TRANSACTION;\n";
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let res = txn.import("test", "test", sql.as_bytes()).await;
+	assert!(matches!(res, Err(Error::ImportError(5, _))));
+	txn.cancel().await?;
+	//
+	let sql = "OPTION IMPORT;\nSELECT * FROM person;\n";
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let res = txn.import("test", "test", sql.as_bytes()).await;
+	assert!(matches!(res, Err(Error::ImportError(2, _))));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
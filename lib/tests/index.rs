@@ -0,0 +1,347 @@
+mod parse;
+use parse::Parse;
+use surrealdb::sql::Operator;
+use surrealdb::sql::Thing;
+use surrealdb::sql::Value;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::IndexEntry;
+use surrealdb::IndexRecommendation;
+use surrealdb::IndexStats;
+use surrealdb::IndexType;
+use surrealdb::Predicate;
+use surrealdb::QueryCostEstimate;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+async fn datastore() -> Result<Datastore, Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE INDEX name ON person COLUMNS name;
+		DEFINE INDEX email ON person COLUMNS email UNIQUE;
+		CREATE person:1 SET name = 'Tobie', email = 'tobie@surrealdb.com', age = 33;
+		CREATE person:2 SET name = 'Jaime', email = 'jaime@surrealdb.com', age = 28;
+		CREATE person:3 SET name = 'Tobie', email = 'tobie@example.com', age = 12;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 5);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	Ok(dbs)
+}
+
+#[tokio::test]
+async fn drop_index_safe_checks_references() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE INDEX age ON person COLUMNS age;
+		DEFINE TABLE adult AS SELECT * FROM person WHERE age >= 18;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	// An index which is referenced by a view is kept
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	match txn.drop_index_safe("test", "test", "person", "age").await {
+		Err(Error::IndexInUse {
+			referenced_by,
+		}) => assert_eq!(referenced_by, vec!["adult".to_owned()]),
+		res => panic!("unexpected result: {:?}", res),
+	}
+	assert!(matches!(
+		txn.drop_index_safe("test", "test", "person", "other").await,
+		Err(Error::IxNotFound)
+	));
+	// An unreferenced index is removed along with its entries
+	txn.drop_index_safe("test", "test", "person", "name").await?;
+	txn.commit().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.all_ix("test", "test", "person").await?;
+	let res: Vec<String> = res.iter().map(|v| v.name.to_string()).collect();
+	assert_eq!(res, vec!["age".to_owned(), "email".to_owned()]);
+	let beg = b"/*test\x00*test\x00*person\x00\xa4name\x00".to_vec();
+	let end = b"/*test\x00*test\x00*person\x00\xa4name\x01".to_vec();
+	assert!(txn.getr(beg..end, u32::MAX).await?.is_empty());
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn scan_index_entries_decodes_entries() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	// Entries are ordered by the indexed values
+	let res = txn.scan_index_entries("test", "test", "person", "name", u32::MAX).await?;
+	let val = vec![
+		IndexEntry {
+			index_key: Value::parse("['Jaime']"),
+			record_id: Thing::parse("person:2"),
+		},
+		IndexEntry {
+			index_key: Value::parse("['Tobie']"),
+			record_id: Thing::parse("person:1"),
+		},
+		IndexEntry {
+			index_key: Value::parse("['Tobie']"),
+			record_id: Thing::parse("person:3"),
+		},
+	];
+	assert_eq!(res, val);
+	// Unique index entries point to the indexed record
+	let res = txn.scan_index_entries("test", "test", "person", "email", 2).await?;
+	let val = vec![
+		IndexEntry {
+			index_key: Value::parse("['jaime@surrealdb.com']"),
+			record_id: Thing::parse("person:2"),
+		},
+		IndexEntry {
+			index_key: Value::parse("['tobie@example.com']"),
+			record_id: Thing::parse("person:3"),
+		},
+	];
+	assert_eq!(res, val);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn get_index_entry_count_follows_records() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.get_index_entry_count("test", "test", "person", "name").await?, 3);
+	assert_eq!(txn.get_index_entry_count("test", "test", "person", "email").await?, 3);
+	assert_eq!(txn.get_index_entry_count("test", "test", "person", "other").await?, 0);
+	txn.cancel().await?;
+	// Deleted records are removed from the index
+	let res = dbs.execute("DELETE person:1;", &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.get_index_entry_count("test", "test", "person", "name").await?, 2);
+	assert_eq!(txn.get_index_entry_count("test", "test", "person", "email").await?, 2);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn scan_by_index_fetches_records() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "DEFINE INDEX name_age ON person COLUMNS name, age;";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let one =
+		Value::parse("{ id: person:1, name: 'Tobie', email: 'tobie@surrealdb.com', age: 33 }");
+	let three =
+		Value::parse("{ id: person:3, name: 'Tobie', email: 'tobie@example.com', age: 12 }");
+	let val = Value::from("Tobie");
+	let res = txn.scan_by_index("test", "test", "person", "name", &val, u32::MAX).await?;
+	assert_eq!(
+		res,
+		vec![(Thing::parse("person:1"), one.clone()), (Thing::parse("person:3"), three.clone())]
+	);
+	let res = txn.scan_by_index("test", "test", "person", "name", &val, 1).await?;
+	assert_eq!(res, vec![(Thing::parse("person:1"), one.clone())]);
+	let val = Value::from("Lana");
+	assert!(txn.scan_by_index("test", "test", "person", "name", &val, u32::MAX).await?.is_empty());
+	// Composite indexes are looked up with a value for each field
+	let val = Value::parse("['Tobie', 12]");
+	let res = txn.scan_by_index("test", "test", "person", "name_age", &val, u32::MAX).await?;
+	assert_eq!(res, vec![(Thing::parse("person:3"), three)]);
+	// Unique indexes can be scanned too
+	let val = Value::from("tobie@surrealdb.com");
+	let res = txn.scan_by_index("test", "test", "person", "email", &val, u32::MAX).await?;
+	assert_eq!(res, vec![(Thing::parse("person:1"), one)]);
+	assert!(matches!(
+		txn.scan_by_index("test", "test", "person", "other", &val, u32::MAX).await,
+		Err(Error::IxNotFound)
+	));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn get_by_unique_index_detects_duplicates() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let val = Value::from("tobie@surrealdb.com");
+	let res = txn.get_by_unique_index("test", "test", "person", "email", &val).await?;
+	let one =
+		Value::parse("{ id: person:1, name: 'Tobie', email: 'tobie@surrealdb.com', age: 33 }");
+	assert_eq!(res, Some((Thing::parse("person:1"), one)));
+	let val = Value::from("lana@surrealdb.com");
+	assert_eq!(txn.get_by_unique_index("test", "test", "person", "email", &val).await?, None);
+	assert!(matches!(
+		txn.get_by_unique_index("test", "test", "person", "other", &val).await,
+		Err(Error::IxNotFound)
+	));
+	txn.cancel().await?;
+	// Corrupt the index with a second entry for a value
+	let beg = b"/*test\x00*test\x00*person\x00\xa4email\x00".to_vec();
+	let end = b"/*test\x00*test\x00*person\x00\xa4email\x01".to_vec();
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let res = txn.getr(beg..end, u32::MAX).await?;
+	assert_eq!(res.len(), 3);
+	txn.set([res[2].0.clone(), vec![0x01]].concat(), res[1].1.clone()).await?;
+	txn.commit().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let val = Value::from("tobie@surrealdb.com");
+	assert!(matches!(
+		txn.get_by_unique_index("test", "test", "person", "email", &val).await,
+		Err(Error::DuplicateIndexValue)
+	));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn index_statistics_of_table() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.all_ix_with_stats("test", "test", "person").await?;
+	txn.cancel().await?;
+	// Indexes are ordered by name
+	assert_eq!(res.len(), 2);
+	let (ix, email) = &res[0];
+	assert_eq!(ix.name.to_string(), "email");
+	assert_eq!((email.entry_count, email.distinct_values), (3, 3));
+	let (ix, name) = &res[1];
+	assert_eq!(ix.name.to_string(), "name");
+	assert_eq!((name.entry_count, name.distinct_values), (3, 2));
+	// The size covers the keys and values of every entry
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let beg = b"/*test\x00*test\x00*person\x00\xa4name\x00".to_vec();
+	let end = b"/*test\x00*test\x00*person\x00\xa4name\x01".to_vec();
+	let len: usize =
+		txn.getr(beg..end, u32::MAX).await?.iter().map(|(k, v)| k.len() + v.len()).sum();
+	assert_eq!(name.size_bytes, len as u64);
+	let res = txn.get_index_statistics("test", "test", "person", "name").await?;
+	assert_eq!(&res, name);
+	let res = txn.get_index_statistics("test", "test", "person", "other").await?;
+	assert_eq!(res, IndexStats::default());
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn recommend_indexes_for_selective_fields() -> Result<(), Error> {
+	let sql = "
+		DEFINE FIELD name ON person;
+		DEFINE FIELD email ON person;
+		DEFINE FIELD country ON person;
+		DEFINE FIELD active ON person;
+		DEFINE FIELD nickname ON person;
+		DEFINE INDEX name ON person COLUMNS name;
+		CREATE person:1 SET name = 'Tobie', email = 'a@surrealdb.com', country = 'uk', active = true;
+		CREATE person:2 SET name = 'Jaime', email = 'b@surrealdb.com', country = 'uk', active = true;
+		CREATE person:3 SET name = 'Tobie', email = 'c@surrealdb.com', country = 'us', active = true;
+		CREATE person:4 SET name = 'Jaime', email = 'd@surrealdb.com', country = 'us', active = true;
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 10);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	// Indexed, unselective, and empty fields are not recommended
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.recommend_indexes("test", "test", "person").await?;
+	txn.cancel().await?;
+	assert_eq!(
+		res,
+		vec![
+			IndexRecommendation {
+				fields: vec!["email".to_owned()],
+				type_: IndexType::Unique,
+				reason: "All 4 records have a distinct value".to_owned(),
+				estimated_speedup: 4.0,
+			},
+			IndexRecommendation {
+				fields: vec!["country".to_owned()],
+				type_: IndexType::Standard,
+				reason: "2 distinct values across 4 records".to_owned(),
+				estimated_speedup: 2.0,
+			},
+		]
+	);
+	// A table without records has no recommendations
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert!(txn.recommend_indexes("test", "test", "team").await?.is_empty());
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn estimate_scan_cost_picks_best_index() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let pred = |field: &str, op: Operator, value: &str| Predicate {
+		field: field.to_owned(),
+		op,
+		value: Value::from(value),
+	};
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let beg = b"/*test\x00*test\x00*person\x00*\x00".to_vec();
+	let end = b"/*test\x00*test\x00*person\x00*\xff".to_vec();
+	let len: usize =
+		txn.getr(beg..end, u32::MAX).await?.iter().map(|(k, v)| k.len() + v.len()).sum();
+	let len = len as u64;
+	// Without an equality predicate the whole table is scanned
+	let full = QueryCostEstimate {
+		estimated_rows: 3,
+		estimated_bytes: len,
+		index_used: None,
+		full_scan: true,
+	};
+	assert_eq!(txn.estimate_scan_cost("test", "test", "person", &[]).await?, full);
+	let res = txn
+		.estimate_scan_cost("test", "test", "person", &[pred("name", Operator::MoreThan, "A")])
+		.await?;
+	assert_eq!(res, full);
+	// The rows are estimated from the entries per distinct value
+	let res = txn
+		.estimate_scan_cost("test", "test", "person", &[pred("name", Operator::Equal, "Tobie")])
+		.await?;
+	let val = QueryCostEstimate {
+		estimated_rows: 2,
+		estimated_bytes: 2 * len / 3,
+		index_used: Some("name".to_owned()),
+		full_scan: false,
+	};
+	assert_eq!(res, val);
+	// A unique index matches a single row
+	let res = txn
+		.estimate_scan_cost(
+			"test",
+			"test",
+			"person",
+			&[
+				pred("name", Operator::Equal, "Tobie"),
+				pred("email", Operator::Exact, "tobie@surrealdb.com"),
+			],
+		)
+		.await?;
+	let val = QueryCostEstimate {
+		estimated_rows: 1,
+		estimated_bytes: len / 3,
+		index_used: Some("email".to_owned()),
+		full_scan: false,
+	};
+	assert_eq!(res, val);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
@@ -0,0 +1,112 @@
+mod parse;
+use parse::Parse;
+use std::time::Duration;
+use surrealdb::sql::Part;
+use surrealdb::sql::Value;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+async fn datastore() -> Result<Datastore, Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE SCOPE account SESSION 1h;
+		DEFINE TOKEN api ON DATABASE TYPE HS512 VALUE 'secret';
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 2);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	Ok(dbs)
+}
+
+#[tokio::test]
+async fn issue_and_decode_jwt() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let exp = Duration::from_secs(60);
+	let extra = Value::parse("{ role: 'admin' }");
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	// A scope token is verified with the scope signing key
+	let tk = txn.issue_jwt("test", "test", Some("account"), "user:1", extra.clone(), exp).await?;
+	let res = txn.decode_jwt("test", Some("test"), &tk).await?;
+	assert_eq!(res.subject, "user:1");
+	assert_eq!(res.ns, "test");
+	assert_eq!(res.db.as_deref(), Some("test"));
+	assert_eq!(res.sc.as_deref(), Some("account"));
+	assert!(res.exp > 0);
+	assert_eq!(res.extra.pick(&[Part::from("role")]), Value::from("admin"));
+	// A database token is verified with the token definition
+	let tk = txn.issue_jwt("test", "test", None, "user:1", extra, exp).await?;
+	let res = txn.decode_jwt("test", Some("test"), &tk).await?;
+	assert_eq!(res.subject, "user:1");
+	assert_eq!(res.db.as_deref(), Some("test"));
+	assert_eq!(res.sc, None);
+	assert_eq!(res.extra.pick(&[Part::from("tk")]), Value::from("api"));
+	// Database tokens are not verified without the database
+	let res = txn.decode_jwt("test", None, &tk).await;
+	assert!(matches!(res, Err(Error::InvalidToken)));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn rotate_scope_secret_rejects_tokens() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let exp = Duration::from_secs(60);
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let old = txn.issue_jwt("test", "test", Some("account"), "user:1", Value::None, exp).await?;
+	assert!(txn.decode_jwt("test", Some("test"), &old).await.is_ok());
+	// Tokens signed with the previous secret are rejected
+	txn.rotate_scope_secret("test", "test", "account", "rotated", false).await?;
+	assert!(matches!(txn.decode_jwt("test", Some("test"), &old).await, Err(Error::InvalidToken)));
+	let tk = txn.issue_jwt("test", "test", Some("account"), "user:1", Value::None, exp).await?;
+	assert!(txn.decode_jwt("test", Some("test"), &tk).await.is_ok());
+	txn.commit().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn rotate_scope_secret_revokes_tokens() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let exp = Duration::from_secs(60);
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.rotate_scope_secret("test", "test", "account", "signing", false).await?;
+	let old = txn.issue_jwt("test", "test", Some("account"), "user:1", Value::None, exp).await?;
+	// Tokens signed with the same secret are revoked by the nonce
+	txn.rotate_scope_secret("test", "test", "account", "signing", true).await?;
+	assert!(matches!(txn.decode_jwt("test", Some("test"), &old).await, Err(Error::InvalidToken)));
+	let tk = txn.issue_jwt("test", "test", Some("account"), "user:1", Value::None, exp).await?;
+	assert!(txn.decode_jwt("test", Some("test"), &tk).await.is_ok());
+	// Database tokens are not affected
+	let db = txn.issue_jwt("test", "test", None, "user:1", Value::None, exp).await?;
+	txn.rotate_scope_secret("test", "test", "account", "signing", true).await?;
+	assert!(txn.decode_jwt("test", Some("test"), &db).await.is_ok());
+	txn.commit().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn issue_jwt_with_unsupported_algorithm() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "DEFINE TOKEN api ON DATABASE TYPE ES512 VALUE 'key';";
+	let res = &mut dbs.execute(&sql, &ses, None, false).await?;
+	assert!(res.remove(0).result.is_ok());
+	// Tokens are not signed with a different algorithm
+	let exp = Duration::from_secs(60);
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.issue_jwt("test", "test", None, "user:1", Value::None, exp).await;
+	assert!(matches!(res, Err(Error::Jwt(_))));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
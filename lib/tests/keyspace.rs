@@ -0,0 +1,78 @@
+use std::collections::HashMap;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::KeyspaceSummary;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+#[tokio::test]
+async fn keyspace_summary_counts_definitions() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE NAMESPACE other;
+		DEFINE DATABASE other;
+		DEFINE TOKEN api ON NAMESPACE TYPE HS512 VALUE 'secret';
+		DEFINE TOKEN api ON DATABASE TYPE HS512 VALUE 'secret';
+		DEFINE SCOPE account SESSION 1h;
+		DEFINE TABLE user;
+		DEFINE FIELD name ON person TYPE string;
+		DEFINE INDEX name ON person COLUMNS name;
+		DEFINE EVENT test ON person WHEN $event = 'DELETE' THEN (CREATE activity);
+		CREATE person:1 SET name = 'Tobie';
+		CREATE person:2 SET name = 'Jaime';
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 11);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.keyspace_summary("test", "test").await?;
+	txn.cancel().await?;
+	let val = KeyspaceSummary {
+		namespaces: 2,
+		databases: 2,
+		tables: 2,
+		fields: 1,
+		indexes: 1,
+		events: 1,
+		scopes: 1,
+		tokens: 2,
+		live_queries: 0,
+		records: HashMap::from([("person".to_owned(), 2), ("user".to_owned(), 0)]),
+	};
+	assert_eq!(res, val);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn scan_prefix_histogram_groups_keys() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	// Write more keys than are fetched in a single batch
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	for i in 0..1500 {
+		txn.set(format!("a{:04}", i).into_bytes(), b"".to_vec()).await?;
+	}
+	txn.set(b"ba".to_vec(), b"".to_vec()).await?;
+	txn.set(b"bb".to_vec(), b"".to_vec()).await?;
+	txn.set(b"c".to_vec(), b"".to_vec()).await?;
+	txn.set(b"d".to_vec(), b"".to_vec()).await?;
+	txn.commit().await?;
+	// Keys outside of the range are not counted
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.scan_prefix_histogram(b"a".to_vec()..b"d".to_vec(), 2).await?;
+	txn.cancel().await?;
+	let val = HashMap::from([
+		(b"a0".to_vec(), 1000),
+		(b"a1".to_vec(), 500),
+		(b"ba".to_vec(), 1),
+		(b"bb".to_vec(), 1),
+		(b"c".to_vec(), 1),
+	]);
+	assert_eq!(res, val);
+	//
+	Ok(())
+}
@@ -0,0 +1,180 @@
+mod parse;
+use parse::Parse;
+use surrealdb::sql::Thing;
+use surrealdb::sql::Value;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::LiveAction;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+#[tokio::test]
+async fn subscribe_to_table_with_filter() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let (id, rcv) = txn.subscribe_to_table("test", "test", "person", Some("age > 18")).await?;
+	txn.commit().await?;
+	//
+	let sql = "
+		CREATE person:1 SET age = 10;
+		CREATE person:2 SET age = 20;
+		UPDATE person:2 SET age = 21;
+		DELETE person:2;
+	";
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 4);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	//
+	let msg = rcv.try_recv().unwrap();
+	assert_eq!(msg.id, id);
+	assert_eq!(msg.action, LiveAction::Create);
+	assert_eq!(msg.result, Value::parse("{ id: person:2, age: 20 }"));
+	//
+	let msg = rcv.try_recv().unwrap();
+	assert_eq!(msg.action, LiveAction::Update);
+	assert_eq!(msg.result, Value::parse("{ id: person:2, age: 21 }"));
+	//
+	let msg = rcv.try_recv().unwrap();
+	assert_eq!(msg.action, LiveAction::Delete);
+	assert_eq!(msg.result, Value::parse("person:2"));
+	//
+	assert!(rcv.try_recv().is_err());
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn unsubscribe_from_table_closes_channel() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let (id, rcv) = txn.subscribe_to_table("test", "test", "person", None).await?;
+	txn.commit().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.unsubscribe_from_table("test", "test", "person", id).await?;
+	assert!(txn.all_lv("test", "test", "person").await?.is_empty());
+	assert!(txn.unsubscribe_from_table("test", "test", "person", id).await.is_err());
+	txn.commit().await?;
+	assert!(rcv.is_closed());
+	//
+	let sql = "CREATE person:1";
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert!(res[0].result.is_ok());
+	assert!(rcv.try_recv().is_err());
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn broadcast_live_event_to_matching_queries() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let (adult, adults) = txn.subscribe_to_table("test", "test", "person", Some("age > 18")).await?;
+	let (_, everyone) = txn.subscribe_to_table("test", "test", "person", None).await?;
+	txn.commit().await?;
+	//
+	let rid = Thing::from(("person", "tobie"));
+	let val = Value::parse("{ id: person:tobie, age: 30 }");
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let num = txn.broadcast_live_event("test", "test", "person", &rid, LiveAction::Update, &val).await?;
+	assert_eq!(num, 2);
+	let val = Value::parse("{ id: person:tobie, age: 12 }");
+	let num = txn.broadcast_live_event("test", "test", "person", &rid, LiveAction::Delete, &val).await?;
+	assert_eq!(num, 1);
+	txn.commit().await?;
+	//
+	let msg = adults.try_recv().unwrap();
+	assert_eq!(msg.id, adult);
+	assert_eq!(msg.action, LiveAction::Update);
+	assert_eq!(msg.result, Value::parse("{ id: person:tobie, age: 30 }"));
+	assert!(adults.try_recv().is_err());
+	//
+	assert_eq!(everyone.try_recv().unwrap().action, LiveAction::Update);
+	let msg = everyone.try_recv().unwrap();
+	assert_eq!(msg.action, LiveAction::Delete);
+	assert_eq!(msg.result, Value::parse("person:tobie"));
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn pause_and_resume_live_query() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let (id, rcv) = txn.subscribe_to_table("test", "test", "person", Some("age > 18")).await?;
+	txn.pause_live_query("test", "test", "person", id).await?;
+	txn.commit().await?;
+	//
+	let sql = "
+		DEFINE TABLE person CHANGEFEED 1h;
+		CREATE person:1 SET age = 10;
+		CREATE person:2 SET age = 20;
+		DELETE person:1;
+	";
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 4);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	assert!(rcv.try_recv().is_err());
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let num = txn.resume_live_query("test", "test", "person", id, true).await?;
+	assert_eq!(num, 2);
+	txn.commit().await?;
+	//
+	let msg = rcv.try_recv().unwrap();
+	assert_eq!(msg.action, LiveAction::Update);
+	assert_eq!(msg.result, Value::parse("{ id: person:2, age: 20 }"));
+	let msg = rcv.try_recv().unwrap();
+	assert_eq!(msg.action, LiveAction::Delete);
+	assert_eq!(msg.result, Value::parse("person:1"));
+	//
+	let sql = "UPDATE person:2 SET age = 21";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert!(res[0].result.is_ok());
+	assert_eq!(rcv.try_recv().unwrap().action, LiveAction::Update);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn get_live_query_diff_since_datetime() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let (id, _rcv) = txn.subscribe_to_table("test", "test", "person", Some("age > 18")).await?;
+	txn.commit().await?;
+	//
+	let sql = "
+		DEFINE TABLE person CHANGEFEED 1h;
+		CREATE person:1 SET age = 30;
+		RETURN time::now();
+		CREATE person:2 SET age = 10;
+		UPDATE person:3 SET age = 40;
+		DELETE person:1;
+	";
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 6);
+	let since = match res.into_iter().nth(2).unwrap().result? {
+		Value::Datetime(v) => v.0,
+		v => panic!("unexpected value {}", v),
+	};
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.get_live_query_diff_since("test", "test", "person", id, since).await?;
+	txn.cancel().await?;
+	assert_eq!(res.len(), 2);
+	assert_eq!(res[0].id, id);
+	assert_eq!(res[0].action, LiveAction::Update);
+	assert_eq!(res[0].result, Value::parse("{ id: person:3, age: 40 }"));
+	assert_eq!(res[1].action, LiveAction::Delete);
+	assert_eq!(res[1].result, Value::parse("person:1"));
+	//
+	Ok(())
+}
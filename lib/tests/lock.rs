@@ -0,0 +1,36 @@
+use surrealdb::sql::Thing;
+use surrealdb::sql::Value;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::TransactionType;
+
+#[tokio::test]
+async fn table_write_lock_blocks_other_transactions() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let rid = Thing::from(("person", "tobie"));
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let lock = txn.write_lock_table("test", "test", "person").await?;
+	// The lock holder can acquire the lock again
+	let again = txn.write_lock_table("test", "test", "person").await?;
+	drop(again);
+	// The lock holder can write to the table
+	txn.set_record("test", "test", &rid, &Value::from("test")).await?;
+	// Other transactions can not write to the table
+	let mut other = dbs.transaction(TransactionType::Read, false).await?;
+	assert!(matches!(
+		other.check_table_lock("test", "test", "person").await,
+		Err(Error::TableLocked)
+	));
+	other.check_table_lock("test", "test", "other").await?;
+	other.cancel().await?;
+	//
+	drop(lock);
+	txn.commit().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	txn.check_table_lock("test", "test", "person").await?;
+	txn.cancel().await?;
+	//
+	Ok(())
+}
@@ -0,0 +1,114 @@
+use surrealdb::sql::Id;
+use surrealdb::sql::Thing;
+use surrealdb::sql::Value;
+use surrealdb::ConflictPolicy;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::MergeReport;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+async fn setup(dbs: &Datastore) -> Result<(), Error> {
+	let sql = "
+		USE NS src DB one; CREATE person:1;
+		USE NS src DB two; CREATE person:2;
+		USE NS dst DB two; CREATE person:3;
+	";
+	let ses = Session::for_kv();
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	Ok(())
+}
+
+async fn records(dbs: &Datastore, db: &str) -> Result<usize, Error> {
+	let sql = "SELECT * FROM person";
+	let ses = Session::for_kv().with_ns("dst").with_db(db);
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	match res.into_iter().next().unwrap().result? {
+		Value::Array(v) => Ok(v.len()),
+		v => panic!("unexpected value {}", v),
+	}
+}
+
+#[tokio::test]
+async fn merge_namespaces_skip() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	setup(&dbs).await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let res = txn.merge_namespaces("src", "dst", ConflictPolicy::Skip).await?;
+	assert_eq!(
+		res,
+		MergeReport {
+			moved: vec!["one".to_owned()],
+			skipped: vec!["two".to_owned()],
+			renamed: vec![],
+		}
+	);
+	txn.commit().await?;
+	assert_eq!(records(&dbs, "one").await?, 1);
+	assert_eq!(records(&dbs, "two").await?, 1);
+	// The skipped database is kept in the source namespace
+	let rid = |id: i64| Thing::from(("person".to_owned(), Id::from(id)));
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert!(txn.get_ns("src").await.is_ok());
+	assert!(matches!(txn.get_db("src", "one").await, Err(Error::DbNotFound)));
+	assert!(txn.get_db("src", "two").await.is_ok());
+	let res = txn.get_record("src", "two", &rid(2)).await?;
+	assert_ne!(res, Value::None);
+	let res = txn.get_record("dst", "two", &rid(2)).await?;
+	assert_eq!(res, Value::None);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn merge_namespaces_rename() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	setup(&dbs).await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let res = txn.merge_namespaces("src", "dst", ConflictPolicy::Rename("_src".to_owned())).await?;
+	assert_eq!(res.moved, vec!["one".to_owned()]);
+	assert_eq!(res.renamed, vec![("two".to_owned(), "two_src".to_owned())]);
+	txn.commit().await?;
+	assert_eq!(records(&dbs, "two").await?, 1);
+	assert_eq!(records(&dbs, "two_src").await?, 1);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn merge_namespaces_overwrite() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	setup(&dbs).await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let res = txn.merge_namespaces("src", "dst", ConflictPolicy::Overwrite).await?;
+	assert_eq!(
+		res,
+		MergeReport {
+			moved: vec!["one".to_owned(), "two".to_owned()],
+			skipped: vec![],
+			renamed: vec![],
+		}
+	);
+	txn.commit().await?;
+	assert_eq!(records(&dbs, "one").await?, 1);
+	assert_eq!(records(&dbs, "two").await?, 1);
+	// The existing database is replaced
+	let rid = |id: i64| Thing::from(("person".to_owned(), Id::from(id)));
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.get_record("dst", "two", &rid(3)).await?;
+	assert_eq!(res, Value::None);
+	let res = txn.get_record("dst", "two", &rid(2)).await?;
+	assert_ne!(res, Value::None);
+	txn.cancel().await?;
+	// Merging a namespace into itself leaves it unchanged
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let res = txn.merge_namespaces("dst", "dst", ConflictPolicy::Skip).await?;
+	assert_eq!(res, MergeReport::default());
+	txn.cancel().await?;
+	//
+	Ok(())
+}
@@ -0,0 +1,83 @@
+use chrono::Utc;
+use surrealdb::sql::Thing;
+use surrealdb::sql::Value;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::MigrationRecord;
+use surrealdb::TransactionType;
+
+fn migration(version: u64) -> MigrationRecord {
+	MigrationRecord {
+		version,
+		name: format!("migration_{}", version),
+		applied_at: Utc::now(),
+		applied_by: "test".to_owned(),
+		checksum: [0; 32],
+	}
+}
+
+#[tokio::test]
+async fn rollback_latest_migration() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let rid = Thing::from(("person", "tobie"));
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	for v in 1..=2 {
+		txn.log_migration("test", "test", &migration(v)).await?;
+		txn.set_migration_version("test", "test", v).await?;
+	}
+	txn.set_record("test", "test", &rid, &Value::from("test")).await?;
+	txn.commit().await?;
+	// The down migration runs along with the version update
+	dbs.rollback_migration("test", "test", "DELETE person:tobie;").await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.get_migration_version("test", "test").await?, 1);
+	let log = txn.list_migrations("test", "test").await?;
+	assert_eq!(log.iter().map(|v| v.version).collect::<Vec<_>>(), vec![1]);
+	assert!(txn.get_record("test", "test", &rid).await?.is_none());
+	txn.cancel().await?;
+	// The migration log can be reverted without a down migration
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.revert_migration("test", "test").await?;
+	assert_eq!(txn.get_migration_version("test", "test").await?, 0);
+	assert!(txn.list_migrations("test", "test").await?.is_empty());
+	assert!(matches!(
+		txn.revert_migration("test", "test").await,
+		Err(Error::NoMigrationToRollback)
+	));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn rollback_migration_script_statements() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let rid = Thing::from(("person", "tobie"));
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	for v in 1..=2 {
+		txn.log_migration("test", "test", &migration(v)).await?;
+		txn.set_migration_version("test", "test", v).await?;
+	}
+	txn.set_record("test", "test", &rid, &Value::from("test")).await?;
+	txn.commit().await?;
+	// Transaction statements are ignored in the down migration
+	let sql = "BEGIN TRANSACTION; OPTION IMPORT; DELETE person:tobie; COMMIT TRANSACTION;";
+	dbs.rollback_migration("test", "test", sql).await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.get_migration_version("test", "test").await?, 1);
+	assert!(txn.get_record("test", "test", &rid).await?.is_none());
+	txn.cancel().await?;
+	// The down migration can not change the database
+	let sql = "USE NS test DB other; REMOVE TABLE person;";
+	assert!(matches!(
+		dbs.rollback_migration("test", "test", sql).await,
+		Err(Error::MigrationStatement { .. })
+	));
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.get_migration_version("test", "test").await?, 1);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
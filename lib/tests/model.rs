@@ -54,3 +54,29 @@ async fn model_range() -> Result<(), Error> {
 	//
 	Ok(())
 }
+
+#[tokio::test]
+async fn model_count_without_group() -> Result<(), Error> {
+	let sql = "
+		CREATE |test:10| SET time = time::now();
+		SELECT count() FROM test START 8;
+		SELECT count() FROM test LIMIT 3;
+	";
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = &mut dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 3);
+	//
+	let tmp = res.remove(0).result;
+	assert!(tmp.is_ok());
+	//
+	let tmp = res.remove(0).result?;
+	let val = Value::parse("[{ count: 1 }, { count: 1 }]");
+	assert_eq!(tmp, val);
+	//
+	let tmp = res.remove(0).result?;
+	let val = Value::parse("[{ count: 1 }, { count: 1 }, { count: 1 }]");
+	assert_eq!(tmp, val);
+	//
+	Ok(())
+}
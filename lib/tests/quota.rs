@@ -0,0 +1,248 @@
+use surrealdb::sql::Thing;
+use surrealdb::sql::Value;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::QuotaDefinition;
+use surrealdb::QuotaUsage;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+// Measure the total size of the keys and values under a table key prefix
+async fn measure(dbs: &Datastore, tb: &str, beg: &[u8], end: &[u8]) -> Result<u64, Error> {
+	let key = format!("/*test\x00*test\x00*{}\x00", tb).into_bytes();
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.scan([&key, beg].concat()..[&key, end].concat(), u32::MAX).await?;
+	txn.cancel().await?;
+	Ok(res.iter().map(|(k, v)| (k.len() + v.len()) as u64).sum())
+}
+
+#[tokio::test]
+async fn quota_usage_of_database() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	// An empty database uses no resources
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.get_quota_usage("test", "test").await?, QuotaUsage::default());
+	txn.cancel().await?;
+	//
+	let sql = "
+		DEFINE INDEX age ON person FIELDS age;
+		CREATE person:1 SET age = 10;
+		CREATE person:2 SET age = 20;
+		CREATE person:3 SET age = 30;
+		CREATE team:1 SET name = 'SurrealDB';
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 5);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let _ = txn.subscribe_to_table("test", "test", "person", None).await?;
+	txn.commit().await?;
+	// The usage is measured across every table
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.get_quota_usage("test", "test").await?;
+	txn.cancel().await?;
+	let storage = measure(&dbs, "person", b"*\x00", b"*\xff").await?
+		+ measure(&dbs, "team", b"*\x00", b"*\xff").await?;
+	let index = measure(&dbs, "person", b"\xa4age\x00\x00", b"\xa4age\x00\xff").await?;
+	assert!(index > 0);
+	assert_eq!(
+		res,
+		QuotaUsage {
+			record_count: 4,
+			storage_bytes: storage,
+			index_bytes: index,
+			active_live_queries: 1,
+		}
+	);
+	// Removed records and indexes are no longer measured
+	let sql = "
+		REMOVE INDEX age ON person;
+		DELETE team:1;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 2);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.get_quota_usage("test", "test").await?;
+	txn.cancel().await?;
+	assert_eq!(res.record_count, 3);
+	assert_eq!(res.storage_bytes, measure(&dbs, "person", b"*\x00", b"*\xff").await?);
+	assert_eq!(res.index_bytes, 0);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn quota_enforced_on_existing_usage() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		CREATE person:1;
+		CREATE person:2;
+		CREATE team:1;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 3);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	// A database without a quota is unrestricted
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert!(txn.enforce_quota("test", "test").await.is_ok());
+	txn.cancel().await?;
+	// A quota above the current usage is not exceeded
+	let res = dbs.execute("DEFINE QUOTA ON DATABASE test RECORDS 3", &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert!(txn.enforce_quota("test", "test").await.is_ok());
+	txn.cancel().await?;
+	// A quota below the current usage is exceeded
+	let res = dbs.execute("DEFINE QUOTA ON DATABASE test RECORDS 2", &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.enforce_quota("test", "test").await;
+	txn.cancel().await?;
+	assert!(matches!(
+		res,
+		Err(Error::QuotaExceeded {
+			resource: "records",
+			limit: 2,
+			actual: 3
+		})
+	));
+	// The storage limit is checked against the size of every record
+	let sql = "DEFINE QUOTA ON DATABASE test STORAGE 1";
+	let res = dbs.execute(sql, &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	let storage = measure(&dbs, "person", b"*\x00", b"*\xff").await?
+		+ measure(&dbs, "team", b"*\x00", b"*\xff").await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.enforce_quota("test", "test").await;
+	txn.cancel().await?;
+	assert!(matches!(
+		res,
+		Err(Error::QuotaExceeded {
+			resource: "storage",
+			limit: 1,
+			actual,
+		}) if actual == storage
+	));
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn quota_set_and_removed() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute("CREATE person:1; CREATE person:2;", &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	// A database has no quota by default
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.get_quota("test", "test").await?, None);
+	txn.cancel().await?;
+	// Define a quota below the current usage
+	let quota = QuotaDefinition {
+		max_records: Some(1),
+		max_storage_bytes: None,
+		max_live_queries: Some(5),
+	};
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.set_quota("test", "test", &quota).await?;
+	assert_eq!(txn.get_quota("test", "test").await?, Some(quota.clone()));
+	txn.commit().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.get_quota("test", "test").await?, Some(quota));
+	assert!(matches!(
+		txn.enforce_quota("test", "test").await,
+		Err(Error::QuotaExceeded {
+			resource: "records",
+			limit: 1,
+			actual: 2
+		})
+	));
+	txn.cancel().await?;
+	// New records can not be created
+	let mut res = dbs.execute("CREATE person:3", &ses, None, false).await?;
+	assert!(matches!(res.remove(0).result, Err(Error::QuotaExceeded { .. })));
+	// Removing the quota leaves the database unrestricted
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.del_quota("test", "test").await?;
+	assert_eq!(txn.get_quota("test", "test").await?, None);
+	txn.commit().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert!(txn.enforce_quota("test", "test").await.is_ok());
+	txn.cancel().await?;
+	let mut res = dbs.execute("CREATE person:3", &ses, None, false).await?;
+	assert!(res.remove(0).result.is_ok());
+	// The usage is measured again when a new quota is defined
+	let quota = QuotaDefinition {
+		max_records: Some(3),
+		..QuotaDefinition::default()
+	};
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.set_quota("test", "test", &quota).await?;
+	txn.commit().await?;
+	let mut res = dbs.execute("CREATE person:4", &ses, None, false).await?;
+	assert!(matches!(
+		res.remove(0).result,
+		Err(Error::QuotaExceeded {
+			resource: "records",
+			limit: 3,
+			actual: 4
+		})
+	));
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn quota_usage_tracked_within_transaction() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let res = dbs.execute("DEFINE QUOTA ON DATABASE test RECORDS 2", &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	// Records created earlier in the transaction are counted
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.set_record("test", "test", &Thing::from(("person", "1")), &Value::from(1)).await?;
+	txn.set_record("test", "test", &Thing::from(("person", "2")), &Value::from(2)).await?;
+	let res = txn.set_record("test", "test", &Thing::from(("team", "1")), &Value::from(3)).await;
+	assert!(matches!(
+		res,
+		Err(Error::QuotaExceeded {
+			resource: "records",
+			limit: 2,
+			actual: 3
+		})
+	));
+	// Usage which is rolled back is released
+	let sp = txn.savepoint()?;
+	txn.del_record("test", "test", &Thing::from(("person", "2"))).await?;
+	txn.rollback_to(sp).await?;
+	txn.release_savepoint(sp)?;
+	assert!(txn.enforce_quota("test", "test").await.is_ok());
+	txn.set_record("test", "test", &Thing::from(("person", "1")), &Value::from(4)).await?;
+	txn.commit().await?;
+	// The usage is stored when the transaction is committed
+	let mut res = dbs.execute("CREATE person:3", &ses, None, false).await?;
+	assert!(matches!(
+		res.remove(0).result,
+		Err(Error::QuotaExceeded {
+			resource: "records",
+			limit: 2,
+			actual: 3
+		})
+	));
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.del_record("test", "test", &Thing::from(("person", "2"))).await?;
+	txn.commit().await?;
+	let mut res = dbs.execute("CREATE person:3", &ses, None, false).await?;
+	assert!(res.remove(0).result.is_ok());
+	//
+	Ok(())
+}
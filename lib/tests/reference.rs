@@ -0,0 +1,139 @@
+mod parse;
+use parse::Parse;
+use surrealdb::sql::Thing;
+use surrealdb::sql::Value;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+async fn datastore() -> Result<Datastore, Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE FIELD author ON post TYPE record(person) ON DELETE CASCADE;
+		DEFINE FIELD editor ON post TYPE record(person);
+		DEFINE FIELD post ON comment TYPE record(post) ON DELETE CASCADE;
+		DEFINE FIELD link ON bookmark TYPE record(person, post);
+		CREATE person:1;
+		CREATE person:2;
+		CREATE post:1 SET author = person:1, editor = person:2;
+		CREATE post:2 SET author = person:2, editor = person:1;
+		CREATE comment:1 SET post = post:1;
+		CREATE comment:2 SET post = post:2;
+		CREATE bookmark:1 SET link = person:1;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 11);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	Ok(dbs)
+}
+
+#[tokio::test]
+async fn foreign_key_checking() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let rid = Thing::parse("post:3");
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?.with_fk_checking(true);
+	assert!(txn.check_foreign_key("test", "test", &Thing::parse("person:1")).await?);
+	assert!(!txn.check_foreign_key("test", "test", &Thing::parse("person:9")).await?);
+	// Records referencing missing records are not stored
+	let val = Value::parse("{ id: post:3, author: person:1, editor: person:9 }");
+	match txn.set_record("test", "test", &rid, &val).await {
+		Err(Error::ForeignKeyViolation {
+			field,
+			missing,
+		}) => {
+			assert_eq!(field.to_string(), "editor");
+			assert_eq!(missing, Thing::parse("person:9"));
+		}
+		res => panic!("unexpected result: {:?}", res),
+	}
+	assert!(txn.get_record("test", "test", &rid).await?.is_none());
+	let val = Value::parse("{ id: post:3, author: person:1, editor: person:2 }");
+	txn.set_record("test", "test", &rid, &val).await?;
+	txn.cancel().await?;
+	// Records are not checked by default
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let val = Value::parse("{ id: post:3, author: person:9 }");
+	txn.set_record("test", "test", &rid, &val).await?;
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn get_referencing_records_of_record() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.get_referencing_records("test", "test", &Thing::parse("person:1")).await?;
+	let val = vec![
+		("bookmark".to_owned(), "link".to_owned(), Thing::parse("bookmark:1")),
+		("post".to_owned(), "author".to_owned(), Thing::parse("post:1")),
+		("post".to_owned(), "editor".to_owned(), Thing::parse("post:2")),
+	];
+	assert_eq!(res, val);
+	let res = txn.get_referencing_records("test", "test", &Thing::parse("post:2")).await?;
+	assert_eq!(res, vec![("comment".to_owned(), "post".to_owned(), Thing::parse("comment:2"))]);
+	let res = txn.get_referencing_records("test", "test", &Thing::parse("comment:1")).await?;
+	assert!(res.is_empty());
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn cascade_delete_removes_referencing_records() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	// Only the fields defined with cascading deletes are followed
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert_eq!(txn.cascade_delete("test", "test", &Thing::parse("person:1")).await?, 3);
+	assert_eq!(txn.cascade_delete("test", "test", &Thing::parse("person:1")).await?, 0);
+	txn.commit().await?;
+	let sql = "SELECT id FROM person, post, comment, bookmark;";
+	let res = &mut dbs.execute(&sql, &ses, None, false).await?;
+	let tmp = res.remove(0).result?;
+	let val =
+		Value::parse("[{ id: person:2 }, { id: post:2 }, { id: comment:2 }, { id: bookmark:1 }]");
+	assert_eq!(tmp, val);
+	// Circular references are only followed once
+	let sql = "
+		DEFINE FIELD best ON person TYPE record(post) ON DELETE CASCADE;
+		UPDATE person:2 SET best = post:2;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert_eq!(txn.cascade_delete("test", "test", &Thing::parse("post:2")).await?, 3);
+	txn.commit().await?;
+	let sql = "SELECT id FROM person, post, comment, bookmark;";
+	let res = &mut dbs.execute(&sql, &ses, None, false).await?;
+	let tmp = res.remove(0).result?;
+	let val = Value::parse("[{ id: bookmark:1 }]");
+	assert_eq!(tmp, val);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn get_referencing_tables_of_table() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.get_referencing_tables("test", "test", "person").await?;
+	let val = vec![
+		("bookmark".to_owned(), "link".to_owned()),
+		("post".to_owned(), "author".to_owned()),
+		("post".to_owned(), "editor".to_owned()),
+	];
+	assert_eq!(res, val);
+	let res = txn.get_referencing_tables("test", "test", "post").await?;
+	let val =
+		vec![("bookmark".to_owned(), "link".to_owned()), ("comment".to_owned(), "post".to_owned())];
+	assert_eq!(res, val);
+	assert!(txn.get_referencing_tables("test", "test", "bookmark").await?.is_empty());
+	txn.cancel().await?;
+	//
+	Ok(())
+}
@@ -0,0 +1,60 @@
+use surrealdb::sql::Thing;
+use surrealdb::sql::Value;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::QuotaDefinition;
+use surrealdb::TransactionType;
+
+#[tokio::test]
+async fn savepoint_rollback_to() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.set("a", "1").await?;
+	let sp1 = txn.savepoint()?;
+	txn.set("a", "2").await?;
+	txn.set("b", "1").await?;
+	let sp2 = txn.savepoint()?;
+	txn.del("a").await?;
+	// Rolling back restores the values at the savepoint
+	txn.rollback_to(sp1).await?;
+	assert_eq!(txn.get("a").await?, Some(b"1".to_vec()));
+	assert_eq!(txn.get("b").await?, None);
+	// Later savepoints are released by the rollback
+	assert!(matches!(txn.rollback_to(sp2).await, Err(Error::InvalidSavepoint)));
+	// The savepoint can be rolled back to again
+	txn.set("a", "3").await?;
+	txn.rollback_to(sp1).await?;
+	assert_eq!(txn.get("a").await?, Some(b"1".to_vec()));
+	// Released savepoints can not be used
+	txn.release_savepoint(sp1)?;
+	assert!(matches!(txn.rollback_to(sp1).await, Err(Error::InvalidSavepoint)));
+	assert!(matches!(txn.release_savepoint(sp1), Err(Error::InvalidSavepoint)));
+	txn.commit().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.get("a").await?, Some(b"1".to_vec()));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn savepoint_rollback_to_restores_definitions() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let quota = QuotaDefinition {
+		max_records: Some(1),
+		..QuotaDefinition::default()
+	};
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let sp = txn.savepoint()?;
+	txn.set_quota("test", "test", &quota).await?;
+	txn.set_record("test", "test", &Thing::from(("person", "1")), &Value::from(1)).await?;
+	// Rolling back removes the quota from the records which are stored later
+	txn.rollback_to(sp).await?;
+	txn.release_savepoint(sp)?;
+	txn.set_record("test", "test", &Thing::from(("person", "1")), &Value::from(1)).await?;
+	txn.set_record("test", "test", &Thing::from(("person", "2")), &Value::from(2)).await?;
+	txn.commit().await?;
+	//
+	Ok(())
+}
@@ -0,0 +1,157 @@
+mod parse;
+use parse::Parse;
+use surrealdb::sql::Thing;
+use surrealdb::sql::Value;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+#[tokio::test]
+async fn scan_with_projection_picks_fields() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		CREATE person:1 SET name = 'Tobie', age = 30, address.city = 'London';
+		CREATE person:2 SET name = 'Jaime', age = 25;
+		CREATE person:3 SET name = 'Lana', age = 20;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 3);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res =
+		txn.scan_with_projection("test", "test", "person", &["name", "address.city"], 2).await?;
+	txn.cancel().await?;
+	assert_eq!(
+		res,
+		vec![
+			(Thing::parse("person:1"), Value::parse("{ name: 'Tobie', 'address.city': 'London' }")),
+			(Thing::parse("person:2"), Value::parse("{ name: 'Jaime', 'address.city': NONE }")),
+		]
+	);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn scan_by_index_range_bounds_and_direction() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE INDEX age ON person FIELDS age;
+		CREATE person:1 SET age = 10;
+		CREATE person:2 SET age = 20;
+		CREATE person:3 SET age = 30;
+		CREATE person:4 SET age = 40;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 5);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let cases = [
+		(Value::from(20), Value::from(30), true, 10, vec![2, 3]),
+		(Value::from(20), Value::from(30), false, 10, vec![3, 2]),
+		(Value::None, Value::from(20), true, 10, vec![1, 2]),
+		(Value::Null, Value::from(20), false, 10, vec![2, 1]),
+		(Value::from(30), Value::Null, true, 10, vec![3, 4]),
+		(Value::from(20), Value::None, false, 10, vec![4, 3, 2]),
+		(Value::None, Value::None, true, 2, vec![1, 2]),
+		(Value::None, Value::None, false, 2, vec![4, 3]),
+	];
+	for (low, high, asc, limit, ids) in cases {
+		let res = txn.scan_by_index_range("test", "test", "person", "age", &low, &high, asc, limit);
+		let res: Vec<Thing> = res.await?.into_iter().map(|(t, _)| t).collect();
+		let ids: Vec<Thing> = ids.iter().map(|v| Thing::parse(&format!("person:{}", v))).collect();
+		assert_eq!(res, ids, "range {} to {} with asc {}", low, high, asc);
+	}
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn scan_with_sort_keeps_best_records() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	// Store more records than are scanned in a single batch
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	for i in 0..1500 {
+		let rid = Thing::from(("person".to_owned(), format!("{:04}", i)));
+		let val = Value::parse(&format!("{{ age: {} }}", i * 7 % 1500));
+		txn.set_record("test", "test", &rid, &val).await?;
+	}
+	for (id, v) in [("a", 1), ("b", 1), ("c", 0)] {
+		let val = Value::parse(&format!("{{ v: {} }}", v));
+		txn.set_record("test", "test", &Thing::from(("tie", id)), &val).await?;
+	}
+	txn.commit().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let ages = |res: Vec<(Thing, Value)>| res.into_iter().map(|(_, v)| v).collect::<Vec<_>>();
+	let res = txn.scan_with_sort("test", "test", "person", "age", true, 3).await?;
+	assert_eq!(
+		ages(res),
+		vec![Value::parse("{ age: 0 }"), Value::parse("{ age: 1 }"), Value::parse("{ age: 2 }")]
+	);
+	let res = txn.scan_with_sort("test", "test", "person", "age", false, 2).await?;
+	assert_eq!(ages(res), vec![Value::parse("{ age: 1499 }"), Value::parse("{ age: 1498 }")]);
+	assert!(txn.scan_with_sort("test", "test", "person", "age", true, 0).await?.is_empty());
+	// An unbounded limit returns every record
+	let res = txn.scan_with_sort("test", "test", "person", "age", true, u32::MAX).await?;
+	assert_eq!(res.len(), 1500);
+	// Equal values are kept in key order
+	let res = txn.scan_with_sort("test", "test", "tie", "v", false, 10).await?;
+	let ids: Vec<Thing> = res.into_iter().map(|(t, _)| t).collect();
+	assert_eq!(
+		ids,
+		vec![Thing::from(("tie", "a")), Thing::from(("tie", "b")), Thing::from(("tie", "c"))]
+	);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn scan_by_id_prefix_matches_string_ids() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	for id in ["tobie", "tobias", "tob", "to", "jaime"] {
+		let val = Value::parse(&format!("{{ name: '{}' }}", id));
+		txn.set_record("test", "test", &Thing::from(("person", id)), &val).await?;
+	}
+	txn.set_record("test", "test", &Thing::parse("person:1"), &Value::from(1)).await?;
+	txn.set_record("test", "test", &Thing::from(("user", "tobie")), &Value::from(1)).await?;
+	txn.commit().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let ids = |res: Vec<(Thing, Value)>| res.into_iter().map(|(t, _)| t).collect::<Vec<_>>();
+	let res = txn.scan_by_id_prefix("test", "test", "person", "tob", 10).await?;
+	assert_eq!(
+		ids(res),
+		vec![
+			Thing::from(("person", "tob")),
+			Thing::from(("person", "tobias")),
+			Thing::from(("person", "tobie")),
+		]
+	);
+	let res = txn.scan_by_id_prefix("test", "test", "person", "tobie", 10).await?;
+	assert_eq!(res, vec![(Thing::from(("person", "tobie")), Value::parse("{ name: 'tobie' }"))]);
+	// The number of records is limited
+	let res = txn.scan_by_id_prefix("test", "test", "person", "tob", 2).await?;
+	assert_eq!(ids(res), vec![Thing::from(("person", "tob")), Thing::from(("person", "tobias"))]);
+	// An empty prefix matches every string id
+	let res = txn.scan_by_id_prefix("test", "test", "person", "", 10).await?;
+	assert_eq!(ids(res).len(), 5);
+	// Numeric ids are never matched
+	assert!(txn.scan_by_id_prefix("test", "test", "person", "1", 10).await?.is_empty());
+	assert!(txn.scan_by_id_prefix("test", "test", "person", "x", 10).await?.is_empty());
+	txn.cancel().await?;
+	//
+	Ok(())
+}
@@ -0,0 +1,73 @@
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+async fn datastore() -> Result<Datastore, Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE TABLE person SCHEMAFULL;
+		DEFINE FIELD name ON person TYPE string ASSERT $value != NONE;
+		DEFINE FIELD age ON person TYPE int;
+		DEFINE FIELD address.city ON person TYPE string;
+		DEFINE FIELD friend ON person TYPE record(person);
+		DEFINE FIELD tags ON person TYPE array;
+		DEFINE FIELD tags.* ON person TYPE string;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 7);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	Ok(dbs)
+}
+
+#[tokio::test]
+async fn table_openapi_schema() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.get_tb_openapi_schema("test", "test", "person").await?;
+	txn.cancel().await?;
+	let val = serde_json::json!({
+		"title": "person",
+		"type": "object",
+		"properties": {
+			"id": { "type": "string", "format": "record", "readOnly": true },
+			"address": {
+				"type": "object",
+				"properties": {
+					"city": { "type": "string" },
+				},
+			},
+			"age": { "type": "integer", "format": "int64" },
+			"friend": { "type": "string", "format": "record" },
+			"name": { "type": "string" },
+			"tags": { "type": "array", "items": { "type": "string" } },
+		},
+		"required": ["id", "name"],
+		"additionalProperties": false,
+	});
+	assert_eq!(res, val);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn table_graphql_type() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.get_tb_graphql_type("test", "test", "person").await?;
+	txn.cancel().await?;
+	// Nested fields are not rendered
+	let val = "type Person {
+	id: ID!
+	age: Int
+	friend: Person
+	name: String!
+	tags: [String]
+}";
+	assert_eq!(res, val);
+	//
+	Ok(())
+}
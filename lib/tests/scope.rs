@@ -0,0 +1,118 @@
+use surrealdb::sql::statements::DefineStatement;
+use surrealdb::sql::statements::DefineTokenStatement;
+use surrealdb::sql::Statement;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+// Parse a token definition, along with its key on a scope
+fn token(sc: &str, tk: &str) -> (Vec<u8>, DefineTokenStatement) {
+	let sql = format!("DEFINE TOKEN {} ON DATABASE TYPE HS512 VALUE 'secret'", tk);
+	let mut stms = surrealdb::sql::parse(&sql).unwrap().0;
+	let key = format!("/*test\x00*test\x00!st{}\x00!tk{}\x00", sc, tk).into_bytes();
+	match stms.0.remove(0) {
+		Statement::Define(DefineStatement::Token(v)) => (key, v),
+		v => panic!("unexpected statement: {}", v),
+	}
+}
+
+// Retrieve the names of the token definitions on a scope
+async fn tokens(dbs: &Datastore, sc: &str) -> Result<Vec<String>, Error> {
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.all_st("test", "test", sc).await?;
+	txn.cancel().await?;
+	Ok(res.iter().map(|v| v.name.to_string()).collect())
+}
+
+async fn datastore() -> Result<Datastore, Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE SCOPE account SESSION 1h;
+		DEFINE SCOPE admin SESSION 5m;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 2);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	// Scope tokens can not be defined with a statement
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	for (sc, tk) in [("account", "api"), ("account", "web"), ("admin", "cli")] {
+		let (key, val) = token(sc, tk);
+		txn.set(key, &val).await?;
+	}
+	txn.commit().await?;
+	Ok(dbs)
+}
+
+#[tokio::test]
+async fn rename_scope_moves_tokens() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	assert_eq!(tokens(&dbs, "account").await?, vec!["api", "web"]);
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.rename_scope("test", "test", "account", "user").await?;
+	txn.commit().await?;
+	// The definition is stored under the new name only
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let sc = txn.get_sc("test", "test", "user").await?;
+	assert_eq!(sc.name.to_string(), "user");
+	assert!(sc.session.is_some());
+	assert!(matches!(txn.get_sc("test", "test", "account").await, Err(Error::ScNotFound)));
+	let res: Vec<String> =
+		txn.all_sc("test", "test").await?.iter().map(|v| v.name.to_string()).collect();
+	assert_eq!(res, vec!["admin", "user"]);
+	txn.cancel().await?;
+	// The tokens are moved along with the scope
+	assert_eq!(tokens(&dbs, "user").await?, vec!["api", "web"]);
+	assert!(tokens(&dbs, "account").await?.is_empty());
+	assert_eq!(tokens(&dbs, "admin").await?, vec!["cli"]);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn rename_scope_to_existing_or_from_missing_scope() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	// An existing scope is not overwritten
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let res = txn.rename_scope("test", "test", "account", "admin").await;
+	assert!(matches!(res, Err(Error::TxKeyAlreadyExists)));
+	txn.cancel().await?;
+	assert_eq!(tokens(&dbs, "account").await?, vec!["api", "web"]);
+	assert_eq!(tokens(&dbs, "admin").await?, vec!["cli"]);
+	// A missing scope can not be renamed
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let res = txn.rename_scope("test", "test", "missing", "other").await;
+	assert!(matches!(res, Err(Error::ScNotFound)));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn all_sc_tokens_flat_of_database() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let names = |res: &[DefineTokenStatement]| {
+		res.iter().map(|v| v.name.to_string()).collect::<Vec<String>>()
+	};
+	// Tokens are only listed for defined scopes
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let (key, val) = token("removed", "old");
+	txn.set(key, &val).await?;
+	txn.commit().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let res = txn.all_sc_tokens_flat("test", "test").await?;
+	assert_eq!(names(&res), vec!["api", "web", "cli"]);
+	// The cached tokens are updated when a scope is renamed
+	txn.rename_scope("test", "test", "account", "user").await?;
+	let res = txn.all_sc_tokens_flat("test", "test").await?;
+	assert_eq!(names(&res), vec!["cli", "api", "web"]);
+	assert!(txn.all_sc_tokens_flat("test", "other").await?.is_empty());
+	txn.cancel().await?;
+	//
+	Ok(())
+}
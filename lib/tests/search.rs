@@ -0,0 +1,71 @@
+mod parse;
+use parse::Parse;
+use surrealdb::sql::Thing;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+async fn datastore() -> Result<Datastore, Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE INDEX body ON article COLUMNS body;
+		CREATE article:1 SET body = 'The quick brown fox';
+		CREATE article:2 SET body = 'The lazy dog sleeps, the dog dreams';
+		CREATE article:3 SET body = 'A quick dog and a quick fox';
+		CREATE article:4 SET body = 42;
+		CREATE article:5;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 6);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	Ok(dbs)
+}
+
+#[tokio::test]
+async fn search_full_text_ranks_records() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	// Records with more occurrences of a term rank higher
+	let res = txn.search_full_text("test", "test", "article", "body", "dog", 10).await?;
+	let ids: Vec<Thing> = res.iter().map(|(t, _)| t.clone()).collect();
+	assert_eq!(ids, vec![Thing::parse("article:2"), Thing::parse("article:3")]);
+	assert!(res[0].1 > res[1].1 && res[1].1 > 0.0);
+	// Shorter records rank higher for the same terms
+	let res = txn.search_full_text("test", "test", "article", "body", "Quick FOX", 10).await?;
+	let ids: Vec<Thing> = res.iter().map(|(t, _)| t.clone()).collect();
+	assert_eq!(ids, vec![Thing::parse("article:1"), Thing::parse("article:3")]);
+	let res = txn.search_full_text("test", "test", "article", "body", "quick fox", 1).await?;
+	assert_eq!(res.len(), 1);
+	assert_eq!(res[0].0, Thing::parse("article:1"));
+	// Records without any of the terms are not returned
+	assert!(txn.search_full_text("test", "test", "article", "body", "cat", 10).await?.is_empty());
+	assert!(matches!(
+		txn.search_full_text("test", "test", "article", "other", "dog", 10).await,
+		Err(Error::IxNotFound)
+	));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn get_doc_frequency_counts_records() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	// Every index entry is counted in the total, and records without the field are not indexed
+	assert_eq!(txn.get_doc_frequency("test", "test", "article", "body", "dog").await?, (2, 4));
+	assert_eq!(txn.get_doc_frequency("test", "test", "article", "body", "QUICK").await?, (2, 4));
+	assert_eq!(txn.get_doc_frequency("test", "test", "article", "body", "lazy").await?, (1, 4));
+	assert_eq!(txn.get_doc_frequency("test", "test", "article", "body", "cat").await?, (0, 4));
+	assert!(matches!(
+		txn.get_doc_frequency("test", "test", "article", "other", "dog").await,
+		Err(Error::IxNotFound)
+	));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
@@ -0,0 +1,120 @@
+use chrono::Duration;
+use chrono::Utc;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::SessionRecord;
+use surrealdb::TransactionType;
+
+// Create a session which expires after a number of seconds
+fn session(id: &str, secs: i64) -> SessionRecord {
+	let now = Utc::now();
+	SessionRecord {
+		id: id.to_owned(),
+		sc: Some("account".to_owned()),
+		subject: format!("user:{}", id),
+		created_at: now,
+		expires_at: now + Duration::seconds(secs),
+	}
+}
+
+// The key range of the stored sessions of a database
+fn sessions(db: &str) -> std::ops::Range<Vec<u8>> {
+	let key = |v: u8| [format!("/*test\x00*{}\x00!se", db).as_bytes(), &[v]].concat();
+	key(0x00)..key(0xff)
+}
+
+// Retrieve the ids of the active sessions
+async fn active(dbs: &Datastore, db: Option<&str>) -> Result<Vec<String>, Error> {
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.scan_active_sessions("test", db).await?;
+	txn.cancel().await?;
+	Ok(res.into_iter().map(|v| v.id).collect())
+}
+
+async fn datastore() -> Result<Datastore, Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test");
+	let sql = "
+		DEFINE DATABASE one;
+		DEFINE DATABASE two;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 2);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.store_session("test", "one", &session("a", 3600)).await?;
+	txn.store_session("test", "one", &session("b", -3600)).await?;
+	txn.store_session("test", "one", &session("c", 60)).await?;
+	txn.store_session("test", "two", &session("d", 3600)).await?;
+	txn.store_session("other", "one", &session("e", 3600)).await?;
+	txn.commit().await?;
+	Ok(dbs)
+}
+
+#[tokio::test]
+async fn scan_and_count_active_sessions() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	// Expired sessions are not active
+	assert_eq!(active(&dbs, Some("one")).await?, vec!["a", "c"]);
+	assert_eq!(active(&dbs, Some("two")).await?, vec!["d"]);
+	assert!(active(&dbs, Some("three")).await?.is_empty());
+	// The sessions of every database in the namespace are included
+	assert_eq!(active(&dbs, None).await?, vec!["a", "c", "d"]);
+	//
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.scan_active_sessions("test", Some("two")).await?;
+	assert_eq!(res.len(), 1);
+	assert_eq!(res[0].subject, "user:d");
+	assert_eq!(res[0].sc.as_deref(), Some("account"));
+	assert_eq!(txn.count_active_sessions("test", Some("one")).await?, 2);
+	assert_eq!(txn.count_active_sessions("test", None).await?, 3);
+	assert_eq!(txn.count_active_sessions("other", None).await?, 0);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn invalidate_session_of_database() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.invalidate_session("test", "one", "a").await?;
+	// Sessions are only invalidated in the specified database
+	txn.invalidate_session("test", "one", "d").await?;
+	txn.invalidate_session("test", "one", "missing").await?;
+	txn.commit().await?;
+	//
+	assert_eq!(active(&dbs, Some("one")).await?, vec!["c"]);
+	assert_eq!(active(&dbs, None).await?, vec!["c", "d"]);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn purge_expired_sessions_of_database() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	// A session which expires now is no longer active
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.store_session("test", "two", &session("f", 0)).await?;
+	txn.commit().await?;
+	assert_eq!(active(&dbs, Some("two")).await?, vec!["d"]);
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert_eq!(txn.purge_expired_sessions("test", "one").await?, 1);
+	assert_eq!(txn.purge_expired_sessions("test", "one").await?, 0);
+	assert_eq!(txn.purge_expired_sessions("test", "two").await?, 1);
+	txn.commit().await?;
+	// Only the expired sessions are removed
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.count_range(sessions("one")).await?, 2);
+	assert_eq!(txn.count_range(sessions("two")).await?, 1);
+	txn.cancel().await?;
+	assert_eq!(active(&dbs, None).await?, vec!["a", "c", "d"]);
+	//
+	Ok(())
+}
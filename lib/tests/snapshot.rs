@@ -0,0 +1,38 @@
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::TransactionType;
+
+#[tokio::test]
+async fn snapshot_reads_are_consistent() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	// Write more keys than are fetched in a single batch
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	for i in 0..1500 {
+		txn.set(format!("test{:04}", i).into_bytes(), b"one".to_vec()).await?;
+	}
+	txn.commit().await?;
+	// Pin a snapshot before the keys are changed
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let token = txn.begin_consistent_snapshot().await?;
+	let mut tmp = dbs.transaction(TransactionType::Write, false).await?;
+	tmp.set(b"test0000".to_vec(), b"two".to_vec()).await?;
+	tmp.set(b"test9999".to_vec(), b"two".to_vec()).await?;
+	tmp.commit().await?;
+	// The changes are not visible in the snapshot
+	let res = txn.scan_with_snapshot(&token, b"test".to_vec()..b"tesu".to_vec(), 10).await?;
+	assert_eq!(res.len(), 10);
+	assert_eq!(res[0], (b"test0000".to_vec(), b"one".to_vec()));
+	let res = txn.getr_with_snapshot(&token, b"test".to_vec()..b"tesu".to_vec(), u32::MAX).await?;
+	assert_eq!(res.len(), 1500);
+	assert!(res.iter().all(|(_, v)| v == b"one"));
+	assert_eq!(res[1499].0, b"test1499".to_vec());
+	// The limit applies across batches
+	let res = txn.getr_with_snapshot(&token, b"test".to_vec()..b"tesu".to_vec(), 1200).await?;
+	assert_eq!(res.len(), 1200);
+	assert_eq!(res[1199].0, b"test1199".to_vec());
+	txn.cancel().await?;
+	// A snapshot can not be pinned on a closed transaction
+	assert!(matches!(txn.begin_consistent_snapshot().await, Err(Error::TxFinished)));
+	//
+	Ok(())
+}
@@ -0,0 +1,45 @@
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+async fn datastore() -> Result<Datastore, Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE TABLE open;
+		DEFINE TABLE secret PERMISSIONS NONE;
+		DEFINE TABLE partial PERMISSIONS FOR select FULL, FOR create NONE;
+		DEFINE INDEX name ON secret COLUMNS name;
+		CREATE implicit:1;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 5);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	Ok(dbs)
+}
+
+#[tokio::test]
+async fn scan_tables_without_indexes() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.scan_tables_without_indexes("test", "test").await?;
+	assert_eq!(res, vec!["implicit", "open", "partial"]);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn scan_tables_without_permissions() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	// Tables which are created implicitly deny access by default
+	let res = txn.scan_tables_without_permissions("test", "test").await?;
+	assert_eq!(res, vec!["open"]);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
@@ -0,0 +1,92 @@
+use surrealdb::channel;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::Timestamp;
+use surrealdb::TransactionType;
+
+#[tokio::test]
+async fn truncate_table_removes_records() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		DEFINE TABLE person CHANGEFEED 1h;
+		DEFINE INDEX email ON TABLE person COLUMNS email UNIQUE;
+		DEFINE TABLE adult AS SELECT * FROM person WHERE age >= 18;
+		DEFINE QUOTA ON DATABASE test RECORDS 5;
+		CREATE person:1 SET age = 10, email = 'one@surrealdb.com';
+		CREATE person:2 SET age = 20, email = 'two@surrealdb.com';
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 6);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	// Take the timestamp after the last change
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let since = Timestamp(txn.get_tb_version("test", "test", "person").await? + 1);
+	txn.set_materialized_view_refreshed("test", "test", "adult").await?;
+	txn.commit().await?;
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert_eq!(txn.truncate_table("test", "test", "person").await?, 2);
+	assert_eq!(txn.truncate_table("test", "test", "person").await?, 0);
+	txn.commit().await?;
+	// The dependent view is stale, and is emptied when refreshed
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert_eq!(txn.refresh_materialized_view("test", "test", "adult").await?, 0);
+	assert_eq!(txn.count_prefix(b"/*test\x00*test\x00*adult\x00*".to_vec()).await?, 0);
+	txn.cancel().await?;
+	// The removal of each record is in the change feed
+	let (snd, rcv) = channel::new(1000);
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	txn.export_since("test", "test", since, snd).await?;
+	txn.cancel().await?;
+	let mut out = vec![];
+	while let Ok(v) = rcv.try_recv() {
+		out.push(String::from_utf8(v).unwrap());
+	}
+	let out: Vec<&str> = out
+		.iter()
+		.map(|v| v.trim())
+		.filter(|v| v.starts_with("UPDATE") || v.starts_with("DELETE"))
+		.collect();
+	assert_eq!(out, vec!["DELETE person:1;", "DELETE person:2;"]);
+	// The index entries and the quota usage are released
+	let sql = "
+		CREATE person:3 SET email = 'one@surrealdb.com';
+		CREATE person:4 SET email = 'two@surrealdb.com';
+		CREATE user:1;
+		CREATE user:2;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 4);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn truncate_table_without_quota() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		CREATE person:1 SET age = 10;
+		CREATE person:2 SET age = 20;
+		CREATE person:3 SET age = 30;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 3);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	//
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert_eq!(txn.truncate_table("test", "test", "person").await?, 3);
+	assert_eq!(txn.count_prefix(b"/*test\x00*test\x00*person\x00*".to_vec()).await?, 0);
+	txn.commit().await?;
+	//
+	Ok(())
+}
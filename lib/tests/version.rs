@@ -0,0 +1,63 @@
+use surrealdb::sql::Thing;
+use surrealdb::sql::Value;
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::TransactionType;
+
+// The key range of the version keys of the test table
+fn versions() -> std::ops::Range<Vec<u8>> {
+	b"/*test\x00*test\x00*person\x00!vs\x00".to_vec()
+		..b"/*test\x00*test\x00*person\x00!vs\xff".to_vec()
+}
+
+#[tokio::test]
+async fn superseded_table_versions_are_removed() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let rid = Thing::from(("person", "tobie"));
+	// Every writing transaction records a table version
+	for i in 0..2500 {
+		let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+		txn.set_record("test", "test", &rid, &Value::from(i)).await?;
+		txn.commit().await?;
+		dbs.tick().await?;
+	}
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let num = txn.count_range(versions()).await?;
+	assert!(num > 0 && num <= 1001, "unexpected number of table versions: {}", num);
+	let ver = txn.get_tb_version("test", "test", "person").await?;
+	txn.cancel().await?;
+	// Only the latest version is kept
+	dbs.compact_table_versions().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert_eq!(txn.count_range(versions()).await?, 1);
+	assert_eq!(txn.get_tb_version("test", "test", "person").await?, ver);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn table_versions_are_taken_at_commit() -> Result<(), Error> {
+	let dbs = Datastore::new("memory").await?;
+	let rid = Thing::from(("person", "tobie"));
+	let now = || std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap();
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.set_record("test", "test", &rid, &Value::from(1)).await?;
+	// The version is not recorded until the transaction is committed
+	assert_eq!(txn.get_tb_version("test", "test", "person").await?, 0);
+	let before = now().as_micros() as u64;
+	txn.commit().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let ver = txn.get_tb_version("test", "test", "person").await?;
+	txn.cancel().await?;
+	assert!(ver >= before, "version {} was taken before the commit at {}", ver, before);
+	// Later transactions have later versions
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.set_record("test", "test", &rid, &Value::from(2)).await?;
+	txn.commit().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	assert!(txn.get_tb_version("test", "test", "person").await? > ver);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
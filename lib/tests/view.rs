@@ -0,0 +1,99 @@
+use surrealdb::Datastore;
+use surrealdb::Error;
+use surrealdb::Session;
+use surrealdb::TransactionType;
+
+async fn datastore() -> Result<Datastore, Error> {
+	let dbs = Datastore::new("memory").await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	let sql = "
+		CREATE person:1 SET age = 20;
+		CREATE person:2 SET age = 30;
+		DEFINE TABLE adult AS SELECT * FROM person WHERE age >= 18;
+	";
+	let res = dbs.execute(&sql, &ses, None, false).await?;
+	assert_eq!(res.len(), 3);
+	for v in res.into_iter() {
+		assert!(v.result.is_ok());
+	}
+	Ok(dbs)
+}
+
+// Count the records of a table
+async fn count(dbs: &Datastore, tb: &str) -> Result<u64, Error> {
+	let key = format!("/*test\x00*test\x00*{}\x00*", tb).into_bytes();
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let res = txn.count_prefix(key).await?;
+	txn.cancel().await?;
+	Ok(res)
+}
+
+#[tokio::test]
+async fn refresh_materialized_view_only_when_stale() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let ses = Session::for_kv().with_ns("test").with_db("test");
+	assert_eq!(count(&dbs, "adult").await?, 2);
+	// An up to date view is left untouched
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert_eq!(txn.refresh_materialized_view("test", "test", "adult").await?, 0);
+	txn.commit().await?;
+	assert_eq!(count(&dbs, "adult").await?, 2);
+	// A change to a source table makes the view stale, and it is repopulated
+	let res = dbs.execute("CREATE person:3 SET age = 40", &ses, None, false).await?;
+	assert!(res.into_iter().all(|v| v.result.is_ok()));
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert_eq!(txn.refresh_materialized_view("test", "test", "adult").await?, 3);
+	assert_eq!(txn.count_prefix(b"/*test\x00*test\x00*adult\x00*".to_vec()).await?, 3);
+	txn.commit().await?;
+	assert_eq!(count(&dbs, "adult").await?, 3);
+	// Only views can be refreshed
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	let res = txn.refresh_materialized_view("test", "test", "person").await;
+	assert!(matches!(res, Err(Error::TableIsNotView { table }) if table == "person"));
+	txn.cancel().await?;
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn invalidate_materialized_view_forces_refresh() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	// Invalidate the view, without changing its source tables
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert_eq!(txn.refresh_materialized_view("test", "test", "adult").await?, 0);
+	txn.invalidate_materialized_view("test", "test", "adult").await?;
+	txn.commit().await?;
+	// The invalidated view is stale
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert_eq!(txn.refresh_materialized_view("test", "test", "adult").await?, 2);
+	txn.cancel().await?;
+	// The view is up to date once the refresh has been recorded
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.set_materialized_view_refreshed("test", "test", "adult").await?;
+	assert_eq!(txn.refresh_materialized_view("test", "test", "adult").await?, 0);
+	txn.cancel().await?;
+	assert_eq!(count(&dbs, "adult").await?, 2);
+	//
+	Ok(())
+}
+
+#[tokio::test]
+async fn refresh_materialized_view_does_not_change_sources() -> Result<(), Error> {
+	let dbs = datastore().await?;
+	let mut txn = dbs.transaction(TransactionType::Read, false).await?;
+	let ver = txn.get_tb_version("test", "test", "person").await?;
+	txn.cancel().await?;
+	// Repopulate the view
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	txn.invalidate_materialized_view("test", "test", "adult").await?;
+	assert_eq!(txn.refresh_materialized_view("test", "test", "adult").await?, 2);
+	txn.commit().await?;
+	// The source table is unchanged, and the view is up to date
+	let mut txn = dbs.transaction(TransactionType::Write, false).await?;
+	assert_eq!(txn.get_tb_version("test", "test", "person").await?, ver);
+	assert_ne!(txn.get_tb_version("test", "test", "adult").await?, 0);
+	assert_eq!(txn.refresh_materialized_view("test", "test", "adult").await?, 0);
+	txn.cancel().await?;
+	//
+	Ok(())
+}
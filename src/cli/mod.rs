@@ -45,6 +45,9 @@ fn path_valid(v: &str) -> Result<(), String> {
 	if v.starts_with("file://") {
 		return Ok(());
 	}
+	if v.starts_with("rocksdb://") {
+		return Ok(());
+	}
 	if v.starts_with("tikv://") {
 		return Ok(());
 	}
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 pub const LOGO: &str = "
  .d8888b.                                             888 8888888b.  888888b.
 d88P  Y88b                                            888 888  'Y88b 888  '88b
@@ -22,3 +24,6 @@ pub const APP_ENDPOINT: &str = "https://app.surrealdb.com";
 
 // Specifies how many concurrent jobs can be buffered in the worker channel.
 pub const MAX_CONCURRENT_CALLS: usize = 24;
+
+// Specifies how often the periodic maintenance of the datastore is performed.
+pub const MAINTENANCE_INTERVAL: Duration = Duration::from_secs(1);
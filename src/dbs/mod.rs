@@ -1,4 +1,5 @@
 use crate::cli::CF;
+use crate::cnf::MAINTENANCE_INTERVAL;
 use crate::err::Error;
 use once_cell::sync::OnceCell;
 use surrealdb::Datastore;
@@ -19,6 +20,16 @@ pub async fn init() -> Result<(), Error> {
 	let dbs = Datastore::new(&opt.path).await?;
 	// Store database instance
 	let _ = DB.set(dbs);
+	// Perform periodic maintenance in the background
+	tokio::spawn(async {
+		let mut interval = tokio::time::interval(MAINTENANCE_INTERVAL);
+		loop {
+			interval.tick().await;
+			if let Err(e) = DB.get().unwrap().tick().await {
+				warn!(target: LOG, "Unable to perform datastore maintenance: {}", e);
+			}
+		}
+	});
 	// All ok
 	Ok(())
 }
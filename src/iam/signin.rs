@@ -2,13 +2,17 @@ use crate::cli::CF;
 use crate::cnf::SERVER_NAME;
 use crate::dbs::DB;
 use crate::err::Error;
-use crate::iam::token::{Claims, HEADER};
+use crate::iam::token::Claims;
 use argon2::password_hash::{PasswordHash, PasswordVerifier};
 use argon2::Argon2;
 use chrono::{Duration, Utc};
-use jsonwebtoken::{encode, EncodingKey};
+use jsonwebtoken::encode;
+use surrealdb::jwt::signer;
+use surrealdb::sql::Algorithm;
 use surrealdb::sql::Object;
+use surrealdb::sql::Value;
 use surrealdb::Session;
+use surrealdb::TransactionType;
 
 pub async fn signin(vars: Object) -> Result<String, Error> {
 	// Parse the specified variables
@@ -80,7 +84,7 @@ pub async fn sc(ns: String, db: String, sc: String, vars: Object) -> Result<Stri
 	// Get local copy of options
 	let opt = CF.get().unwrap();
 	// Create a new readonly transaction
-	let mut tx = kvs.transaction(false, false).await?;
+	let mut tx = kvs.transaction(TransactionType::Read, false).await?;
 	// Check if the supplied NS Login exists
 	match tx.get_sc(&ns, &db, &sc).await {
 		Ok(sv) => {
@@ -97,26 +101,15 @@ pub async fn sc(ns: String, db: String, sc: String, vars: Object) -> Result<Stri
 						Ok(val) => match val.rid() {
 							// There is a record returned
 							Some(rid) => {
-								// Create the authentication key
-								let key = EncodingKey::from_secret(sv.code.as_ref());
-								// Create the authentication claim
-								let val = Claims {
-									iss: SERVER_NAME.to_owned(),
-									iat: Utc::now().timestamp(),
-									nbf: Utc::now().timestamp(),
-									exp: match sv.session {
-										Some(v) => Utc::now() + Duration::from_std(v.0).unwrap(),
-										_ => Utc::now() + Duration::hours(1),
-									}
-									.timestamp(),
-									ns: Some(ns),
-									db: Some(db),
-									sc: Some(sc),
-									id: Some(rid.to_raw()),
-									..Claims::default()
-								};
 								// Create the authentication token
-								match encode(&*HEADER, &val, &key) {
+								let id = rid.to_raw();
+								let sc = Some(sc.as_str());
+								let exp = match sv.session {
+									Some(v) => v.0,
+									_ => std::time::Duration::from_secs(3600),
+								};
+								let res = tx.issue_jwt(&ns, &db, sc, &id, Value::None, exp);
+								match res.await {
 									// The auth token was created successfully
 									Ok(tk) => Ok(tk),
 									// There was an error creating the token
@@ -143,7 +136,7 @@ pub async fn db(ns: String, db: String, user: String, pass: String) -> Result<St
 	// Get a database reference
 	let kvs = DB.get().unwrap();
 	// Create a new readonly transaction
-	let mut tx = kvs.transaction(false, false).await?;
+	let mut tx = kvs.transaction(TransactionType::Read, false).await?;
 	// Check if the supplied DB Login exists
 	match tx.get_dl(&ns, &db, &user).await {
 		Ok(dl) => {
@@ -153,7 +146,7 @@ pub async fn db(ns: String, db: String, user: String, pass: String) -> Result<St
 			match Argon2::default().verify_password(pass.as_ref(), &hash) {
 				Ok(_) => {
 					// Create the authentication key
-					let key = EncodingKey::from_secret(dl.code.as_ref());
+					let (key, head) = signer(&Algorithm::Hs512, &dl.code)?;
 					// Create the authentication claim
 					let val = Claims {
 						iss: SERVER_NAME.to_owned(),
@@ -166,7 +159,7 @@ pub async fn db(ns: String, db: String, user: String, pass: String) -> Result<St
 						..Claims::default()
 					};
 					// Create the authentication token
-					match encode(&*HEADER, &val, &key) {
+					match encode(&head, &val, &key) {
 						// The auth token was created successfully
 						Ok(tk) => Ok(tk),
 						// There was an error creating the token
@@ -186,7 +179,7 @@ pub async fn ns(ns: String, user: String, pass: String) -> Result<String, Error>
 	// Get a database reference
 	let kvs = DB.get().unwrap();
 	// Create a new readonly transaction
-	let mut tx = kvs.transaction(false, false).await?;
+	let mut tx = kvs.transaction(TransactionType::Read, false).await?;
 	// Check if the supplied NS Login exists
 	match tx.get_nl(&ns, &user).await {
 		Ok(nl) => {
@@ -196,7 +189,7 @@ pub async fn ns(ns: String, user: String, pass: String) -> Result<String, Error>
 			match Argon2::default().verify_password(pass.as_ref(), &hash) {
 				Ok(_) => {
 					// Create the authentication key
-					let key = EncodingKey::from_secret(nl.code.as_ref());
+					let (key, head) = signer(&Algorithm::Hs512, &nl.code)?;
 					// Create the authentication claim
 					let val = Claims {
 						iss: SERVER_NAME.to_owned(),
@@ -208,7 +201,7 @@ pub async fn ns(ns: String, user: String, pass: String) -> Result<String, Error>
 						..Claims::default()
 					};
 					// Create the authentication token
-					match encode(&*HEADER, &val, &key) {
+					match encode(&head, &val, &key) {
 						// The auth token was created successfully
 						Ok(tk) => Ok(tk),
 						// There was an error creating the token
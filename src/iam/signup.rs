@@ -1,12 +1,11 @@
 use crate::cli::CF;
-use crate::cnf::SERVER_NAME;
 use crate::dbs::DB;
 use crate::err::Error;
-use crate::iam::token::{Claims, HEADER};
-use chrono::{Duration, Utc};
-use jsonwebtoken::{encode, EncodingKey};
+use std::time::Duration;
 use surrealdb::sql::Object;
+use surrealdb::sql::Value;
 use surrealdb::Session;
+use surrealdb::TransactionType;
 
 pub async fn signup(vars: Object) -> Result<String, Error> {
 	// Parse the specified variables
@@ -35,7 +34,7 @@ pub async fn sc(ns: String, db: String, sc: String, vars: Object) -> Result<Stri
 	// Get local copy of options
 	let opt = CF.get().unwrap();
 	// Create a new readonly transaction
-	let mut tx = kvs.transaction(false, false).await?;
+	let mut tx = kvs.transaction(TransactionType::Read, false).await?;
 	// Check if the supplied NS Login exists
 	match tx.get_sc(&ns, &db, &sc).await {
 		Ok(sv) => {
@@ -52,26 +51,15 @@ pub async fn sc(ns: String, db: String, sc: String, vars: Object) -> Result<Stri
 						Ok(val) => match val.rid() {
 							// There is a record returned
 							Some(rid) => {
-								// Create the authentication key
-								let key = EncodingKey::from_secret(sv.code.as_ref());
-								// Create the authentication claim
-								let val = Claims {
-									iss: SERVER_NAME.to_owned(),
-									iat: Utc::now().timestamp(),
-									nbf: Utc::now().timestamp(),
-									exp: match sv.session {
-										Some(v) => Utc::now() + Duration::from_std(v.0).unwrap(),
-										_ => Utc::now() + Duration::hours(1),
-									}
-									.timestamp(),
-									ns: Some(ns),
-									db: Some(db),
-									sc: Some(sc),
-									id: Some(rid.to_raw()),
-									..Claims::default()
-								};
 								// Create the authentication token
-								match encode(&*HEADER, &val, &key) {
+								let id = rid.to_raw();
+								let sc = Some(sc.as_str());
+								let exp = match sv.session {
+									Some(v) => v.0,
+									_ => Duration::from_secs(3600),
+								};
+								let res = tx.issue_jwt(&ns, &db, sc, &id, Value::None, exp);
+								match res.await {
 									// The auth token was created successfully
 									Ok(tk) => Ok(tk),
 									// There was an error creating the token
@@ -1,9 +1,5 @@
-use jsonwebtoken::{Algorithm, Header};
-use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 
-pub static HEADER: Lazy<Header> = Lazy::new(|| Header::new(Algorithm::HS512));
-
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Claims {
 	pub iat: i64,
@@ -20,4 +16,6 @@ pub struct Claims {
 	pub tk: Option<String>,
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub id: Option<String>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub nonce: Option<u64>,
 }
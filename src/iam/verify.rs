@@ -7,67 +7,12 @@ use argon2::Argon2;
 use jsonwebtoken::{decode, DecodingKey, Validation};
 use once_cell::sync::Lazy;
 use std::sync::Arc;
+use surrealdb::jwt::config;
 use surrealdb::sql::Algorithm;
 use surrealdb::sql::Value;
 use surrealdb::Auth;
 use surrealdb::Session;
-
-fn config(algo: Algorithm, code: String) -> Result<(DecodingKey, Validation), Error> {
-	match algo {
-		Algorithm::Hs256 => Ok((
-			DecodingKey::from_secret(code.as_ref()),
-			Validation::new(jsonwebtoken::Algorithm::HS256),
-		)),
-		Algorithm::Hs384 => Ok((
-			DecodingKey::from_secret(code.as_ref()),
-			Validation::new(jsonwebtoken::Algorithm::HS384),
-		)),
-		Algorithm::Hs512 => Ok((
-			DecodingKey::from_secret(code.as_ref()),
-			Validation::new(jsonwebtoken::Algorithm::HS512),
-		)),
-		Algorithm::EdDSA => Ok((
-			DecodingKey::from_ed_pem(code.as_ref())?,
-			Validation::new(jsonwebtoken::Algorithm::EdDSA),
-		)),
-		Algorithm::Es256 => Ok((
-			DecodingKey::from_ec_pem(code.as_ref())?,
-			Validation::new(jsonwebtoken::Algorithm::ES256),
-		)),
-		Algorithm::Es384 => Ok((
-			DecodingKey::from_ec_pem(code.as_ref())?,
-			Validation::new(jsonwebtoken::Algorithm::ES384),
-		)),
-		Algorithm::Es512 => Ok((
-			DecodingKey::from_ec_pem(code.as_ref())?,
-			Validation::new(jsonwebtoken::Algorithm::ES384),
-		)),
-		Algorithm::Ps256 => Ok((
-			DecodingKey::from_rsa_pem(code.as_ref())?,
-			Validation::new(jsonwebtoken::Algorithm::PS256),
-		)),
-		Algorithm::Ps384 => Ok((
-			DecodingKey::from_rsa_pem(code.as_ref())?,
-			Validation::new(jsonwebtoken::Algorithm::PS384),
-		)),
-		Algorithm::Ps512 => Ok((
-			DecodingKey::from_rsa_pem(code.as_ref())?,
-			Validation::new(jsonwebtoken::Algorithm::PS512),
-		)),
-		Algorithm::Rs256 => Ok((
-			DecodingKey::from_rsa_pem(code.as_ref())?,
-			Validation::new(jsonwebtoken::Algorithm::RS256),
-		)),
-		Algorithm::Rs384 => Ok((
-			DecodingKey::from_rsa_pem(code.as_ref())?,
-			Validation::new(jsonwebtoken::Algorithm::RS384),
-		)),
-		Algorithm::Rs512 => Ok((
-			DecodingKey::from_rsa_pem(code.as_ref())?,
-			Validation::new(jsonwebtoken::Algorithm::RS512),
-		)),
-	}
-}
+use surrealdb::TransactionType;
 
 static KEY: Lazy<DecodingKey> = Lazy::new(|| DecodingKey::from_secret(&[]));
 
@@ -106,7 +51,7 @@ pub async fn basic(session: &mut Session, auth: String) -> Result<(), Error> {
 			// Check if this is NS authentication
 			if let Some(ns) = &session.ns {
 				// Create a new readonly transaction
-				let mut tx = kvs.transaction(false, false).await?;
+				let mut tx = kvs.transaction(TransactionType::Read, false).await?;
 				// Check if the supplied NS Login exists
 				if let Ok(nl) = tx.get_nl(ns, user).await {
 					// Compute the hash and verify the password
@@ -154,12 +99,12 @@ pub async fn token(session: &mut Session, auth: String) -> Result<(), Error> {
 				..
 			} => {
 				// Create a new readonly transaction
-				let mut tx = kvs.transaction(false, false).await?;
+				let mut tx = kvs.transaction(TransactionType::Read, false).await?;
 				// Parse the record id
 				let id = surrealdb::sql::thing(&id)?;
 				// Get the scope token
 				let de = tx.get_st(&ns, &db, &sc, &tk).await?;
-				let cf = config(de.kind, de.code)?;
+				let cf = config(&de.kind, &de.code)?;
 				// Verify the token
 				decode::<Claims>(auth, &cf.0, &cf.1)?;
 				// Set the session
@@ -176,17 +121,22 @@ pub async fn token(session: &mut Session, auth: String) -> Result<(), Error> {
 				db: Some(db),
 				sc: Some(sc),
 				id: Some(id),
+				nonce,
 				..
 			} => {
 				// Create a new readonly transaction
-				let mut tx = kvs.transaction(false, false).await?;
+				let mut tx = kvs.transaction(TransactionType::Read, false).await?;
 				// Parse the record id
 				let id = surrealdb::sql::thing(&id)?;
 				// Get the scope
 				let de = tx.get_sc(&ns, &db, &sc).await?;
-				let cf = config(Algorithm::Hs512, de.code)?;
+				let cf = config(&Algorithm::Hs512, &de.code)?;
 				// Verify the token
 				decode::<Claims>(auth, &cf.0, &cf.1)?;
+				// Check the token has not been revoked
+				if nonce.unwrap_or(0) != de.nonce {
+					return Err(Error::InvalidAuth);
+				}
 				// Set the session
 				session.ns = Some(ns.to_owned());
 				session.db = Some(db.to_owned());
@@ -203,10 +153,10 @@ pub async fn token(session: &mut Session, auth: String) -> Result<(), Error> {
 				..
 			} => {
 				// Create a new readonly transaction
-				let mut tx = kvs.transaction(false, false).await?;
+				let mut tx = kvs.transaction(TransactionType::Read, false).await?;
 				// Get the database token
 				let de = tx.get_dt(&ns, &db, &tk).await?;
-				let cf = config(de.kind, de.code)?;
+				let cf = config(&de.kind, &de.code)?;
 				// Verify the token
 				decode::<Claims>(auth, &cf.0, &cf.1)?;
 				// Set the session
@@ -223,10 +173,10 @@ pub async fn token(session: &mut Session, auth: String) -> Result<(), Error> {
 				..
 			} => {
 				// Create a new readonly transaction
-				let mut tx = kvs.transaction(false, false).await?;
+				let mut tx = kvs.transaction(TransactionType::Read, false).await?;
 				// Get the database login
 				let de = tx.get_dl(&ns, &db, &id).await?;
-				let cf = config(Algorithm::Hs512, de.code)?;
+				let cf = config(&Algorithm::Hs512, &de.code)?;
 				// Verify the token
 				decode::<Claims>(auth, &cf.0, &cf.1)?;
 				// Set the session
@@ -242,10 +192,10 @@ pub async fn token(session: &mut Session, auth: String) -> Result<(), Error> {
 				..
 			} => {
 				// Create a new readonly transaction
-				let mut tx = kvs.transaction(false, false).await?;
+				let mut tx = kvs.transaction(TransactionType::Read, false).await?;
 				// Get the namespace token
 				let de = tx.get_nt(&ns, &tk).await?;
-				let cf = config(de.kind, de.code)?;
+				let cf = config(&de.kind, &de.code)?;
 				// Verify the token
 				decode::<Claims>(auth, &cf.0, &cf.1)?;
 				// Set the session
@@ -260,10 +210,10 @@ pub async fn token(session: &mut Session, auth: String) -> Result<(), Error> {
 				..
 			} => {
 				// Create a new readonly transaction
-				let mut tx = kvs.transaction(false, false).await?;
+				let mut tx = kvs.transaction(TransactionType::Read, false).await?;
 				// Get the namespace login
 				let de = tx.get_nl(&ns, &id).await?;
-				let cf = config(Algorithm::Hs512, de.code)?;
+				let cf = config(&Algorithm::Hs512, &de.code)?;
 				// Verify the token
 				decode::<Claims>(auth, &cf.0, &cf.1)?;
 				// Set the session
@@ -0,0 +1,28 @@
+use crate::dbs::DB;
+use crate::err::Error;
+use surrealdb::TransactionType;
+use warp::http;
+use warp::Filter;
+
+pub fn config() -> impl Filter<Extract = impl warp::Reply, Error = warp::Rejection> + Clone {
+	warp::path("health").and(warp::path::end()).and(warp::get()).and_then(handler)
+}
+
+async fn handler() -> Result<impl warp::Reply, warp::Rejection> {
+	// Get the datastore reference
+	let db = DB.get().unwrap();
+	// Start a new read transaction
+	let mut tx = match db.transaction(TransactionType::Read, false).await {
+		Ok(tx) => tx,
+		Err(e) => return Err(warp::reject::custom(Error::from(e))),
+	};
+	// Check the datastore is responding
+	let res = tx.health_check().await;
+	// Close the read transaction
+	let _ = tx.cancel().await;
+	// Return the round-trip duration
+	match res {
+		Ok(v) => Ok(warp::reply::with_status(format!("{:?}", v), http::StatusCode::OK)),
+		Err(e) => Err(warp::reject::custom(Error::from(e))),
+	}
+}